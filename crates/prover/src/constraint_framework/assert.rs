@@ -1,7 +1,11 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display};
+
 use num_traits::{One, Zero};
 
-use super::EvalAtRow;
-use crate::core::backend::{Backend, Column};
+use super::{Boundary, EvalAtRow};
+use crate::core::backend::{Backend, Column, CpuBackend};
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
@@ -9,18 +13,154 @@ use crate::core::pcs::TreeVec;
 use crate::core::poly::circle::{CanonicCoset, CirclePoly};
 use crate::core::utils::circle_domain_order_to_coset_order;
 
+/// The trace polynomials of a component, evaluated on the trace domains the constraints read them
+/// on, as the constraints are evaluated.
+pub struct TraceEvals {
+    polys: TreeVec<Vec<CirclePoly<CpuBackend>>>,
+    /// The values of each column on each trace domain it was read on, in row order, by
+    /// `(interaction, column index, log size)`.
+    evals: RefCell<BTreeMap<(usize, usize, u32), Vec<BaseField>>>,
+    /// The log sizes of the trace domains of the constraints seen so far.
+    domain_log_sizes: RefCell<BTreeSet<u32>>,
+}
+impl TraceEvals {
+    pub fn new<B: Backend>(trace_polys: &TreeVec<Vec<&CirclePoly<B>>>) -> Self {
+        Self {
+            polys: trace_polys
+                .clone()
+                .map_cols(|poly| CirclePoly::new(poly.coeffs.to_cpu())),
+            evals: RefCell::default(),
+            domain_log_sizes: RefCell::default(),
+        }
+    }
+
+    /// Returns the value of a column at a row of the trace domain of size `2^log_size`.
+    /// A column read on a domain smaller than its polynomial is expected to be of degree less than
+    /// the domain size, e.g. a trace column extended beyond its trace domain.
+    fn value(&self, interaction: usize, col_index: usize, log_size: u32, row: usize) -> BaseField {
+        let mut evals = self.evals.borrow_mut();
+        let values = evals
+            .entry((interaction, col_index, log_size))
+            .or_insert_with(|| {
+                let poly = &self.polys[interaction][col_index];
+                let poly = match poly.log_size() > log_size {
+                    true => CirclePoly::new(poly.coeffs[..1 << log_size].to_vec()),
+                    false => poly.extend(log_size),
+                };
+                let domain = CanonicCoset::new(log_size).circle_domain();
+                circle_domain_order_to_coset_order(&poly.evaluate(domain).bit_reverse().values)
+            });
+        values[row]
+    }
+
+    /// Calls `eval_row` with every row of every trace domain with constraints, and the log size of
+    /// that domain, starting with the domain of size `2^log_size`. The domains are found as the
+    /// constraints are evaluated.
+    fn for_each_row(&self, log_size: u32, mut eval_row: impl FnMut(usize, u32)) {
+        self.domain_log_sizes.borrow_mut().insert(log_size);
+        let mut checked_log_sizes = BTreeSet::new();
+        loop {
+            let unchecked_log_size = self
+                .domain_log_sizes
+                .borrow()
+                .difference(&checked_log_sizes)
+                .next()
+                .copied();
+            let Some(row_log_size) = unchecked_log_size else {
+                break;
+            };
+            for row in 0..1 << row_log_size {
+                eval_row(row, row_log_size);
+            }
+            checked_log_sizes.insert(row_log_size);
+        }
+    }
+}
+
 /// Evaluates expressions at a trace domain row, and asserts constraints. Mainly used for testing.
 pub struct AssertEvaluator<'a> {
-    pub trace: &'a TreeVec<Vec<Vec<BaseField>>>,
+    pub trace: &'a TraceEvals,
     pub col_index: TreeVec<usize>,
     pub row: usize,
+    /// The log size of the trace domain of `row`. Only the constraints on that domain are
+    /// asserted.
+    pub row_log_size: u32,
+    /// The log size of the current trace domain.
+    pub log_size: u32,
+    /// The index of the next constraint, in evaluation order.
+    pub constraint_index: usize,
+    /// The mask values read so far at the row, for each interaction.
+    pub mask_values: TreeVec<Vec<BaseField>>,
+    /// When set, unsatisfied constraints are collected here instead of panicking.
+    pub failures: Option<Vec<ConstraintFailure>>,
 }
 impl<'a> AssertEvaluator<'a> {
-    pub fn new(trace: &'a TreeVec<Vec<Vec<BaseField>>>, row: usize) -> Self {
+    pub fn new(trace: &'a TraceEvals, row: usize, row_log_size: u32, log_size: u32) -> Self {
         Self {
             trace,
-            col_index: TreeVec::new(vec![0; trace.len()]),
+            col_index: TreeVec::new(vec![0; trace.polys.len()]),
             row,
+            row_log_size,
+            log_size,
+            constraint_index: 0,
+            mask_values: TreeVec::new(vec![vec![]; trace.polys.len()]),
+            failures: None,
+        }
+    }
+
+    /// Returns an evaluator that collects the unsatisfied constraints, see [ConstraintReport].
+    pub fn new_collecting(
+        trace: &'a TraceEvals,
+        row: usize,
+        row_log_size: u32,
+        log_size: u32,
+    ) -> Self {
+        Self {
+            failures: Some(vec![]),
+            ..Self::new(trace, row, row_log_size, log_size)
+        }
+    }
+
+    /// Returns whether the constraints on the current trace domain are asserted at `row`, and
+    /// records the domain to be checked otherwise.
+    fn is_row_domain(&self) -> bool {
+        self.trace
+            .domain_log_sizes
+            .borrow_mut()
+            .insert(self.log_size);
+        self.log_size == self.row_log_size
+    }
+
+    fn check_constraint<G>(&mut self, name: Option<&str>, constraint: G)
+    where
+        SecureField: std::ops::Mul<G, Output = SecureField>,
+    {
+        let constraint_index = self.constraint_index;
+        self.constraint_index += 1;
+        if !self.is_row_domain() {
+            return;
+        }
+        // Cast to SecureField.
+        let res = SecureField::one() * constraint;
+        // The constraint should be zero at the given row, since we are evaluating on the trace
+        // domain.
+        match &mut self.failures {
+            Some(failures) if !res.is_zero() => failures.push(ConstraintFailure {
+                component: String::new(),
+                constraint_index,
+                name: name.map(str::to_string),
+                row: self.row,
+                mask_values: self.mask_values.clone(),
+                value: res,
+            }),
+            Some(_) => {}
+            None => assert_eq!(
+                res,
+                SecureField::zero(),
+                "row: {}, constraint: {}",
+                self.row,
+                name.map_or_else(|| constraint_index.to_string(), str::to_string)
+            ),
         }
     }
 }
@@ -35,23 +175,48 @@ impl EvalAtRow for AssertEvaluator<'_> {
     ) -> [Self::F; N] {
         let col_index = self.col_index[interaction];
         self.col_index[interaction] += 1;
-        offsets.map(|off| {
-            // The mask row might wrap around the column size.
-            let col_size = self.trace[interaction][col_index].len() as isize;
-            self.trace[interaction][col_index]
-                [(self.row as isize + off).rem_euclid(col_size) as usize]
-        })
+        // Columns read on another trace domain than the row's are read at the same row, wrapped
+        // around their domain size.
+        let col_size = 1 << self.log_size;
+        let values = offsets.map(|off| {
+            let row = (self.row as isize + off).rem_euclid(col_size) as usize;
+            self.trace.value(interaction, col_index, self.log_size, row)
+        });
+        self.mask_values[interaction].extend(values);
+        values
     }
 
     fn add_constraint<G>(&mut self, constraint: G)
     where
         Self::EF: std::ops::Mul<G, Output = Self::EF>,
     {
-        // Cast to SecureField.
-        let res = SecureField::one() * constraint;
-        // The constraint should be zero at the given row, since we are evaluating on the trace
-        // domain.
-        assert_eq!(res, SecureField::zero(), "row: {}", self.row);
+        self.check_constraint(None, constraint);
+    }
+
+    fn add_constraint_named<G>(&mut self, name: &str, constraint: G)
+    where
+        Self::EF: std::ops::Mul<G, Output = Self::EF>,
+    {
+        self.check_constraint(Some(name), constraint);
+    }
+
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    ) {
+        // The constraint only applies to the boundary row.
+        if self.row != boundary.row(self.log_size) {
+            self.is_row_domain();
+            self.constraint_index += 1;
+            return;
+        }
+        self.check_constraint(Some(&boundary.to_string()), value - public_value);
+    }
+
+    fn enter_domain(&mut self, log_size: u32) {
+        self.log_size = log_size;
     }
 
     fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
@@ -59,26 +224,191 @@ impl EvalAtRow for AssertEvaluator<'_> {
     }
 }
 
+/// Asserts the constraints on every row of each trace domain they are on, starting with
+/// `trace_domain`. Each column is evaluated on the trace domain it is read on.
 pub fn assert_constraints<B: Backend>(
     trace_polys: &TreeVec<Vec<CirclePoly<B>>>,
     trace_domain: CanonicCoset,
     assert_func: impl Fn(AssertEvaluator<'_>),
 ) {
-    let traces = trace_polys.as_ref().map(|tree| {
-        tree.iter()
-            .map(|poly| {
-                circle_domain_order_to_coset_order(
-                    &poly
-                        .evaluate(trace_domain.circle_domain())
-                        .bit_reverse()
-                        .values
-                        .to_cpu(),
-                )
-            })
-            .collect()
+    let traces = TraceEvals::new(&trace_polys.as_cols_ref());
+    let log_size = trace_domain.log_size();
+    traces.for_each_row(log_size, |row, row_log_size| {
+        assert_func(AssertEvaluator::new(&traces, row, row_log_size, log_size));
     });
-    for row in 0..trace_domain.size() {
-        let eval = AssertEvaluator::new(&traces, row);
-        assert_func(eval);
+}
+
+/// A constraint that is not satisfied at a row of the trace.
+#[derive(Clone, Debug)]
+pub struct ConstraintFailure {
+    pub component: String,
+    /// The index of the constraint in the component, in evaluation order.
+    pub constraint_index: usize,
+    /// The name given in [EvalAtRow::add_constraint_named], if any.
+    pub name: Option<String>,
+    pub row: usize,
+    /// The mask values read before the constraint was added, for each interaction.
+    pub mask_values: TreeVec<Vec<BaseField>>,
+    /// The value of the constraint at the row.
+    pub value: SecureField,
+}
+
+impl Display for ConstraintFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: constraint {}",
+            self.component, self.constraint_index
+        )?;
+        if let Some(name) = &self.name {
+            write!(f, " ({name})")?;
+        }
+        write!(
+            f,
+            " at row {} evaluates to {}, mask values: {:?}",
+            self.row, self.value, self.mask_values.0
+        )
+    }
+}
+
+/// The unsatisfied constraints of a set of components, over their whole trace.
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintReport {
+    pub failures: Vec<ConstraintFailure>,
+}
+
+impl ConstraintReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates the constraints of `component` on every row of each trace domain they are on,
+    /// starting with `trace_domain`, and collects the unsatisfied ones.
+    /// Each column is evaluated on the trace domain it is read on.
+    pub fn check_component<B: Backend>(
+        &mut self,
+        component: &str,
+        trace_polys: &TreeVec<Vec<&CirclePoly<B>>>,
+        trace_domain: CanonicCoset,
+        eval_func: impl Fn(AssertEvaluator<'_>) -> AssertEvaluator<'_>,
+    ) {
+        let traces = TraceEvals::new(trace_polys);
+        let log_size = trace_domain.log_size();
+        traces.for_each_row(log_size, |row, row_log_size| {
+            let eval = AssertEvaluator::new_collecting(&traces, row, row_log_size, log_size);
+            let failures = eval_func(eval).failures.unwrap_or_default();
+            self.failures
+                .extend(failures.into_iter().map(|failure| ConstraintFailure {
+                    component: component.to_string(),
+                    ..failure
+                }));
+        });
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl Display for ConstraintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "All constraints are satisfied.");
+        }
+        writeln!(f, "{} unsatisfied constraints:", self.failures.len())?;
+        for failure in &self.failures {
+            writeln!(f, "  {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use num_traits::One;
+
+    use super::ConstraintReport;
+    use crate::constraint_framework::{EvalAtRow, ORIGINAL_TRACE_IDX};
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::pcs::TreeVec;
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
+    use crate::core::poly::NaturalOrder;
+    use crate::core::utils::coset_order_to_circle_domain_order;
+
+    const LOG_SIZE: u32 = 5;
+
+    #[test]
+    fn test_constraint_report_collects_failures() {
+        let domain = CanonicCoset::new(LOG_SIZE);
+        // The second column is the square of the first, except at row 3.
+        let x = (0..1 << LOG_SIZE).map(BaseField::from).collect_vec();
+        let mut x_squared = x.iter().map(|&v| v * v).collect_vec();
+        x_squared[3] += BaseField::one();
+        let trace_polys = TreeVec::new(vec![
+            vec![],
+            [x, x_squared]
+                .into_iter()
+                .map(|values| {
+                    CircleEvaluation::<CpuBackend, _, NaturalOrder>::new(
+                        domain.circle_domain(),
+                        coset_order_to_circle_domain_order(&values),
+                    )
+                    .bit_reverse()
+                    .interpolate()
+                })
+                .collect_vec(),
+        ]);
+        let mut report = ConstraintReport::new();
+
+        report.check_component("square", &trace_polys.as_cols_ref(), domain, |mut eval| {
+            let x = eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0])[0];
+            let x_squared = eval.next_trace_mask();
+            eval.add_constraint(x - x);
+            eval.add_constraint_named("is_square", x_squared - x * x);
+            eval
+        });
+
+        let [failure] = report.failures.try_into().unwrap();
+        assert_eq!(failure.component, "square");
+        assert_eq!(failure.constraint_index, 1);
+        assert_eq!(failure.name.as_deref(), Some("is_square"));
+        assert_eq!(failure.row, 3);
+        assert_eq!(failure.mask_values[ORIGINAL_TRACE_IDX].len(), 2);
+        assert!(failure.value.is_one());
+    }
+
+    #[test]
+    fn test_constraint_report_checks_rows_of_larger_domain() {
+        // The column is committed extended beyond its trace domain, and read on a trace domain
+        // larger than the one the evaluation starts on. Its last row breaks the constraint.
+        let mut values = vec![BaseField::one(); 1 << LOG_SIZE];
+        *values.last_mut().unwrap() += BaseField::one();
+        let poly = CircleEvaluation::<CpuBackend, _, NaturalOrder>::new(
+            CanonicCoset::new(LOG_SIZE).circle_domain(),
+            coset_order_to_circle_domain_order(&values),
+        )
+        .bit_reverse()
+        .interpolate()
+        .extend(LOG_SIZE + 1);
+        let trace_polys = TreeVec::new(vec![vec![], vec![&poly]]);
+        let mut report = ConstraintReport::new();
+
+        report.check_component(
+            "ones",
+            &trace_polys,
+            CanonicCoset::new(LOG_SIZE - 1),
+            |mut eval| {
+                eval.enter_domain(LOG_SIZE);
+                let x = eval.next_trace_mask();
+                eval.add_constraint(x - BaseField::one());
+                eval
+            },
+        );
+
+        let [failure] = report.failures.try_into().unwrap();
+        assert_eq!(failure.row, (1 << LOG_SIZE) - 1);
+        assert!(failure.value.is_one());
     }
 }
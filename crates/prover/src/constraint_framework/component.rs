@@ -1,66 +1,172 @@
+use std::any;
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Deref;
 
 use itertools::Itertools;
+use num_traits::{One, Zero};
 use tracing::{span, Level};
 
-use super::{EvalAtRow, InfoEvaluator, PointEvaluator, SimdDomainEvaluator};
-use crate::core::air::accumulation::{DomainEvaluationAccumulator, PointEvaluationAccumulator};
-use crate::core::air::{Component, ComponentProver, ComponentTrace};
+use super::{
+    Boundary, ConstraintReport, CpuDomainEvaluator, EvalAtRow, InfoEvaluator, PointEvaluator,
+    SimdDomainEvaluator,
+};
+use crate::core::air::accumulation::{
+    AccumulationOps, DomainEvaluationAccumulator, PointEvaluationAccumulator,
+};
+use crate::core::air::{Component, ComponentProver, ComponentTrace, RelationClaimedSum};
 use crate::core::backend::simd::column::VeryPackedSecureColumnByCoords;
 use crate::core::backend::simd::m31::LOG_N_LANES;
-use crate::core::backend::simd::very_packed_m31::{VeryPackedBaseField, LOG_N_VERY_PACKED_ELEMS};
+use crate::core::backend::simd::very_packed_m31::LOG_N_VERY_PACKED_ELEMS;
 use crate::core::backend::simd::SimdBackend;
+use crate::core::backend::{Backend, Column, CpuBackend};
 use crate::core::circle::CirclePoint;
 use crate::core::constraints::coset_vanishing;
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SecureColumnByCoords;
 use crate::core::fields::FieldExpOps;
+use crate::core::lookups::gkr_lookups::GkrLookup;
 use crate::core::pcs::TreeVec;
-use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, PolyOps};
+use crate::core::poly::circle::{CanonicCoset, CircleDomain, CircleEvaluation, CirclePoly};
 use crate::core::poly::BitReversedOrder;
-use crate::core::{utils, ColumnVec, InteractionElements, LookupValues};
+use crate::core::{utils, ColumnVec, InteractionElements, LookupValueId, LookupValues};
 
 /// A component defined solely in means of the constraints framework.
 /// Implementing this trait introduces implementations for [Component] and [ComponentProver] for the
-/// SIMD backend.
-/// Note that the constraint framework only support components with columns of the same size.
-pub trait FrameworkComponent {
+/// SIMD and CPU backends, on the [FrameworkComponent] built from it.
+/// Columns and constraints live on the trace domain of size `2^log_size()`, unless the evaluation
+/// moves to a trace domain of another size with [EvalAtRow::enter_domain]. Trace domains must be
+/// of log size at least `LOG_N_LANES + LOG_N_VERY_PACKED_ELEMS`.
+pub trait FrameworkEval {
+    /// Returns the log size of the trace domain the evaluation starts on.
     fn log_size(&self) -> u32;
-    fn max_constraint_log_degree_bound(&self) -> u32;
+    /// Returns the log degree bound of the constraint quotients, if it differs from the bound
+    /// inferred from the degrees of the constraints, see
+    /// [InfoEvaluator::max_constraint_log_degree_bound]. A larger bound may be returned, but not a
+    /// smaller one.
+    fn max_constraint_log_degree_bound(&self) -> Option<u32> {
+        None
+    }
+    /// Returns the index in the preprocessed trace of each column read with
+    /// [EvalAtRow::get_preprocessed_column], in order. Computed when the component is built, with
+    /// [super::constant_columns::PreprocessedColumnRegistry::add_component].
+    fn preprocessed_column_indices(&self) -> ColumnVec<usize>;
+    /// Returns the sums the component claims for the lookup relations it uses. See
+    /// [crate::constraint_framework::logup::LogupAtRow].
+    fn claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        vec![]
+    }
+    /// Returns the sums of the lookups of values known to the verifier. See
+    /// [Component::public_claimed_sums].
+    fn public_claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        vec![]
+    }
+    /// Returns the ids of the lookup values the component reads.
+    fn lookup_value_ids(&self) -> Vec<LookupValueId> {
+        vec![]
+    }
+    /// Returns the lookups the component checks with [super::gkr::GkrLookupAtRow]. See
+    /// [Component::gkr_lookups].
+    fn gkr_lookups(&self) -> Vec<GkrLookup> {
+        vec![]
+    }
     fn evaluate<E: EvalAtRow>(&self, eval: E) -> E;
 }
 
-impl<C: FrameworkComponent> Component for C {
+/// The [Component] of a [FrameworkEval]. The constraints are evaluated with [InfoEvaluator] once,
+/// when the component is built, and its mask, constraint degrees and trace domains are read from
+/// the result.
+pub struct FrameworkComponent<C: FrameworkEval> {
+    eval: C,
+    info: InfoEvaluator,
+    max_constraint_log_degree_bound: u32,
+}
+
+impl<C: FrameworkEval> FrameworkComponent<C> {
+    /// # Panics
+    ///
+    /// Panics if the log degree bound returned by [FrameworkEval::max_constraint_log_degree_bound]
+    /// is smaller than the one the degrees of the constraints require.
+    pub fn new(eval: C) -> Self {
+        let info = eval.evaluate(InfoEvaluator::new(eval.log_size()));
+        let inferred_log_degree_bound = info.max_constraint_log_degree_bound();
+        let max_constraint_log_degree_bound = eval
+            .max_constraint_log_degree_bound()
+            .unwrap_or(inferred_log_degree_bound);
+        assert!(
+            max_constraint_log_degree_bound >= inferred_log_degree_bound,
+            "The constraint log degree bound {max_constraint_log_degree_bound} is smaller than the \
+            degree of the constraints, which requires {inferred_log_degree_bound}."
+        );
+        Self {
+            eval,
+            info,
+            max_constraint_log_degree_bound,
+        }
+    }
+
+    /// Returns the [FrameworkEval] the component is built from.
+    pub fn into_eval(self) -> C {
+        self.eval
+    }
+}
+
+impl<C: FrameworkEval> Deref for FrameworkComponent<C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        &self.eval
+    }
+}
+
+impl<C: FrameworkEval> Component for FrameworkComponent<C> {
     fn n_constraints(&self) -> usize {
-        self.evaluate(InfoEvaluator::default()).n_constraints
+        self.info.n_constraints
     }
 
     fn max_constraint_log_degree_bound(&self) -> u32 {
-        FrameworkComponent::max_constraint_log_degree_bound(self)
+        self.max_constraint_log_degree_bound
     }
 
     fn trace_log_degree_bounds(&self) -> TreeVec<ColumnVec<u32>> {
-        TreeVec::new(
-            self.evaluate(InfoEvaluator::default())
-                .mask_offsets
-                .iter()
-                .map(|tree_masks| vec![self.log_size(); tree_masks.len()])
-                .collect(),
-        )
+        self.info.mask_log_sizes.clone()
+    }
+
+    fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+        self.eval.preprocessed_column_indices()
+    }
+
+    fn claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        self.eval.claimed_sums()
+    }
+
+    fn public_claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        self.eval.public_claimed_sums()
+    }
+
+    fn lookup_value_ids(&self) -> Vec<LookupValueId> {
+        self.eval.lookup_value_ids()
+    }
+
+    fn gkr_lookups(&self) -> Vec<GkrLookup> {
+        self.eval.gkr_lookups()
     }
 
     fn mask_points(
         &self,
         point: CirclePoint<SecureField>,
     ) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
-        let info = self.evaluate(InfoEvaluator::default());
-        let trace_step = CanonicCoset::new(self.log_size()).step();
-        info.mask_offsets.map_cols(|col_mask| {
-            col_mask
-                .iter()
-                .map(|off| point + trace_step.mul_signed(*off).into_ef())
-                .collect()
-        })
+        self.info
+            .mask_offsets
+            .zip_cols(&self.info.mask_log_sizes)
+            .map_cols(|(col_mask, &log_size)| {
+                let trace_step = CanonicCoset::new(log_size).step();
+                col_mask
+                    .iter()
+                    .map(|off| point + trace_step.mul_signed(*off).into_ef())
+                    .collect()
+            })
     }
 
     fn evaluate_constraint_quotients_at_point(
@@ -71,15 +177,148 @@ impl<C: FrameworkComponent> Component for C {
         _interaction_elements: &InteractionElements,
         _lookup_values: &LookupValues,
     ) {
-        self.evaluate(PointEvaluator::new(
+        self.eval.evaluate(PointEvaluator::new(
             mask.as_ref(),
             evaluation_accumulator,
-            coset_vanishing(CanonicCoset::new(self.log_size()).coset, point).inverse(),
+            point,
+            self.eval.log_size(),
         ));
     }
 }
 
-impl<C: FrameworkComponent> ComponentProver<SimdBackend> for C {
+/// Returns the domain of log size `eval_log_size` to evaluate the constraints on, and the trace
+/// evaluated on it.
+/// When all the columns are committed on the same domain, of at least that size, the constraints
+/// are evaluated on its subdomain of the first `2^eval_log_size` bit-reversed points, and the
+/// committed evaluations are read as is. Otherwise, the columns are extended to the canonic
+/// domain.
+fn trace_on_eval_domain<'a, B: Backend>(
+    trace: &ComponentTrace<'a, B>,
+    eval_log_size: u32,
+) -> (
+    CircleDomain,
+    TreeVec<Vec<Cow<'a, CircleEvaluation<B, BaseField, BitReversedOrder>>>>,
+) {
+    let committed_domain = trace
+        .evals
+        .iter()
+        .flatten()
+        .map(|c| c.domain)
+        .all_equal_value();
+    if let Ok(committed_domain) = committed_domain {
+        if committed_domain.log_size() >= eval_log_size {
+            let (eval_domain, _) =
+                committed_domain.split(committed_domain.log_size() - eval_log_size);
+            return (
+                eval_domain,
+                trace.evals.as_cols_ref().map_cols(|c| Cow::Borrowed(*c)),
+            );
+        }
+    }
+
+    let _span = span!(Level::INFO, "Extension").entered();
+    let eval_domain = CanonicCoset::new(eval_log_size).circle_domain();
+    let twiddles = B::precompute_twiddles(eval_domain.half_coset);
+    (
+        eval_domain,
+        trace
+            .polys
+            .as_cols_ref()
+            .map_cols(|col| Cow::Owned(col.evaluate_with_twiddles(eval_domain, &twiddles))),
+    )
+}
+
+/// Adds `values`, the evaluation of a polynomial on `domain`, to `col`, which holds evaluations on
+/// the canonic domain of the same size.
+fn accumulate_from_domain<B: Backend>(
+    col: &mut SecureColumnByCoords<B>,
+    domain: CircleDomain,
+    values: SecureColumnByCoords<B>,
+) {
+    let _span = span!(Level::INFO, "Constraint domain change").entered();
+    let canonic_domain = CanonicCoset::new(domain.log_size()).circle_domain();
+    let twiddles = B::precompute_twiddles(domain.half_coset);
+    let canonic_twiddles = B::precompute_twiddles(canonic_domain.half_coset);
+    let canonic_values = SecureColumnByCoords {
+        columns: values.columns.map(|c| {
+            CircleEvaluation::<B, BaseField, BitReversedOrder>::new(domain, c)
+                .interpolate_with_twiddles(&twiddles)
+                .evaluate_with_twiddles(canonic_domain, &canonic_twiddles)
+                .values
+        }),
+    };
+    B::accumulate(col, &canonic_values);
+}
+
+/// Returns the inverses of the vanishing polynomial of each trace domain used by `component` on
+/// `eval_domain`, by log size. See [SimdDomainEvaluator::denom_inv].
+fn denom_inverses<C: FrameworkEval>(
+    component: &FrameworkComponent<C>,
+    eval_domain: CircleDomain,
+) -> BTreeMap<u32, Vec<BaseField>> {
+    let mut domain_log_sizes = component.info.domain_log_sizes();
+    domain_log_sizes.insert(component.eval.log_size());
+    domain_log_sizes
+        .into_iter()
+        .map(|log_size| {
+            let domain = CanonicCoset::new(log_size);
+            let log_expand = eval_domain.log_size() - log_size;
+            let mut denom_inv = (0..1 << log_expand)
+                .map(|i| coset_vanishing(domain.coset(), eval_domain.at(i)).inverse())
+                .collect_vec();
+            utils::bit_reverse(&mut denom_inv);
+            (log_size, denom_inv)
+        })
+        .collect()
+}
+
+/// Returns the inverses of the vanishing polynomial of the point of each boundary constraint of
+/// `component` on `eval_domain`, by trace domain log size and boundary. See
+/// [SimdDomainEvaluator::boundary_denom_inv].
+fn boundary_denom_inverses<C: FrameworkEval>(
+    component: &FrameworkComponent<C>,
+    eval_domain: CircleDomain,
+) -> BTreeMap<(u32, Boundary), Vec<BaseField>> {
+    component
+        .info
+        .boundaries()
+        .into_iter()
+        .map(|(log_size, boundary)| {
+            // The inverse of `point_vanishing(point, p)` is `(1 + h.x) / h.y`, for `h = p - point`.
+            let point = boundary.point(log_size);
+            let shifted_points = eval_domain.iter().map(|p| p - point).collect_vec();
+            let ys = shifted_points.iter().map(|h| h.y).collect_vec();
+            let mut denom_inv = vec![BaseField::zero(); ys.len()];
+            BaseField::batch_inverse(&ys, &mut denom_inv);
+            for (denom_inv, h) in denom_inv.iter_mut().zip(&shifted_points) {
+                *denom_inv *= BaseField::one() + h.x;
+            }
+            utils::bit_reverse(&mut denom_inv);
+            ((log_size, boundary), denom_inv)
+        })
+        .collect()
+}
+
+/// Checks the constraints of `component` on every row of its trace. See
+/// [ComponentProver::check_constraints_on_trace].
+fn check_component_constraints_on_trace<B: Backend, C: FrameworkEval>(
+    component: &C,
+    trace_polys: &TreeVec<ColumnVec<&CirclePoly<B>>>,
+) -> Result<(), String> {
+    let mut report = ConstraintReport::new();
+    report.check_component(
+        any::type_name::<C>(),
+        trace_polys,
+        CanonicCoset::new(component.log_size()),
+        |eval| component.evaluate(eval),
+    );
+    match report.is_ok() {
+        true => Ok(()),
+        false => Err(report.to_string()),
+    }
+}
+
+impl<C: FrameworkEval> ComponentProver<SimdBackend> for FrameworkComponent<C> {
     fn evaluate_constraint_quotients_on_domain(
         &self,
         trace: &ComponentTrace<'_, SimdBackend>,
@@ -87,44 +326,35 @@ impl<C: FrameworkComponent> ComponentProver<SimdBackend> for C {
         _interaction_elements: &InteractionElements,
         _lookup_values: &LookupValues,
     ) {
-        let eval_domain = CanonicCoset::new(self.max_constraint_log_degree_bound()).circle_domain();
-        let trace_domain = CanonicCoset::new(self.log_size());
-
-        // Extend trace if necessary.
-        // TODO(spapini): Don't extend when eval_size < committed_size. Instead, pick a good
-        // subdomain.
-        let need_to_extend = trace
-            .evals
-            .iter()
-            .flatten()
-            .any(|c| c.domain != eval_domain);
-        let trace: TreeVec<
-            Vec<Cow<'_, CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>>,
-        > = if need_to_extend {
-            let _span = span!(Level::INFO, "Extension").entered();
-            let twiddles = SimdBackend::precompute_twiddles(eval_domain.half_coset);
-            trace
-                .polys
-                .as_cols_ref()
-                .map_cols(|col| Cow::Owned(col.evaluate_with_twiddles(eval_domain, &twiddles)))
-        } else {
-            trace.evals.as_cols_ref().map_cols(|c| Cow::Borrowed(*c))
-        };
+        let trace_domain = CanonicCoset::new(self.eval.log_size());
 
-        // Denom inverses.
-        let log_expand = eval_domain.log_size() - trace_domain.log_size();
-        let mut denom_inv = (0..1 << log_expand)
-            .map(|i| coset_vanishing(trace_domain.coset(), eval_domain.at(i)).inverse())
-            .collect_vec();
-        utils::bit_reverse(&mut denom_inv);
+        // Pick the evaluation domain, extending the trace if necessary.
+        let (eval_domain, trace) =
+            trace_on_eval_domain(trace, self.max_constraint_log_degree_bound);
+
+        // Denom inverses, for each trace domain.
+        let denom_inv = denom_inverses(self, eval_domain);
+        assert!(denom_inv
+            .keys()
+            .all(|&log_size| log_size >= LOG_N_LANES + LOG_N_VERY_PACKED_ELEMS));
+        let boundary_denom_inv = boundary_denom_inverses(self, eval_domain);
 
         // Accumulator.
         let [mut accum] =
             evaluation_accumulator.columns([(eval_domain.log_size(), self.n_constraints())]);
         accum.random_coeff_powers.reverse();
 
-        let _span = span!(Level::INFO, "Constraint pointwise eval").entered();
-        let col = unsafe { VeryPackedSecureColumnByCoords::transform_under_mut(accum.col) };
+        // Constraints evaluated on a subdomain of the committed domain are moved to the canonic
+        // domain after the evaluation.
+        let mut subdomain_col = (!eval_domain.is_canonic())
+            .then(|| SecureColumnByCoords::<SimdBackend>::zeros(eval_domain.size()));
+
+        let span = span!(Level::INFO, "Constraint pointwise eval").entered();
+        let col = unsafe {
+            VeryPackedSecureColumnByCoords::transform_under_mut(
+                subdomain_col.as_mut().unwrap_or(&mut *accum.col),
+            )
+        };
 
         for vec_row in 0..(1 << (eval_domain.log_size() - LOG_N_LANES - LOG_N_VERY_PACKED_ELEMS)) {
             let trace_cols = trace.as_cols_ref().map_cols(|c| c.as_ref());
@@ -136,21 +366,798 @@ impl<C: FrameworkComponent> ComponentProver<SimdBackend> for C {
                 &accum.random_coeff_powers,
                 trace_domain.log_size(),
                 eval_domain.log_size(),
+                &denom_inv,
+                &boundary_denom_inv,
             );
-            let row_res = self.evaluate(eval).row_res;
+            let row_res = self.eval.evaluate(eval).finalize();
 
             // Finalize row.
-            unsafe {
-                let denom_inv = VeryPackedBaseField::broadcast(
-                    denom_inv[vec_row
-                        >> (trace_domain.log_size() - LOG_N_LANES - LOG_N_VERY_PACKED_ELEMS)],
-                );
-                col.set_packed(vec_row, col.packed_at(vec_row) + row_res * denom_inv)
-            }
+            unsafe { col.set_packed(vec_row, col.packed_at(vec_row) + row_res) }
+        }
+        span.exit();
+
+        if let Some(subdomain_col) = subdomain_col {
+            accumulate_from_domain(accum.col, eval_domain, subdomain_col);
         }
     }
 
     fn lookup_values(&self, _trace: &ComponentTrace<'_, SimdBackend>) -> LookupValues {
         LookupValues::default()
     }
+
+    fn check_constraints_on_trace(
+        &self,
+        trace_polys: &TreeVec<ColumnVec<&CirclePoly<SimdBackend>>>,
+    ) -> Result<(), String> {
+        check_component_constraints_on_trace(&self.eval, trace_polys)
+    }
+}
+
+impl<C: FrameworkEval> ComponentProver<CpuBackend> for FrameworkComponent<C> {
+    fn evaluate_constraint_quotients_on_domain(
+        &self,
+        trace: &ComponentTrace<'_, CpuBackend>,
+        evaluation_accumulator: &mut DomainEvaluationAccumulator<CpuBackend>,
+        _interaction_elements: &InteractionElements,
+        _lookup_values: &LookupValues,
+    ) {
+        let trace_domain = CanonicCoset::new(self.eval.log_size());
+
+        // Pick the evaluation domain, extending the trace if necessary.
+        let (eval_domain, trace) =
+            trace_on_eval_domain(trace, self.max_constraint_log_degree_bound);
+
+        // Denom inverses, for each trace domain.
+        let denom_inv = denom_inverses(self, eval_domain);
+        let boundary_denom_inv = boundary_denom_inverses(self, eval_domain);
+
+        // Accumulator.
+        let [mut accum] =
+            evaluation_accumulator.columns([(eval_domain.log_size(), self.n_constraints())]);
+        accum.random_coeff_powers.reverse();
+
+        // Constraints evaluated on a subdomain of the committed domain are moved to the canonic
+        // domain after the evaluation.
+        let mut subdomain_col = (!eval_domain.is_canonic())
+            .then(|| SecureColumnByCoords::<CpuBackend>::zeros(eval_domain.size()));
+
+        let span = span!(Level::INFO, "Constraint pointwise eval").entered();
+        let col = subdomain_col.as_mut().unwrap_or(&mut *accum.col);
+        let trace_cols = trace.as_cols_ref().map_cols(|c| c.as_ref());
+
+        for row in 0..eval_domain.size() {
+            // Evaluate constrains at row.
+            let eval = CpuDomainEvaluator::new(
+                &trace_cols,
+                row,
+                &accum.random_coeff_powers,
+                trace_domain.log_size(),
+                eval_domain.log_size(),
+                &denom_inv,
+                &boundary_denom_inv,
+            );
+            let row_res = self.eval.evaluate(eval).finalize();
+
+            // Finalize row.
+            col.set(row, col.at(row) + row_res);
+        }
+        span.exit();
+
+        if let Some(subdomain_col) = subdomain_col {
+            accumulate_from_domain(accum.col, eval_domain, subdomain_col);
+        }
+    }
+
+    fn lookup_values(&self, _trace: &ComponentTrace<'_, CpuBackend>) -> LookupValues {
+        LookupValues::default()
+    }
+
+    fn check_constraints_on_trace(
+        &self,
+        trace_polys: &TreeVec<ColumnVec<&CirclePoly<CpuBackend>>>,
+    ) -> Result<(), String> {
+        check_component_constraints_on_trace(&self.eval, trace_polys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+    use std::cell::Cell;
+    use std::collections::BTreeMap;
+
+    use itertools::{chain, Itertools};
+    use num_traits::One;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::constraint_framework::constant_columns::{
+        PreprocessedColumn, PreprocessedColumnRegistry,
+    };
+    use crate::constraint_framework::{
+        assert_constraints, Boundary, EvalAtRow, FrameworkComponent, FrameworkEval,
+        ORIGINAL_TRACE_IDX,
+    };
+    use crate::core::air::accumulation::DomainEvaluationAccumulator;
+    use crate::core::air::{Component, ComponentProver, ComponentTrace, Components};
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::{BackendForChannel, Column, CpuBackend};
+    use crate::core::channel::Blake3Channel;
+    use crate::core::circle::CirclePoint;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::FieldExpOps;
+    use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig, TreeVec};
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, PolyOps};
+    use crate::core::poly::BitReversedOrder;
+    use crate::core::prover::{
+        check_constraints_on_trace, prove, verify, Phase, ProvingError, Session, StarkProof,
+        VerificationError,
+    };
+    use crate::core::test_utils::test_pcs_config;
+    use crate::core::utils::{bit_reverse, coset_order_to_circle_domain_order};
+    use crate::core::vcs::blake3_merkle::{Blake3MerkleChannel, Blake3MerkleHasher};
+    use crate::core::{ColumnVec, InteractionElements, LookupValues, PublicInputId, Statement};
+    use crate::qm31;
+
+    const LOG_SIZE: u32 = 7;
+    const SMALL_LOG_SIZE: u32 = 5;
+
+    /// A component with a squaring constraint on its main trace domain, and a running sum
+    /// constraint on a smaller trace domain.
+    struct MixedSizesEval;
+
+    impl FrameworkEval for MixedSizesEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            vec![]
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let x = eval.next_trace_mask();
+            let x_squared = eval.next_trace_mask();
+            eval.add_constraint(x_squared - x.square());
+
+            eval.enter_domain(SMALL_LOG_SIZE);
+            let [sum, prev_sum] = eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0, -1]);
+            let value = eval.next_trace_mask();
+            eval.add_constraint(sum - prev_sum - value);
+            eval
+        }
+    }
+
+    /// A component with a squaring constraint on a trace domain of the given size.
+    struct SquaresEval {
+        log_size: u32,
+    }
+
+    impl FrameworkEval for SquaresEval {
+        fn log_size(&self) -> u32 {
+            self.log_size
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            vec![]
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let x = eval.next_trace_mask();
+            let x_squared = eval.next_trace_mask();
+            eval.add_constraint(x_squared - x.square());
+            eval
+        }
+    }
+
+    /// A component with a squaring constraint, which counts its evaluations.
+    struct CountingEval {
+        n_evaluations: Cell<usize>,
+    }
+
+    impl FrameworkEval for CountingEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            vec![]
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            self.n_evaluations.set(self.n_evaluations.get() + 1);
+            let x = eval.next_trace_mask();
+            let x_squared = eval.next_trace_mask();
+            eval.add_constraint(x_squared - x.square());
+            eval
+        }
+    }
+
+    /// A component with a constraint of degree 4, with a degree bound that only fits degree 3.
+    struct UnderestimatedBoundEval;
+
+    impl FrameworkEval for UnderestimatedBoundEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn max_constraint_log_degree_bound(&self) -> Option<u32> {
+            Some(LOG_SIZE + 1)
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            vec![]
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let x = eval.next_trace_mask();
+            eval.add_constraint(x.square().square() - x);
+            eval
+        }
+    }
+
+    const FIRST_X: &str = "first_x";
+    const LAST_X_SQUARED: &str = "last_x_squared";
+
+    /// A component with a squaring constraint, whose first input and last output are public.
+    struct BoundarySquaresEval {
+        first_x: BaseField,
+        last_x_squared: BaseField,
+    }
+
+    impl BoundarySquaresEval {
+        fn from_statement(statement: &Statement) -> Self {
+            Self {
+                first_x: statement.get(&PublicInputId(FIRST_X.to_string())).unwrap(),
+                last_x_squared: statement
+                    .get(&PublicInputId(LAST_X_SQUARED.to_string()))
+                    .unwrap(),
+            }
+        }
+    }
+
+    impl FrameworkEval for BoundarySquaresEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            vec![]
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let x = eval.next_trace_mask();
+            let x_squared = eval.next_trace_mask();
+            eval.add_constraint(x_squared.clone() - x.square());
+            eval.add_boundary_constraint(Boundary::First, x, self.first_x);
+            eval.add_boundary_constraint(Boundary::Last, x_squared, self.last_x_squared);
+            eval
+        }
+    }
+
+    /// A component whose column starts with `first_x`, checked with a shared preprocessed column.
+    struct FirstValueEval {
+        first_x: BaseField,
+        preprocessed_column_indices: ColumnVec<usize>,
+    }
+
+    impl FirstValueEval {
+        fn new(first_x: BaseField, registry: &mut PreprocessedColumnRegistry) -> Self {
+            let mut component = Self {
+                first_x,
+                preprocessed_column_indices: vec![],
+            };
+            component.preprocessed_column_indices = registry.add_component(&component);
+            component
+        }
+    }
+
+    impl FrameworkEval for FirstValueEval {
+        fn log_size(&self) -> u32 {
+            LOG_SIZE
+        }
+
+        fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+            self.preprocessed_column_indices.clone()
+        }
+
+        fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+            let is_first = eval.get_preprocessed_column(PreprocessedColumn::IsFirst(LOG_SIZE));
+            let x = eval.next_trace_mask();
+            eval.add_constraint(is_first * (x - E::F::from(self.first_x)));
+            eval
+        }
+    }
+
+    /// Returns the trace of [BoundarySquaresEval], and the statement it satisfies.
+    fn gen_boundary_trace() -> (
+        Statement,
+        Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    ) {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let x: Vec<BaseField> = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect();
+        let x_squared: Vec<BaseField> = x.iter().map(|&v| v * v).collect();
+        let statement = Statement::new(BTreeMap::from([
+            (PublicInputId(FIRST_X.to_string()), x[0]),
+            (
+                PublicInputId(LAST_X_SQUARED.to_string()),
+                *x_squared.last().unwrap(),
+            ),
+        ]));
+        let trace = [x, x_squared]
+            .into_iter()
+            .map(|coset_order_values| {
+                let mut values = coset_order_to_circle_domain_order(&coset_order_values);
+                bit_reverse(&mut values);
+                CircleEvaluation::new(
+                    CanonicCoset::new(LOG_SIZE).circle_domain(),
+                    values.into_iter().collect(),
+                )
+            })
+            .collect();
+        (statement, trace)
+    }
+
+    /// Returns the trace of a [SquaresEval] of the given size.
+    fn gen_squares_trace(
+        log_size: u32,
+    ) -> Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        let mut rng = SmallRng::seed_from_u64(log_size as u64);
+        let x: Vec<BaseField> = (0..1 << log_size).map(|_| rng.gen()).collect();
+        let x_squared = x.iter().map(|&v| v * v).collect();
+        [x, x_squared]
+            .into_iter()
+            .map(|coset_order_values| {
+                let mut values = coset_order_to_circle_domain_order(&coset_order_values);
+                bit_reverse(&mut values);
+                CircleEvaluation::new(
+                    CanonicCoset::new(log_size).circle_domain(),
+                    values.into_iter().collect(),
+                )
+            })
+            .collect()
+    }
+
+    fn gen_trace() -> Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>> {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let x: Vec<BaseField> = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect();
+        let x_squared = x.iter().map(|&v| v * v).collect();
+        let sum: Vec<BaseField> = (0..1 << SMALL_LOG_SIZE).map(|_| rng.gen()).collect();
+        let value = (0..sum.len())
+            .map(|i| sum[i] - sum[(i + sum.len() - 1) % sum.len()])
+            .collect();
+        [x, x_squared, sum, value]
+            .into_iter()
+            .map(|coset_order_values| {
+                let log_size = coset_order_values.len().ilog2();
+                let mut values = coset_order_to_circle_domain_order(&coset_order_values);
+                bit_reverse(&mut values);
+                CircleEvaluation::new(
+                    CanonicCoset::new(log_size).circle_domain(),
+                    values.into_iter().collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mixed_sizes_infers_log_degree_bound() {
+        assert_eq!(
+            FrameworkComponent::new(MixedSizesEval).max_constraint_log_degree_bound(),
+            LOG_SIZE + 1
+        );
+    }
+
+    #[test]
+    fn test_component_info_is_evaluated_once() {
+        let component = FrameworkComponent::new(CountingEval {
+            n_evaluations: Cell::new(0),
+        });
+
+        component.n_constraints();
+        component.max_constraint_log_degree_bound();
+        component.trace_log_degree_bounds();
+        component.mask_points(CirclePoint::zero());
+
+        assert_eq!(component.n_evaluations.get(), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "is smaller than the degree of the constraints")]
+    fn test_underestimated_log_degree_bound_panics() {
+        FrameworkComponent::new(UnderestimatedBoundEval);
+    }
+
+    #[test]
+    fn test_mixed_sizes_constraints() {
+        let trace_polys = TreeVec::new(vec![
+            vec![],
+            gen_trace()
+                .into_iter()
+                .map(|eval| eval.interpolate())
+                .collect_vec(),
+        ]);
+
+        assert_constraints(&trace_polys, CanonicCoset::new(LOG_SIZE), |eval| {
+            MixedSizesEval.evaluate(eval);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mixed_sizes_constraints_fail_on_small_domain() {
+        let mut trace = gen_trace();
+        let value = &mut trace.last_mut().unwrap().values;
+        value.set(0, value.at(0) + BaseField::one());
+        let trace_polys = TreeVec::new(vec![
+            vec![],
+            trace
+                .into_iter()
+                .map(|eval| eval.interpolate())
+                .collect_vec(),
+        ]);
+
+        assert_constraints(&trace_polys, CanonicCoset::new(LOG_SIZE), |eval| {
+            MixedSizesEval.evaluate(eval);
+        });
+    }
+
+    #[test]
+    fn test_mixed_sizes_quotients_on_committed_subdomain() {
+        let log_degree_bound = LOG_SIZE + 1;
+        let polys = gen_trace()
+            .into_iter()
+            .map(|eval| eval.interpolate().extend(log_degree_bound))
+            .collect_vec();
+        let component = FrameworkComponent::new(MixedSizesEval);
+        let quotients = |commitment_log_size: u32| {
+            let domain = CanonicCoset::new(commitment_log_size).circle_domain();
+            let evals = polys.iter().map(|poly| poly.evaluate(domain)).collect_vec();
+            let trace = ComponentTrace::new(
+                TreeVec::new(vec![vec![], polys.iter().collect()]),
+                TreeVec::new(vec![vec![], evals.iter().collect()]),
+            );
+            let mut accum = DomainEvaluationAccumulator::<SimdBackend>::new(
+                qm31!(2, 3, 4, 5),
+                log_degree_bound,
+                component.n_constraints(),
+            );
+            component.evaluate_constraint_quotients_on_domain(
+                &trace,
+                &mut accum,
+                &InteractionElements::default(),
+                &LookupValues::default(),
+            );
+            accum.finalize().0.map(|poly| poly.coeffs.to_cpu())
+        };
+
+        // Columns committed on a larger domain are read on its subdomain, without extension.
+        assert_eq!(quotients(log_degree_bound + 2), quotients(log_degree_bound));
+    }
+
+    fn prove_mixed_sizes<B: BackendForChannel<Blake3MerkleChannel>>(
+        config: PcsConfig,
+        trace: Vec<CircleEvaluation<B, BaseField, BitReversedOrder>>,
+    ) -> StarkProof<Blake3MerkleHasher>
+    where
+        FrameworkComponent<MixedSizesEval>: ComponentProver<B>,
+    {
+        let log_degree_bound = LOG_SIZE + 1;
+        let twiddles = B::precompute_twiddles(
+            CanonicCoset::new(log_degree_bound + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<B, Blake3MerkleChannel>::new(config, &twiddles);
+        // The component reads no preprocessed columns.
+        commitment_scheme.tree_builder().commit(channel);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace, log_degree_bound);
+        tree_builder.commit(channel);
+        prove::<B, Blake3MerkleChannel>(
+            &[&FrameworkComponent::new(MixedSizesEval)],
+            channel,
+            &Statement::default(),
+            None,
+            &InteractionElements::default(),
+            commitment_scheme,
+        )
+        .unwrap()
+    }
+
+    /// Verifies a proof of components without interaction, whose trees are laid out as the
+    /// components describe.
+    fn verify_components(
+        components: &[&dyn Component],
+        config: PcsConfig,
+        statement: &Statement,
+        proof: StarkProof<Blake3MerkleHasher>,
+    ) -> Result<(), VerificationError> {
+        let session = Session::new(
+            Components(components.to_vec())
+                .column_log_sizes()
+                .0
+                .into_iter()
+                .map(|column_log_sizes| Phase::new(column_log_sizes, vec![]))
+                .collect(),
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
+        let challenges = session.commit_verifier(statement, channel, commitment_scheme, &proof)?;
+        verify(
+            components,
+            channel,
+            &challenges.interaction_elements,
+            commitment_scheme,
+            proof,
+        )
+    }
+
+    fn verify_mixed_sizes(config: PcsConfig, proof: StarkProof<Blake3MerkleHasher>) {
+        verify_components(
+            &[&FrameworkComponent::new(MixedSizesEval)],
+            config,
+            &Statement::default(),
+            proof,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_mixed_sizes_prove_and_verify() {
+        let config = test_pcs_config();
+
+        let proof = prove_mixed_sizes::<SimdBackend>(config, gen_trace());
+
+        verify_mixed_sizes(config, proof);
+    }
+
+    #[test]
+    fn test_mixed_sizes_prove_and_verify_cpu() {
+        let config = test_pcs_config();
+        let cpu_trace = gen_trace()
+            .into_iter()
+            .map(|eval| CircleEvaluation::new(eval.domain, eval.values.to_cpu()))
+            .collect();
+
+        let cpu_proof = prove_mixed_sizes::<CpuBackend>(config, cpu_trace);
+        let simd_proof = prove_mixed_sizes::<SimdBackend>(config, gen_trace());
+
+        // Both backends compute the same composition polynomial, hence the same proof.
+        assert_eq!(cpu_proof.commitments.0, simd_proof.commitments.0);
+        verify_mixed_sizes(config, cpu_proof);
+    }
+
+    fn prove_boundary_squares(
+        config: PcsConfig,
+        statement: &Statement,
+        trace: Vec<CircleEvaluation<SimdBackend, BaseField, BitReversedOrder>>,
+    ) -> Result<StarkProof<Blake3MerkleHasher>, ProvingError> {
+        let component = FrameworkComponent::new(BoundarySquaresEval::from_statement(statement));
+        let log_degree_bound = Component::max_constraint_log_degree_bound(&component);
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(log_degree_bound + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<SimdBackend, Blake3MerkleChannel>::new(config, &twiddles);
+        statement.mix_into(channel);
+        commitment_scheme.tree_builder().commit(channel);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace, log_degree_bound);
+        tree_builder.commit(channel);
+        prove::<SimdBackend, Blake3MerkleChannel>(
+            &[&component],
+            channel,
+            statement,
+            None,
+            &InteractionElements::default(),
+            commitment_scheme,
+        )
+    }
+
+    fn verify_boundary_squares(
+        config: PcsConfig,
+        statement: &Statement,
+        proof: StarkProof<Blake3MerkleHasher>,
+    ) -> Result<(), VerificationError> {
+        let component = FrameworkComponent::new(BoundarySquaresEval::from_statement(statement));
+        verify_components(&[&component], config, statement, proof)
+    }
+
+    #[test]
+    fn test_boundary_constraints_prove_and_verify() {
+        let config = test_pcs_config();
+        let (statement, trace) = gen_boundary_trace();
+
+        let proof = prove_boundary_squares(config, &statement, trace).unwrap();
+
+        assert_eq!(proof.statement, statement);
+        verify_boundary_squares(config, &statement, proof).unwrap();
+    }
+
+    #[test]
+    fn test_boundary_constraints_reject_other_statement() {
+        let config = test_pcs_config();
+        let (statement, trace) = gen_boundary_trace();
+        let mut proof = prove_boundary_squares(config, &statement, trace).unwrap();
+        let mut other_statement = statement;
+        *other_statement
+            .0
+            .get_mut(&PublicInputId(LAST_X_SQUARED.to_string()))
+            .unwrap() += BaseField::one();
+
+        let result = verify_boundary_squares(config, &other_statement, proof.clone());
+        assert_matches!(result, Err(VerificationError::StatementMismatch));
+
+        // A proof claiming the other statement is still bound to its trace.
+        proof.statement = other_statement.clone();
+        let result = verify_boundary_squares(config, &other_statement, proof);
+        assert_matches!(result, Err(VerificationError::OodsNotMatching));
+    }
+
+    #[test]
+    fn test_boundary_constraints_prove_fails_on_wrong_public_input() {
+        let config = test_pcs_config();
+        let (mut statement, trace) = gen_boundary_trace();
+        *statement
+            .0
+            .get_mut(&PublicInputId(FIRST_X.to_string()))
+            .unwrap() += BaseField::one();
+
+        let result = prove_boundary_squares(config, &statement, trace);
+
+        assert_matches!(
+            result,
+            Err(ProvingError::ConstraintsNotSatisfied { .. }
+                | ProvingError::TraceConstraintsNotSatisfied { .. })
+        );
+    }
+
+    #[test]
+    fn test_shared_preprocessed_column_is_committed_once() {
+        let config = test_pcs_config();
+        let mut registry = PreprocessedColumnRegistry::new();
+        let first = FrameworkComponent::new(FirstValueEval::new(BaseField::from(3), &mut registry));
+        let second =
+            FrameworkComponent::new(FirstValueEval::new(BaseField::from(5), &mut registry));
+        let mut rng = SmallRng::seed_from_u64(0);
+        let trace = [first.first_x, second.first_x]
+            .into_iter()
+            .map(|first_x| {
+                let mut values = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect_vec();
+                values[0] = first_x;
+                let mut values = coset_order_to_circle_domain_order(&values);
+                bit_reverse(&mut values);
+                CircleEvaluation::<SimdBackend, _, BitReversedOrder>::new(
+                    CanonicCoset::new(LOG_SIZE).circle_domain(),
+                    values.into_iter().collect(),
+                )
+            })
+            .collect_vec();
+        let log_degree_bound = LOG_SIZE + 1;
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(log_degree_bound + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<SimdBackend, Blake3MerkleChannel>::new(config, &twiddles);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(
+            registry.gen_trace(|_| unreachable!("No custom columns.")),
+            log_degree_bound,
+        );
+        tree_builder.commit(channel);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace, log_degree_bound);
+        tree_builder.commit(channel);
+        let proof = prove::<SimdBackend, Blake3MerkleChannel>(
+            &[&first, &second],
+            channel,
+            &Statement::default(),
+            None,
+            &InteractionElements::default(),
+            commitment_scheme,
+        )
+        .unwrap();
+
+        assert_eq!(registry.len(), 1);
+        assert_eq!(first.preprocessed_column_indices, [0]);
+        assert_eq!(second.preprocessed_column_indices, [0]);
+        verify_components(&[&first, &second], config, &Statement::default(), proof).unwrap();
+    }
+
+    #[test]
+    fn test_components_of_mixed_sizes_prove_and_verify() {
+        let config = test_pcs_config();
+        let large = FrameworkComponent::new(SquaresEval { log_size: LOG_SIZE });
+        let small = FrameworkComponent::new(SquaresEval {
+            log_size: SMALL_LOG_SIZE,
+        });
+        let components = Components(vec![&large, &small]);
+        let log_degree_bound = components.composition_log_degree_bound();
+        assert_eq!(log_degree_bound, LOG_SIZE + 1);
+        // The columns of the small component are committed extended to the composition bound, as
+        // FRI folds a single column size.
+        assert_eq!(
+            components.column_log_sizes()[ORIGINAL_TRACE_IDX],
+            [log_degree_bound; 4]
+        );
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(log_degree_bound + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<SimdBackend, Blake3MerkleChannel>::new(config, &twiddles);
+        commitment_scheme.tree_builder().commit(channel);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(
+            chain!(
+                gen_squares_trace(LOG_SIZE),
+                gen_squares_trace(SMALL_LOG_SIZE)
+            )
+            .collect(),
+            log_degree_bound,
+        );
+        tree_builder.commit(channel);
+        let proof = prove::<SimdBackend, Blake3MerkleChannel>(
+            &[&large, &small],
+            channel,
+            &Statement::default(),
+            None,
+            &InteractionElements::default(),
+            commitment_scheme,
+        )
+        .unwrap();
+
+        verify_components(&[&large, &small], config, &Statement::default(), proof).unwrap();
+    }
+
+    #[test]
+    fn test_mixed_sizes_prove_reports_failing_component() {
+        let config = test_pcs_config();
+        let log_degree_bound = LOG_SIZE + 1;
+        let twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(log_degree_bound + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let mut trace = gen_trace();
+        let value = &mut trace.last_mut().unwrap().values;
+        value.set(0, value.at(0) + BaseField::one());
+        let component = FrameworkComponent::new(MixedSizesEval);
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<SimdBackend, Blake3MerkleChannel>::new(config, &twiddles);
+        commitment_scheme.tree_builder().commit(channel);
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_evals(trace, log_degree_bound);
+        let trace_check = check_constraints_on_trace(&[&component], &tree_builder.trace_polys());
+        tree_builder.commit(channel);
+        let result = prove::<SimdBackend, Blake3MerkleChannel>(
+            &[&component],
+            channel,
+            &Statement::default(),
+            None,
+            &InteractionElements::default(),
+            commitment_scheme,
+        );
+
+        assert_matches!(
+            trace_check,
+            Err(ProvingError::TraceConstraintsNotSatisfied { component: 0, .. })
+        );
+        assert_matches!(
+            result,
+            Err(ProvingError::ConstraintsNotSatisfied { components }) if components == [0]
+        );
+    }
 }
@@ -1,9 +1,13 @@
+use indexmap::IndexSet;
 use num_traits::One;
 
+use super::{FrameworkEval, InfoEvaluator};
 use crate::core::backend::{Backend, Col, Column};
 use crate::core::fields::m31::BaseField;
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
 use crate::core::poly::BitReversedOrder;
+use crate::core::utils::{bit_reverse, coset_order_to_circle_domain_order};
+use crate::core::ColumnVec;
 
 /// Generates a column with a single one at the first position, and zeros elsewhere.
 pub fn gen_is_first<B: Backend>(log_size: u32) -> CircleEvaluation<B, BaseField, BitReversedOrder> {
@@ -11,3 +15,223 @@ pub fn gen_is_first<B: Backend>(log_size: u32) -> CircleEvaluation<B, BaseField,
     col.set(0, BaseField::one());
     CircleEvaluation::new(CanonicCoset::new(log_size).circle_domain(), col)
 }
+
+/// A named column of the preprocessed trace. Preprocessed columns are known to the verifier, and
+/// are committed once for all the components, see [PreprocessedColumnRegistry].
+/// Rows are indexed in the trace domain order, i.e. row `i` is the `i`-th point of the canonic
+/// coset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PreprocessedColumn {
+    /// One at the first row, zero elsewhere.
+    IsFirst(u32),
+    /// One at the last row, zero elsewhere.
+    IsLast(u32),
+    /// One at every `2^log_step` rows, starting at the first row, zero elsewhere.
+    IsStep { log_size: u32, log_step: u32 },
+    /// The values `0..2^n_bits`.
+    RangeTable(u32),
+    /// The XOR table of all the pairs of `n_bits` values `a, b`, at row `a << n_bits | b`.
+    /// Column `index` 0 holds `a`, 1 holds `b` and 2 holds `a ^ b`.
+    XorTable { n_bits: u32, index: usize },
+    /// The AND table of all the pairs of `n_bits` values, laid out as [Self::XorTable].
+    AndTable { n_bits: u32, index: usize },
+    /// A column defined by the user. Its values are provided when generating the trace, see
+    /// [PreprocessedColumnRegistry::gen_trace].
+    Custom { name: &'static str, log_size: u32 },
+}
+
+impl PreprocessedColumn {
+    pub fn name(&self) -> String {
+        match self {
+            Self::IsFirst(log_size) => format!("is_first_{log_size}"),
+            Self::IsLast(log_size) => format!("is_last_{log_size}"),
+            Self::IsStep { log_size, log_step } => format!("is_step_{log_step}_{log_size}"),
+            Self::RangeTable(n_bits) => format!("range_table_{n_bits}"),
+            Self::XorTable { n_bits, index } => format!("xor_table_{n_bits}_{index}"),
+            Self::AndTable { n_bits, index } => format!("and_table_{n_bits}_{index}"),
+            Self::Custom { name, .. } => name.to_string(),
+        }
+    }
+
+    pub fn log_size(&self) -> u32 {
+        match *self {
+            Self::IsFirst(log_size)
+            | Self::IsLast(log_size)
+            | Self::IsStep { log_size, .. }
+            | Self::Custom { log_size, .. } => log_size,
+            Self::RangeTable(n_bits) => n_bits,
+            Self::XorTable { n_bits, .. } | Self::AndTable { n_bits, .. } => 2 * n_bits,
+        }
+    }
+
+    /// Returns the values of the column, in row order.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [Self::Custom] columns, whose values are not known.
+    pub fn row_values(&self) -> Vec<BaseField> {
+        let size = 1 << self.log_size();
+        let indicator = |is_set: bool| BaseField::from(is_set as u32);
+        let table_value = |n_bits: u32, index: usize, op: fn(u32, u32) -> u32| {
+            (0..size as u32)
+                .map(|row| {
+                    let (a, b) = (row >> n_bits, row & ((1 << n_bits) - 1));
+                    match index {
+                        0 => a,
+                        1 => b,
+                        2 => op(a, b),
+                        _ => panic!("Invalid table column index {index}."),
+                    }
+                })
+                .map(BaseField::from)
+                .collect()
+        };
+        match *self {
+            Self::IsFirst(_) => (0..size).map(|row| indicator(row == 0)).collect(),
+            Self::IsLast(_) => (0..size).map(|row| indicator(row == size - 1)).collect(),
+            Self::IsStep { log_step, .. } => (0..size)
+                .map(|row| indicator(row % (1 << log_step) == 0))
+                .collect(),
+            Self::RangeTable(_) => (0..size as u32).map(BaseField::from).collect(),
+            Self::XorTable { n_bits, index } => table_value(n_bits, index, |a, b| a ^ b),
+            Self::AndTable { n_bits, index } => table_value(n_bits, index, |a, b| a & b),
+            Self::Custom { name, .. } => panic!("Values of custom column {name} are not known."),
+        }
+    }
+
+    /// Generates the column evaluation on its trace domain.
+    ///
+    /// # Panics
+    ///
+    /// Panics for [Self::Custom] columns, whose values are not known.
+    pub fn gen_column<B: Backend>(&self) -> CircleEvaluation<B, BaseField, BitReversedOrder> {
+        let mut values = coset_order_to_circle_domain_order(&self.row_values());
+        bit_reverse(&mut values);
+        CircleEvaluation::new(
+            CanonicCoset::new(self.log_size()).circle_domain(),
+            values.into_iter().collect(),
+        )
+    }
+}
+
+/// The preprocessed columns used by a set of components, deduplicated.
+/// The index of a column in the registry is its index in the preprocessed trace, committed at
+/// [super::PREPROCESSED_TRACE_IDX].
+#[derive(Clone, Debug, Default)]
+pub struct PreprocessedColumnRegistry {
+    columns: IndexSet<PreprocessedColumn>,
+}
+
+impl PreprocessedColumnRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column, if not already present, and returns its index in the preprocessed trace.
+    pub fn add(&mut self, column: PreprocessedColumn) -> usize {
+        self.columns.insert_full(column).0
+    }
+
+    /// Adds the columns read by a component, in order, and returns their indices in the
+    /// preprocessed trace. See [super::InfoEvaluator::preprocessed_columns].
+    pub fn add_all(&mut self, columns: &[PreprocessedColumn]) -> ColumnVec<usize> {
+        columns.iter().map(|&column| self.add(column)).collect()
+    }
+
+    /// Adds the columns `component` reads with [super::EvalAtRow::get_preprocessed_column], in
+    /// order, and returns their indices in the preprocessed trace. Components compute their
+    /// [super::FrameworkEval::preprocessed_column_indices] with it when they are built.
+    pub fn add_component(&mut self, component: &impl FrameworkEval) -> ColumnVec<usize> {
+        let info = component.evaluate(InfoEvaluator::new(component.log_size()));
+        self.add_all(&info.preprocessed_columns)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = &PreprocessedColumn> {
+        self.columns.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Generates the preprocessed trace. The values of [PreprocessedColumn::Custom] columns are
+    /// provided by `gen_custom`.
+    pub fn gen_trace<B: Backend>(
+        &self,
+        mut gen_custom: impl FnMut(
+            &PreprocessedColumn,
+        ) -> CircleEvaluation<B, BaseField, BitReversedOrder>,
+    ) -> ColumnVec<CircleEvaluation<B, BaseField, BitReversedOrder>> {
+        self.columns
+            .iter()
+            .map(|column| match column {
+                PreprocessedColumn::Custom { .. } => gen_custom(column),
+                _ => column.gen_column(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gen_is_first, PreprocessedColumn, PreprocessedColumnRegistry};
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+
+    #[test]
+    fn test_registry_deduplicates_columns() {
+        let mut registry = PreprocessedColumnRegistry::new();
+
+        let first = registry.add_all(&[
+            PreprocessedColumn::IsFirst(5),
+            PreprocessedColumn::RangeTable(6),
+        ]);
+        let second = registry.add_all(&[
+            PreprocessedColumn::RangeTable(6),
+            PreprocessedColumn::IsLast(5),
+            PreprocessedColumn::IsFirst(5),
+        ]);
+
+        assert_eq!(first, vec![0, 1]);
+        assert_eq!(second, vec![1, 2, 0]);
+        assert_eq!(registry.len(), 3);
+    }
+
+    #[test]
+    fn test_xor_table_values() {
+        let n_bits = 3;
+        let [a, b, a_xor_b] =
+            [0, 1, 2].map(|index| PreprocessedColumn::XorTable { n_bits, index }.row_values());
+
+        for row in 0..1 << (2 * n_bits) {
+            assert_eq!(a[row], BaseField::from(row >> n_bits));
+            assert_eq!(b[row], BaseField::from(row & 7));
+            assert_eq!(a_xor_b[row], BaseField::from((row >> n_bits) ^ (row & 7)));
+        }
+    }
+
+    #[test]
+    fn test_is_step_column() {
+        let column = PreprocessedColumn::IsStep {
+            log_size: 5,
+            log_step: 2,
+        };
+
+        let values = column.row_values();
+
+        for (row, value) in values.into_iter().enumerate() {
+            assert_eq!(value, BaseField::from((row % 4 == 0) as u32));
+        }
+    }
+
+    #[test]
+    fn test_is_first_column_matches_gen_is_first() {
+        let eval = PreprocessedColumn::IsFirst(5).gen_column::<CpuBackend>();
+
+        assert_eq!(eval.values, gen_is_first::<CpuBackend>(5).values);
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::ops::Mul;
+
+use num_traits::Zero;
+
+use super::{Boundary, EvalAtRow};
+use crate::core::backend::CpuBackend;
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::pcs::TreeVec;
+use crate::core::poly::circle::CircleEvaluation;
+use crate::core::poly::BitReversedOrder;
+use crate::core::utils::offset_bit_reversed_circle_domain_index;
+
+/// Evaluates constraints at an evaluation domain point, one row at a time.
+/// The scalar counterpart of [super::SimdDomainEvaluator].
+pub struct CpuDomainEvaluator<'a> {
+    pub trace_eval: &'a TreeVec<Vec<&'a CircleEvaluation<CpuBackend, BaseField, BitReversedOrder>>>,
+    pub column_index_per_interaction: Vec<usize>,
+    /// The index of the row of the evaluation domain to evaluate the constraints at.
+    pub row: usize,
+    pub random_coeff_powers: &'a [SecureField],
+    /// The combined constraint quotients of the trace domains that were already left.
+    pub row_res: SecureField,
+    /// The combined constraints of the current trace domain, not yet divided by its vanishing
+    /// polynomial.
+    pub domain_res: SecureField,
+    pub constraint_index: usize,
+    pub domain_log_size: u32,
+    pub eval_domain_log_size: u32,
+    /// Inverses of the vanishing polynomial of each trace domain, by log size, see
+    /// [super::SimdDomainEvaluator::denom_inv].
+    pub denom_inv: &'a BTreeMap<u32, Vec<BaseField>>,
+    /// Inverses of the vanishing polynomial of the point of each boundary constraint, see
+    /// [super::SimdDomainEvaluator::boundary_denom_inv].
+    pub boundary_denom_inv: &'a BTreeMap<(u32, Boundary), Vec<BaseField>>,
+}
+impl<'a> CpuDomainEvaluator<'a> {
+    pub fn new(
+        trace_eval: &'a TreeVec<Vec<&CircleEvaluation<CpuBackend, BaseField, BitReversedOrder>>>,
+        row: usize,
+        random_coeff_powers: &'a [SecureField],
+        domain_log_size: u32,
+        eval_log_size: u32,
+        denom_inv: &'a BTreeMap<u32, Vec<BaseField>>,
+        boundary_denom_inv: &'a BTreeMap<(u32, Boundary), Vec<BaseField>>,
+    ) -> Self {
+        Self {
+            trace_eval,
+            column_index_per_interaction: vec![0; trace_eval.len()],
+            row,
+            random_coeff_powers,
+            row_res: SecureField::zero(),
+            domain_res: SecureField::zero(),
+            constraint_index: 0,
+            domain_log_size,
+            eval_domain_log_size: eval_log_size,
+            denom_inv,
+            boundary_denom_inv,
+        }
+    }
+
+    /// Returns the combined constraint quotients of all the trace domains at the row.
+    pub fn finalize(mut self) -> SecureField {
+        self.divide_domain_res();
+        self.row_res
+    }
+
+    /// Divides the constraints of the current trace domain by its vanishing polynomial, and moves
+    /// them to `row_res`.
+    fn divide_domain_res(&mut self) {
+        let denom_inv = self.denom_inv[&self.domain_log_size][self.row >> self.domain_log_size];
+        self.row_res += self.domain_res * denom_inv;
+        self.domain_res = SecureField::zero();
+    }
+}
+impl EvalAtRow for CpuDomainEvaluator<'_> {
+    type F = BaseField;
+    type EF = SecureField;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        let col_index = self.column_index_per_interaction[interaction];
+        self.column_index_per_interaction[interaction] += 1;
+        let col = &self.trace_eval[interaction][col_index].values;
+        offsets.map(|off| {
+            // The domain is bit-reversed circle domain ordered, so the offset is applied to the
+            // natural order index.
+            let row_index = offset_bit_reversed_circle_domain_index(
+                self.row,
+                self.domain_log_size,
+                self.eval_domain_log_size,
+                off,
+            );
+            col[row_index]
+        })
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        self.domain_res += self.random_coeff_powers[self.constraint_index] * constraint;
+        self.constraint_index += 1;
+    }
+
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    ) {
+        let denom_inv = self.boundary_denom_inv[&(self.domain_log_size, boundary)][self.row];
+        self.row_res +=
+            self.random_coeff_powers[self.constraint_index] * ((value - public_value) * denom_inv);
+        self.constraint_index += 1;
+    }
+
+    fn enter_domain(&mut self, log_size: u32) {
+        if log_size != self.domain_log_size {
+            self.divide_domain_res();
+            self.domain_log_size = log_size;
+        }
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        SecureField::from_m31_array(values)
+    }
+}
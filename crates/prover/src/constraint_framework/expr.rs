@@ -0,0 +1,484 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
+
+use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
+
+use super::constant_columns::PreprocessedColumn;
+use super::logup::LookupElements;
+use super::{Boundary, EvalAtRow, PREPROCESSED_TRACE_IDX};
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::fields::FieldExpOps;
+
+/// A mask value: the value of a column at a row offset from the current row.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnExpr {
+    pub interaction: usize,
+    /// The index of the column in the interaction, among the columns read by the component.
+    pub idx: usize,
+    /// The row offset, in steps of the trace domain of the column.
+    pub offset: isize,
+    /// The log size of the trace domain of the column.
+    pub log_size: u32,
+}
+
+/// A symbolic expression over the mask values of a component, see [ExprEvaluator].
+/// Trivial operations with the constants 0 and 1 are simplified when the expression is built.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Expr {
+    Col(ColumnExpr),
+    Const(BaseField),
+    SecureConst(SecureField),
+    /// A value drawn from the channel, e.g. a lookup element.
+    Param(String),
+    /// An extension field value, from its 4 base field coordinates.
+    SecureCol([Box<Expr>; SECURE_EXTENSION_DEGREE]),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Neg(Box<Expr>),
+    Inv(Box<Expr>),
+}
+
+impl Expr {
+    fn is_one(&self) -> bool {
+        match self {
+            Self::Const(c) => c.is_one(),
+            Self::SecureConst(c) => c.is_one(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Col(col) => {
+                write!(f, "trace_{}[{}]", col.interaction, col.idx)?;
+                if col.offset != 0 {
+                    write!(f, "@{}", col.offset)?;
+                }
+                Ok(())
+            }
+            Self::Const(c) => write!(f, "{c}"),
+            Self::SecureConst(c) => write!(f, "({c})"),
+            Self::Param(name) => write!(f, "{name}"),
+            Self::SecureCol([a, b, c, d]) => write!(f, "secure_col({a}, {b}, {c}, {d})"),
+            Self::Add(a, b) => write!(f, "({a} + {b})"),
+            Self::Sub(a, b) => write!(f, "({a} - {b})"),
+            Self::Mul(a, b) => write!(f, "{a} * {b}"),
+            Self::Neg(a) => write!(f, "-{a}"),
+            Self::Inv(a) => write!(f, "1 / {a}"),
+        }
+    }
+}
+
+impl Zero for Expr {
+    fn zero() -> Self {
+        Self::Const(BaseField::zero())
+    }
+    fn is_zero(&self) -> bool {
+        match self {
+            Self::Const(c) => c.is_zero(),
+            Self::SecureConst(c) => c.is_zero(),
+            _ => false,
+        }
+    }
+}
+
+impl One for Expr {
+    fn one() -> Self {
+        Self::Const(BaseField::one())
+    }
+}
+
+impl Add for Expr {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        if self.is_zero() {
+            return rhs;
+        }
+        if rhs.is_zero() {
+            return self;
+        }
+        Self::Add(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Sub for Expr {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        if rhs.is_zero() {
+            return self;
+        }
+        if self.is_zero() {
+            return -rhs;
+        }
+        Self::Sub(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Mul for Expr {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        if self.is_zero() || rhs.is_zero() {
+            return Self::zero();
+        }
+        if self.is_one() {
+            return rhs;
+        }
+        if rhs.is_one() {
+            return self;
+        }
+        Self::Mul(Box::new(self), Box::new(rhs))
+    }
+}
+
+impl Neg for Expr {
+    type Output = Self;
+    fn neg(self) -> Self {
+        match self {
+            Self::Neg(a) => *a,
+            _ if self.is_zero() => self,
+            _ => Self::Neg(Box::new(self)),
+        }
+    }
+}
+
+impl AddAssign for Expr {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = self.clone() + rhs;
+    }
+}
+
+impl MulAssign for Expr {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.clone() * rhs;
+    }
+}
+
+impl FieldExpOps for Expr {
+    fn inverse(&self) -> Self {
+        Self::Inv(Box::new(self.clone()))
+    }
+}
+
+impl From<BaseField> for Expr {
+    fn from(value: BaseField) -> Self {
+        Self::Const(value)
+    }
+}
+
+impl From<SecureField> for Expr {
+    fn from(value: SecureField) -> Self {
+        Self::SecureConst(value)
+    }
+}
+
+impl AddAssign<BaseField> for Expr {
+    fn add_assign(&mut self, rhs: BaseField) {
+        *self = self.clone() + Self::from(rhs);
+    }
+}
+
+impl Mul<BaseField> for Expr {
+    type Output = Self;
+    fn mul(self, rhs: BaseField) -> Self {
+        self * Self::from(rhs)
+    }
+}
+
+impl Add<SecureField> for Expr {
+    type Output = Self;
+    fn add(self, rhs: SecureField) -> Self {
+        self + Self::from(rhs)
+    }
+}
+
+impl Sub<SecureField> for Expr {
+    type Output = Self;
+    fn sub(self, rhs: SecureField) -> Self {
+        self - Self::from(rhs)
+    }
+}
+
+impl Mul<SecureField> for Expr {
+    type Output = Self;
+    fn mul(self, rhs: SecureField) -> Self {
+        self * Self::from(rhs)
+    }
+}
+
+/// A constraint of a component, required to vanish on its trace domain, or only at the row of a
+/// boundary constraint.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConstraintExpr {
+    /// The name given in [EvalAtRow::add_constraint_named], if any.
+    pub name: Option<String>,
+    /// The log size of the trace domain of the constraint.
+    pub log_size: u32,
+    /// The row of a boundary constraint, see [EvalAtRow::add_boundary_constraint].
+    pub boundary: Option<Boundary>,
+    pub expr: Expr,
+}
+
+impl Display for ConstraintExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.boundary {
+            Some(boundary) => write!(f, "[log_size {}, {boundary}] ", self.log_size)?,
+            None => write!(f, "[log_size {}] ", self.log_size)?,
+        }
+        if let Some(name) = &self.name {
+            write!(f, "{name}: ")?;
+        }
+        write!(f, "{} = 0", self.expr)
+    }
+}
+
+/// Evaluates the constraints symbolically, and collects them as [Expr] trees.
+/// Lookup elements are replaced by the parameters `lookup_{i}_z` and `lookup_{i}_alpha`, where `i`
+/// is the index of the [LookupElements] in order of first use. The claimed sums of the logup
+/// columns are replaced by the parameters `claimed_sum_{i}`, in order of use.
+#[derive(Default)]
+pub struct ExprEvaluator {
+    pub constraints: Vec<ConstraintExpr>,
+    /// The names of the preprocessed columns read, in order, see [PreprocessedColumn::name].
+    pub preprocessed_columns: Vec<String>,
+    /// The log size of the current trace domain.
+    pub log_size: u32,
+    column_index_per_interaction: Vec<usize>,
+    /// The `z` of each [LookupElements] used, identifying it.
+    lookup_elements_z: Vec<SecureField>,
+    n_claimed_sums: usize,
+}
+impl ExprEvaluator {
+    pub fn new(log_size: u32) -> Self {
+        Self {
+            log_size,
+            ..Self::default()
+        }
+    }
+
+    fn add_constraint_expr(&mut self, name: Option<&str>, boundary: Option<Boundary>, expr: Expr) {
+        self.constraints.push(ConstraintExpr {
+            name: name.map(str::to_string),
+            log_size: self.log_size,
+            boundary,
+            expr,
+        });
+    }
+}
+
+impl Display for ExprEvaluator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, name) in self.preprocessed_columns.iter().enumerate() {
+            writeln!(f, "trace_{PREPROCESSED_TRACE_IDX}[{i}] = {name}")?;
+        }
+        for constraint in &self.constraints {
+            writeln!(f, "{constraint}")?;
+        }
+        Ok(())
+    }
+}
+
+impl EvalAtRow for ExprEvaluator {
+    type F = Expr;
+    type EF = Expr;
+
+    fn next_interaction_mask<const N: usize>(
+        &mut self,
+        interaction: usize,
+        offsets: [isize; N],
+    ) -> [Self::F; N] {
+        if self.column_index_per_interaction.len() <= interaction {
+            self.column_index_per_interaction.resize(interaction + 1, 0);
+        }
+        let idx = self.column_index_per_interaction[interaction];
+        self.column_index_per_interaction[interaction] += 1;
+        offsets.map(|offset| {
+            Expr::Col(ColumnExpr {
+                interaction,
+                idx,
+                offset,
+                log_size: self.log_size,
+            })
+        })
+    }
+
+    fn get_preprocessed_column(&mut self, column: PreprocessedColumn) -> Self::F {
+        self.preprocessed_columns.push(column.name());
+        let [mask_item] = self.next_interaction_mask(PREPROCESSED_TRACE_IDX, [0]);
+        mask_item
+    }
+
+    fn combine_lookup<const N: usize>(
+        &mut self,
+        lookup_elements: &LookupElements<N>,
+        values: &[Self::F],
+    ) -> Self::EF {
+        let index = match self
+            .lookup_elements_z
+            .iter()
+            .position(|&z| z == lookup_elements.z)
+        {
+            Some(index) => index,
+            None => {
+                self.lookup_elements_z.push(lookup_elements.z);
+                self.lookup_elements_z.len() - 1
+            }
+        };
+        let alpha = Expr::Param(format!("lookup_{index}_alpha"));
+        let z = Expr::Param(format!("lookup_{index}_z"));
+        let mut alpha_power = Expr::one();
+        let mut res = Expr::zero();
+        for value in values {
+            res += alpha_power.clone() * value.clone();
+            alpha_power *= alpha.clone();
+        }
+        res - z
+    }
+
+    fn logup_cumsum_shift(&mut self, _claimed_sum: SecureField, log_size: u32) -> Self::EF {
+        let claimed_sum = Expr::Param(format!("claimed_sum_{}", self.n_claimed_sums));
+        self.n_claimed_sums += 1;
+        claimed_sum * BaseField::from_u32_unchecked(1 << log_size).inverse()
+    }
+
+    fn add_constraint<G>(&mut self, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        self.add_constraint_expr(None, None, Expr::one() * constraint);
+    }
+
+    fn add_constraint_named<G>(&mut self, name: &str, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        self.add_constraint_expr(Some(name), None, Expr::one() * constraint);
+    }
+
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    ) {
+        self.add_constraint_expr(None, Some(boundary), value - Expr::from(public_value));
+    }
+
+    fn enter_domain(&mut self, log_size: u32) {
+        self.log_size = log_size;
+    }
+
+    fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
+        Expr::SecureCol(values.map(Box::new))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::{ColumnExpr, ConstraintExpr, Expr, ExprEvaluator};
+    use crate::constraint_framework::logup::{LogupAtRow, LookupElements};
+    use crate::constraint_framework::{
+        Boundary, EvalAtRow, INTERACTION_TRACE_IDX, ORIGINAL_TRACE_IDX,
+    };
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+
+    #[test]
+    fn test_expr_evaluator_collects_constraints() {
+        let mut eval = ExprEvaluator::new(5);
+        let [x, x_next] = eval.next_interaction_mask(ORIGINAL_TRACE_IDX, [0, 1]);
+        let y = eval.next_trace_mask();
+        eval.add_constraint_named("step", x_next - x * y * BaseField::from(3));
+
+        let [constraint] = eval.constraints.try_into().unwrap();
+        assert_eq!(constraint.name.as_deref(), Some("step"));
+        assert_eq!(constraint.log_size, 5);
+        assert_eq!(
+            constraint.expr.to_string(),
+            "(trace_1[0]@1 - trace_1[0] * trace_1[1] * 3)"
+        );
+        let serialized = serde_json::to_string(&constraint).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ConstraintExpr>(&serialized).unwrap(),
+            constraint
+        );
+        let Expr::Sub(x_next, _) = constraint.expr else {
+            panic!("Expected a subtraction.");
+        };
+        assert_eq!(
+            *x_next,
+            Expr::Col(ColumnExpr {
+                interaction: ORIGINAL_TRACE_IDX,
+                idx: 0,
+                offset: 1,
+                log_size: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_expr_evaluator_collects_boundary_constraints() {
+        let mut eval = ExprEvaluator::new(5);
+        let x = eval.next_trace_mask();
+        eval.add_boundary_constraint(Boundary::Last, x, BaseField::from(3));
+
+        let [constraint] = eval.constraints.try_into().unwrap();
+        assert_eq!(constraint.boundary, Some(Boundary::Last));
+        assert_eq!(
+            constraint.to_string(),
+            "[log_size 5, last row] (trace_1[0] - 3) = 0"
+        );
+    }
+
+    #[test]
+    fn test_expr_evaluator_replaces_lookup_elements() {
+        let lookup_elements = LookupElements::<2> {
+            z: BaseField::from(7).into(),
+            alpha: BaseField::from(2).into(),
+            alpha_powers: [SecureField::one(), BaseField::from(2).into()],
+        };
+        let mut eval = ExprEvaluator::new(5);
+        let mut logup = LogupAtRow::<1, _>::new(INTERACTION_TRACE_IDX, SecureField::one(), 5);
+        let [a, b] = [eval.next_trace_mask(), eval.next_trace_mask()];
+        logup.push_lookup(&mut eval, Expr::one(), &[a, b], &lookup_elements);
+        logup.finalize(&mut eval);
+
+        let [constraint] = eval.constraints.try_into().unwrap();
+        let formatted = constraint.expr.to_string();
+        assert!(
+            formatted.contains("((trace_1[0] + lookup_0_alpha * trace_1[1]) - lookup_0_z)"),
+            "{formatted}"
+        );
+        assert!(!formatted.contains("(7"));
+    }
+
+    #[test]
+    fn test_expr_evaluator_replaces_claimed_sums() {
+        let lookup_elements = LookupElements::<1> {
+            z: BaseField::from(7).into(),
+            alpha: BaseField::from(2).into(),
+            alpha_powers: [SecureField::one()],
+        };
+        let logup_expr = |claimed_sum: SecureField| {
+            let mut eval = ExprEvaluator::new(5);
+            let mut logup = LogupAtRow::<1, _>::new(INTERACTION_TRACE_IDX, claimed_sum, 5);
+            let a = eval.next_trace_mask();
+            logup.push_lookup(&mut eval, Expr::one(), &[a], &lookup_elements);
+            logup.finalize(&mut eval);
+            let [constraint] = eval.constraints.try_into().unwrap();
+            constraint.expr
+        };
+
+        let expr = logup_expr(SecureField::one());
+
+        assert_eq!(expr, logup_expr(BaseField::from(3).into()));
+        assert!(expr.to_string().contains("claimed_sum_0"), "{expr}");
+    }
+}
@@ -0,0 +1,471 @@
+//! Proves the lookups of components with GKR, instead of committed logup cumulative sum columns.
+//!
+//! Each row of a lookup contributes the fraction `numerator / combine(values)` to its relation.
+//! The fractions of all the lookups are summed by a batch GKR proof, see
+//! [crate::core::lookups::gkr_lookups], which reduces the sums to claims on the multilinear
+//! extensions of the numerators and denominators of each lookup, at a random point. A component
+//! declares its lookups with [super::FrameworkEval::gkr_lookups], and checks these claims in its
+//! constraints with [GkrLookupAtRow]: its interaction trace holds the evaluations of
+//! `eq(row, point)` and the cumulative sum of the lookup terms weighted by them, so the
+//! multilinear claims become univariate claims on committed columns, checked through the circle
+//! PCS like any other constraint.
+//!
+//! The row `i` of a trace domain, in the order of [PreprocessedColumn], is the point of the boolean
+//! hypercube whose first variable is the most significant bit of `i`, as in [Mle].
+use itertools::{zip_eq, Itertools};
+use num_traits::{One, Zero};
+
+use super::constant_columns::PreprocessedColumn;
+use super::logup::LookupElements;
+use super::EvalAtRow;
+use crate::core::backend::{Backend, Col, Column, CpuBackend};
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SecureColumnByCoords;
+use crate::core::lookups::gkr_lookups::GkrLookupClaims;
+use crate::core::lookups::gkr_prover::{GkrOps, Layer};
+use crate::core::lookups::mle::Mle;
+use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
+use crate::core::poly::BitReversedOrder;
+use crate::core::utils::{bit_reverse_index, coset_order_to_circle_domain_order_index};
+use crate::core::ColumnVec;
+
+/// Returns the values of a column in row order, from its evaluation in bit reversed circle domain
+/// order.
+fn row_values<F: Copy>(evals: &[F]) -> Vec<F> {
+    let log_size = evals.len().ilog2();
+    (0..evals.len())
+        .map(|row| {
+            let index = coset_order_to_circle_domain_order_index(row, log_size);
+            evals[bit_reverse_index(index, log_size)]
+        })
+        .collect()
+}
+
+/// Returns the evaluation of a column in bit reversed circle domain order, from its values in row
+/// order.
+fn evals_from_rows<B: Backend>(rows: &[BaseField]) -> Col<B, BaseField> {
+    let log_size = rows.len().ilog2();
+    let mut evals = vec![BaseField::zero(); rows.len()];
+    for (row, &value) in rows.iter().enumerate() {
+        let index = coset_order_to_circle_domain_order_index(row, log_size);
+        evals[bit_reverse_index(index, log_size)] = value;
+    }
+    evals.into_iter().collect()
+}
+
+/// Returns the input layer of a lookup whose row contributes `numerators / denominators`, given in
+/// the order of the trace columns. Numerators default to one.
+pub fn gen_input_layer<B: GkrOps>(
+    numerators: Option<&Col<B, BaseField>>,
+    denominators: &Col<B, SecureField>,
+) -> Layer<B> {
+    let denominators = Mle::new(row_values(&denominators.to_cpu()).into_iter().collect());
+    match numerators {
+        Some(numerators) => Layer::LogUpMultiplicities {
+            numerators: Mle::new(row_values(&numerators.to_cpu()).into_iter().collect()),
+            denominators,
+        },
+        None => Layer::LogUpSingles { denominators },
+    }
+}
+
+/// Generates the interaction trace checked by [GkrLookupAtRow]: the evaluations of
+/// `eq(row, point)`, and the cumulative sum of the lookup terms weighted by them, shifted to add
+/// up to zero. `input_layers` are the layers of the lookups, in the order they are pushed.
+pub fn gen_interaction_trace<B: Backend>(
+    input_layers: &[Layer<CpuBackend>],
+    claims: &GkrLookupClaims,
+) -> ColumnVec<CircleEvaluation<B, BaseField, BitReversedOrder>> {
+    let log_size = claims.log_size();
+    let eq_evals = CpuBackend::gen_eq_evals(&claims.point, SecureField::one()).into_evals();
+
+    let mut terms = vec![SecureField::zero(); 1 << log_size];
+    for (index, layer) in zip_eq(0..claims.claims.len(), input_layers) {
+        let [numerator_coeff, denominator_coeff] = claims.coefficients(index);
+        let (numerators, denominators): (Vec<SecureField>, &[SecureField]) = match layer {
+            Layer::LogUpGeneric {
+                numerators,
+                denominators,
+            } => (numerators.to_vec(), &denominators[..]),
+            Layer::LogUpMultiplicities {
+                numerators,
+                denominators,
+            } => (
+                numerators.iter().map(|&v| v.into()).collect(),
+                &denominators[..],
+            ),
+            Layer::LogUpSingles { denominators } => (
+                vec![SecureField::one(); denominators.len()],
+                &denominators[..],
+            ),
+            Layer::GrandProduct(_) | Layer::FractionProduct { .. } => {
+                panic!("Lookups are proven with logup layers.")
+            }
+        };
+        for (term, numerator, &denominator) in
+            itertools::izip!(&mut terms, numerators, denominators)
+        {
+            *term += numerator * numerator_coeff + denominator * denominator_coeff;
+        }
+    }
+
+    let shift = claims.combined_claim() / BaseField::from_u32_unchecked(1 << log_size);
+    let cumsum = zip_eq(&eq_evals, &terms)
+        .scan(SecureField::zero(), |acc, (&eq_eval, &term)| {
+            *acc += eq_eval * term - shift;
+            Some(*acc)
+        })
+        .collect_vec();
+
+    let domain = CanonicCoset::new(log_size).circle_domain();
+    [eq_evals, cumsum]
+        .into_iter()
+        .flat_map(|rows| SecureColumnByCoords::<CpuBackend>::from_iter(rows).columns)
+        .map(|rows| CircleEvaluation::new(domain, evals_from_rows::<B>(&rows)))
+        .collect()
+}
+
+/// Returns the preprocessed columns read by [GkrLookupAtRow::finalize] on the trace domain of size
+/// `2^log_size`, in order.
+pub fn lookup_preprocessed_columns(log_size: u32) -> Vec<PreprocessedColumn> {
+    (1..log_size)
+        .map(|log_step| PreprocessedColumn::IsStep { log_size, log_step })
+        .chain([PreprocessedColumn::IsFirst(log_size)])
+        .collect()
+}
+
+/// Evaluates the constraints checking the claims of [GkrLookupClaims] on the lookups of a
+/// component. Replaces [super::logup::LogupAtRow]: the lookups are pushed the same way, and the
+/// component reads [lookup_preprocessed_columns] and two extension columns of the interaction
+/// trace, see [gen_interaction_trace].
+pub struct GkrLookupAtRow<E: EvalAtRow> {
+    /// The index of the interaction used for the eq and cumulative sum columns.
+    pub interaction: usize,
+    pub claims: GkrLookupClaims,
+    /// The terms of the lookups pushed so far, combined with their coefficients.
+    pub terms: E::EF,
+    /// The number of lookups pushed so far.
+    pub n_lookups: usize,
+}
+
+impl<E: EvalAtRow> GkrLookupAtRow<E> {
+    pub fn new(interaction: usize, claims: &GkrLookupClaims, log_size: u32) -> Self {
+        assert_eq!(
+            claims.log_size(),
+            log_size,
+            "Claims are on another trace domain."
+        );
+        Self {
+            interaction,
+            claims: claims.clone(),
+            terms: E::EF::zero(),
+            n_lookups: 0,
+        }
+    }
+
+    pub fn push_lookup<const N: usize>(
+        &mut self,
+        eval: &mut E,
+        numerator: E::EF,
+        values: &[E::F],
+        lookup_elements: &LookupElements<N>,
+    ) {
+        let denominator = eval.combine_lookup(lookup_elements, values);
+        self.push_frac(numerator, denominator);
+    }
+
+    pub fn push_frac(&mut self, numerator: E::EF, denominator: E::EF) {
+        let [numerator_coeff, denominator_coeff] = self.claims.coefficients(self.n_lookups);
+        self.terms =
+            self.terms.clone() + numerator * numerator_coeff + denominator * denominator_coeff;
+        self.n_lookups += 1;
+    }
+
+    pub fn finalize(self, eval: &mut E) {
+        assert_eq!(
+            self.n_lookups,
+            self.claims.claims.len(),
+            "Every lookup of the claims must be pushed."
+        );
+        let log_size = self.claims.log_size();
+        // Bit `bit` of the row is the variable `log_size - 1 - bit` of the point.
+        let point = &self.claims.point;
+        let bit_variable = |bit: usize| point[point.len() - 1 - bit];
+
+        // `at_least[t]` is one at the rows with at least `t` trailing zeros. The first row has all
+        // of them.
+        let at_least = [E::F::one()]
+            .into_iter()
+            .chain(
+                lookup_preprocessed_columns(log_size)
+                    .into_iter()
+                    .map(|column| eval.get_preprocessed_column(column)),
+            )
+            .collect_vec();
+        let is_first = at_least.last().unwrap().clone();
+        let [prev_eq, eq] = eval.next_extension_interaction_mask(self.interaction, [-1, 0]);
+        let [prev_cumsum, cumsum] = eval.next_extension_interaction_mask(self.interaction, [-1, 0]);
+
+        // The first row is the origin of the hypercube.
+        let first_eq = (0..point.len())
+            .map(|bit| SecureField::one() - bit_variable(bit))
+            .product::<SecureField>();
+        eval.add_constraint((eq.clone() - first_eq) * is_first);
+
+        // From the previous row, the `t` trailing ones fall and bit `t` rises, where `t` is the
+        // number of trailing zeros of the row. Checked as `eq * (1 - r_t) * prod_{b<t} r_b =
+        // prev_eq * r_t * prod_{b<t} (1 - r_b)`, on all the rows but the first.
+        let mut eq_coeff = E::EF::zero();
+        let mut prev_eq_coeff = E::EF::zero();
+        let mut falling_eq = SecureField::one();
+        let mut falling_prev_eq = SecureField::one();
+        for (t, (at_least_t, more_than_t)) in at_least.into_iter().tuple_windows().enumerate() {
+            let exactly_t = at_least_t - more_than_t;
+            let rising = bit_variable(t);
+            eq_coeff = eq_coeff + exactly_t.clone() * ((SecureField::one() - rising) * falling_eq);
+            prev_eq_coeff = prev_eq_coeff + exactly_t * (rising * falling_prev_eq);
+            falling_eq *= rising;
+            falling_prev_eq *= SecureField::one() - rising;
+        }
+        eval.add_constraint(eq.clone() * eq_coeff - prev_eq * prev_eq_coeff);
+
+        // The weighted terms, shifted to add up to zero, accumulate from the previous row.
+        let shift = self.claims.combined_claim() / BaseField::from_u32_unchecked(1 << log_size);
+        eval.add_constraint(cumsum - prev_cumsum + shift - eq * self.terms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use num_traits::One;
+
+    use super::{
+        evals_from_rows, gen_input_layer, gen_interaction_trace, lookup_preprocessed_columns,
+        GkrLookupAtRow,
+    };
+    use crate::constraint_framework::constant_columns::PreprocessedColumn;
+    use crate::constraint_framework::logup::{LogupTraceGenerator, LookupElements};
+    use crate::constraint_framework::{assert_constraints, EvalAtRow, INTERACTION_TRACE_IDX};
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::m31::LOG_N_LANES;
+    use crate::core::backend::simd::qm31::PackedSecureField;
+    use crate::core::backend::{Col, CpuBackend};
+    use crate::core::channel::Blake3Channel;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::lookups::gkr_lookups::{
+        prove_lookups, verify_lookups, GkrLookup, GkrLookupClaims, GkrLookups,
+    };
+    use crate::core::lookups::gkr_prover::Layer;
+    use crate::core::lookups::gkr_verifier::{Gate, GkrBatchProof};
+    use crate::core::pcs::TreeVec;
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, CirclePoly};
+
+    const LOG_SIZE: u32 = 5;
+
+    /// The lookups of [evaluate_lookups], in the order they are pushed.
+    const LOOKUPS: [GkrLookup; 2] = [
+        GkrLookup {
+            gate: Gate::LogUpSingles,
+            log_size: LOG_SIZE,
+        },
+        GkrLookup {
+            gate: Gate::LogUp,
+            log_size: LOG_SIZE,
+        },
+    ];
+
+    /// Returns the `value` and `mult` columns of the trace, in bit reversed circle domain order.
+    fn gen_trace() -> [Col<CpuBackend, BaseField>; 2] {
+        let rows = |f: fn(u32) -> u32| {
+            (0..1 << LOG_SIZE)
+                .map(|row| BaseField::from(f(row)))
+                .collect_vec()
+        };
+        [
+            evals_from_rows::<CpuBackend>(&rows(|row| row * row + 1)),
+            evals_from_rows::<CpuBackend>(&rows(|row| row % 3)),
+        ]
+    }
+
+    /// Returns the input layers of the lookups of `value`, once with a numerator of one, then with
+    /// a numerator of `mult`.
+    fn gen_input_layers(
+        [value, mult]: &[Col<CpuBackend, BaseField>; 2],
+        lookup_elements: &LookupElements<1>,
+    ) -> Vec<Layer<CpuBackend>> {
+        let denominators = value
+            .iter()
+            .map(|&value| lookup_elements.combine::<BaseField, SecureField>(&[value]))
+            .collect_vec();
+        vec![
+            gen_input_layer::<CpuBackend>(None, &denominators),
+            gen_input_layer::<CpuBackend>(Some(mult), &denominators),
+        ]
+    }
+
+    fn evaluate_lookups<E: EvalAtRow>(
+        mut eval: E,
+        claims: &GkrLookupClaims,
+        lookup_elements: &LookupElements<1>,
+    ) {
+        let mut lookups = GkrLookupAtRow::new(INTERACTION_TRACE_IDX, claims, LOG_SIZE);
+        let value = eval.next_trace_mask();
+        let mult = eval.next_trace_mask();
+        lookups.push_lookup(&mut eval, E::EF::one(), &[value.clone()], lookup_elements);
+        lookups.push_lookup(&mut eval, E::EF::from(mult), &[value], lookup_elements);
+        lookups.finalize(&mut eval);
+    }
+
+    /// Proves the lookups of `trace` with GKR, after drawing their lookup elements.
+    fn prove_trace_lookups(
+        trace: &[Col<CpuBackend, BaseField>; 2],
+    ) -> (LookupElements<1>, GkrBatchProof, GkrLookups) {
+        let channel = &mut Blake3Channel::default();
+        let lookup_elements = LookupElements::draw(channel);
+        let (proof, lookups) = prove_lookups(channel, gen_input_layers(trace, &lookup_elements));
+        (lookup_elements, proof, lookups)
+    }
+
+    /// Returns the preprocessed, original and interaction trace polynomials of the lookups.
+    fn gen_trace_polys(
+        trace: &[Col<CpuBackend, BaseField>; 2],
+        lookup_elements: &LookupElements<1>,
+        claims: &GkrLookupClaims,
+    ) -> TreeVec<Vec<CirclePoly<CpuBackend>>> {
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let input_layers = gen_input_layers(trace, lookup_elements);
+        TreeVec::new(vec![
+            lookup_preprocessed_columns(LOG_SIZE)
+                .iter()
+                .map(|column| column.gen_column::<CpuBackend>().interpolate())
+                .collect(),
+            trace
+                .iter()
+                .map(|column| CircleEvaluation::new(domain, column.clone()).interpolate())
+                .collect(),
+            gen_interaction_trace::<CpuBackend>(&input_layers, claims)
+                .into_iter()
+                .map(|eval| eval.interpolate())
+                .collect(),
+        ])
+    }
+
+    #[test]
+    fn test_input_layer_is_in_row_order() {
+        let rows = PreprocessedColumn::RangeTable(4);
+        let numerators = rows.gen_column::<CpuBackend>().values;
+        let denominators = numerators.iter().map(|&v| v.into()).collect_vec();
+
+        let layer = gen_input_layer::<CpuBackend>(Some(&numerators), &denominators);
+
+        let Layer::LogUpMultiplicities {
+            numerators,
+            denominators,
+        } = layer
+        else {
+            panic!("Unexpected layer.");
+        };
+        assert_eq!(numerators.to_vec(), rows.row_values());
+        assert_eq!(
+            denominators.to_vec(),
+            (0..16)
+                .map(|v| SecureField::from(BaseField::from(v)))
+                .collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_input_layers_sum_to_logup_claimed_sum() {
+        let trace = gen_trace();
+        let (lookup_elements, _, lookups) = prove_trace_lookups(&trace);
+
+        // The logup columns the component would commit instead, on the same lookups.
+        let [value, mult] = trace.map(|column| column.into_iter().collect::<BaseColumn>());
+        let mut logup_gen = LogupTraceGenerator::new(LOG_SIZE);
+        let mut col_gen = logup_gen.new_col();
+        for vec_row in 0..1 << (LOG_SIZE - LOG_N_LANES) {
+            let denominator: PackedSecureField = lookup_elements.combine(&[value.data[vec_row]]);
+            col_gen.write_frac(
+                vec_row,
+                PackedSecureField::broadcast(SecureField::one())
+                    + PackedSecureField::from(mult.data[vec_row]),
+                denominator,
+            );
+        }
+        col_gen.finalize_col();
+        let (_, claimed_sum) = logup_gen.finalize();
+
+        assert_eq!(
+            lookups.claimed_sums().iter().sum::<SecureField>(),
+            claimed_sum
+        );
+    }
+
+    #[test]
+    fn test_lookup_constraints_hold() {
+        let trace = gen_trace();
+        let (lookup_elements, _, lookups) = prove_trace_lookups(&trace);
+        let claims = lookups.claims(0..LOOKUPS.len());
+        let trace_polys = gen_trace_polys(&trace, &lookup_elements, &claims);
+
+        assert_constraints(&trace_polys, CanonicCoset::new(LOG_SIZE), |eval| {
+            evaluate_lookups(eval, &claims, &lookup_elements);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lookup_constraints_fail_on_tampered_claim() {
+        let trace = gen_trace();
+        let (lookup_elements, _, lookups) = prove_trace_lookups(&trace);
+        let claims = lookups.claims(0..LOOKUPS.len());
+        let trace_polys = gen_trace_polys(&trace, &lookup_elements, &claims);
+        let mut tampered_claims = claims.clone();
+        tampered_claims.claims[1][0] += SecureField::one();
+
+        assert_constraints(&trace_polys, CanonicCoset::new(LOG_SIZE), |eval| {
+            evaluate_lookups(eval, &tampered_claims, &lookup_elements);
+        });
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_lookup_constraints_fail_on_tampered_lookup_value() {
+        let trace = gen_trace();
+        let (lookup_elements, _, lookups) = prove_trace_lookups(&trace);
+        let claims = lookups.claims(0..LOOKUPS.len());
+        // The interaction trace is generated from the honest values.
+        let mut trace_polys = gen_trace_polys(&trace, &lookup_elements, &claims);
+        let [mut value, _] = gen_trace();
+        value[3] += BaseField::one();
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        trace_polys[1][0] = CircleEvaluation::new(domain, value).interpolate();
+
+        assert_constraints(&trace_polys, CanonicCoset::new(LOG_SIZE), |eval| {
+            evaluate_lookups(eval, &claims, &lookup_elements);
+        });
+    }
+
+    #[test]
+    fn test_verify_lookups_rejects_tampered_output() {
+        let (_, mut proof, lookups) = prove_trace_lookups(&gen_trace());
+        let verify = |proof: &GkrBatchProof| {
+            let channel = &mut Blake3Channel::default();
+            LookupElements::<1>::draw(channel);
+            verify_lookups(channel, &LOOKUPS, proof)
+        };
+
+        let verified_lookups = verify(&proof).unwrap();
+        proof.output_claims_by_instance[1][0] += SecureField::one();
+
+        assert_eq!(
+            verified_lookups.claims(0..LOOKUPS.len()),
+            lookups.claims(0..LOOKUPS.len())
+        );
+        assert_eq!(verified_lookups.claimed_sums(), lookups.claimed_sums());
+        assert!(verify(&proof).is_err());
+    }
+}
@@ -1,27 +1,198 @@
-use std::ops::Mul;
+use std::collections::BTreeSet;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub};
 
-use num_traits::One;
+use itertools::chain;
+use num_traits::{One, Zero};
 
-use super::EvalAtRow;
+use super::constant_columns::PreprocessedColumn;
+use super::{Boundary, EvalAtRow, PREPROCESSED_TRACE_IDX};
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
+use crate::core::fields::FieldExpOps;
 use crate::core::pcs::TreeVec;
 
+/// A degree bound of an expression over the columns, used by [InfoEvaluator] in place of field
+/// values.
+/// A column on a trace domain of size `2^log_size` has degree `2^log_size`. Adding expressions
+/// takes the max of their degrees, and multiplying them sums their degrees. Constants, including
+/// [Zero::zero] and [One::one], have degree 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Degree(pub u64);
+impl Degree {
+    pub fn of_column(log_size: u32) -> Self {
+        Self(1 << log_size)
+    }
+}
+impl Zero for Degree {
+    fn zero() -> Self {
+        Self(0)
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+}
+impl One for Degree {
+    fn one() -> Self {
+        Self(0)
+    }
+}
+impl Add for Degree {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.max(rhs)
+    }
+}
+impl Sub for Degree {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.max(rhs)
+    }
+}
+impl Mul for Degree {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+    }
+}
+impl Neg for Degree {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self
+    }
+}
+impl AddAssign for Degree {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+impl MulAssign for Degree {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+impl FieldExpOps for Degree {
+    fn inverse(&self) -> Self {
+        panic!("Constraints must be polynomials in the columns, and can't be inverted.")
+    }
+}
+impl From<BaseField> for Degree {
+    fn from(_value: BaseField) -> Self {
+        Self::zero()
+    }
+}
+impl From<SecureField> for Degree {
+    fn from(_value: SecureField) -> Self {
+        Self::zero()
+    }
+}
+impl AddAssign<BaseField> for Degree {
+    fn add_assign(&mut self, _rhs: BaseField) {}
+}
+impl Mul<BaseField> for Degree {
+    type Output = Self;
+    fn mul(self, _rhs: BaseField) -> Self {
+        self
+    }
+}
+impl Add<SecureField> for Degree {
+    type Output = Self;
+    fn add(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+impl Sub<SecureField> for Degree {
+    type Output = Self;
+    fn sub(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+impl Mul<SecureField> for Degree {
+    type Output = Self;
+    fn mul(self, _rhs: SecureField) -> Self {
+        self
+    }
+}
+
 /// Collects information about the constraints.
-/// This includes mask offsets and columns at each interaction, and the number of constraints.
+/// This includes mask offsets and columns at each interaction, the trace domain of each column and
+/// constraint, the degree of each constraint, and the number of constraints.
 #[derive(Default)]
 pub struct InfoEvaluator {
     pub mask_offsets: TreeVec<Vec<Vec<isize>>>,
+    /// The log size of the trace domain of each column.
+    pub mask_log_sizes: TreeVec<Vec<u32>>,
+    /// The log size of the trace domain of each constraint.
+    pub constraint_log_sizes: Vec<u32>,
+    /// The degree of each constraint, see [Degree].
+    pub constraint_degrees: Vec<Degree>,
+    /// The row of each boundary constraint, or `None` for constraints on the whole trace domain.
+    pub constraint_boundaries: Vec<Option<Boundary>>,
+    /// The log size of the current trace domain.
+    pub log_size: u32,
+    /// The preprocessed columns read, in order.
+    pub preprocessed_columns: Vec<PreprocessedColumn>,
     pub n_constraints: usize,
 }
 impl InfoEvaluator {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(log_size: u32) -> Self {
+        Self {
+            log_size,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the log sizes of all the trace domains used by the component, in ascending order.
+    pub fn domain_log_sizes(&self) -> BTreeSet<u32> {
+        chain!(
+            [self.log_size],
+            self.mask_log_sizes.iter().flatten().copied(),
+            self.constraint_log_sizes.iter().copied()
+        )
+        .collect()
+    }
+
+    /// Returns the trace domain log size and row of each boundary constraint, deduplicated.
+    pub fn boundaries(&self) -> BTreeSet<(u32, Boundary)> {
+        self.constraint_log_sizes
+            .iter()
+            .zip(&self.constraint_boundaries)
+            .filter_map(|(&log_size, boundary)| Some((log_size, (*boundary)?)))
+            .collect()
+    }
+
+    /// Returns the smallest log degree bound of the constraint quotients, i.e. of the constraints
+    /// divided by the vanishing polynomials of their trace domains.
+    /// The bound is at least one more than the log size of every trace domain, so that the
+    /// vanishing polynomials don't vanish on the evaluation domain.
+    /// A boundary constraint is divided by the vanishing polynomial of a single point, which
+    /// leaves its degree as is, but the quotient is not in the FFT space of that degree. Its bound
+    /// is one more than the log of its degree.
+    pub fn max_constraint_log_degree_bound(&self) -> u32 {
+        let quotient_log_degree_bounds = self
+            .constraint_degrees
+            .iter()
+            .zip(&self.constraint_log_sizes)
+            .zip(&self.constraint_boundaries)
+            .map(|((degree, &log_size), boundary)| match boundary {
+                Some(_) => degree.0.next_power_of_two().ilog2() + 1,
+                None => {
+                    let quotient_degree = degree.0.saturating_sub(1 << log_size);
+                    quotient_degree.next_power_of_two().ilog2()
+                }
+            });
+        chain!(
+            self.domain_log_sizes()
+                .into_iter()
+                .map(|log_size| log_size + 1),
+            quotient_log_degree_bounds
+        )
+        .max()
+        .unwrap()
     }
 }
 impl EvalAtRow for InfoEvaluator {
-    type F = BaseField;
-    type EF = SecureField;
+    type F = Degree;
+    type EF = Degree;
     fn next_interaction_mask<const N: usize>(
         &mut self,
         interaction: usize,
@@ -31,18 +202,115 @@ impl EvalAtRow for InfoEvaluator {
         if self.mask_offsets.len() <= interaction {
             // Extend `mask_offsets` so that `interaction` is the last index.
             self.mask_offsets.resize(interaction + 1, vec![]);
+            self.mask_log_sizes.resize(interaction + 1, vec![]);
         }
         self.mask_offsets[interaction].push(offsets.into_iter().collect());
-        [BaseField::one(); N]
+        self.mask_log_sizes[interaction].push(self.log_size);
+        [Degree::of_column(self.log_size); N]
     }
-    fn add_constraint<G>(&mut self, _constraint: G)
+    fn get_preprocessed_column(&mut self, column: PreprocessedColumn) -> Self::F {
+        self.preprocessed_columns.push(column);
+        let [mask_item] = self.next_interaction_mask(PREPROCESSED_TRACE_IDX, [0]);
+        mask_item
+    }
+    fn add_constraint<G>(&mut self, constraint: G)
     where
         Self::EF: Mul<G, Output = Self::EF>,
     {
+        self.constraint_log_sizes.push(self.log_size);
+        self.constraint_degrees.push(Degree::one() * constraint);
+        self.constraint_boundaries.push(None);
+        self.n_constraints += 1;
+    }
+
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        _public_value: BaseField,
+    ) {
+        self.constraint_log_sizes.push(self.log_size);
+        self.constraint_degrees.push(value);
+        self.constraint_boundaries.push(Some(boundary));
         self.n_constraints += 1;
     }
 
-    fn combine_ef(_values: [Self::F; 4]) -> Self::EF {
-        SecureField::one()
+    fn enter_domain(&mut self, log_size: u32) {
+        self.log_size = log_size;
+    }
+
+    fn combine_ef(values: [Self::F; 4]) -> Self::EF {
+        values
+            .into_iter()
+            .fold(Degree::zero(), |acc, value| acc + value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::InfoEvaluator;
+    use crate::constraint_framework::logup::{LogupAtRow, LookupElements};
+    use crate::constraint_framework::{Boundary, EvalAtRow, INTERACTION_TRACE_IDX};
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+
+    const LOG_SIZE: u32 = 6;
+
+    fn logup_log_degree_bound<const BATCH_SIZE: usize>() -> u32 {
+        let lookup_elements = LookupElements::<1> {
+            z: SecureField::one(),
+            alpha: SecureField::one(),
+            alpha_powers: [SecureField::one()],
+        };
+        let mut eval = InfoEvaluator::new(LOG_SIZE);
+        let mut logup =
+            LogupAtRow::<BATCH_SIZE, _>::new(INTERACTION_TRACE_IDX, SecureField::one(), LOG_SIZE);
+        for _ in 0..3 {
+            let value = eval.next_trace_mask();
+            logup.push_lookup(
+                &mut eval,
+                SecureField::one().into(),
+                &[value],
+                &lookup_elements,
+            );
+        }
+        logup.finalize(&mut eval);
+        eval.max_constraint_log_degree_bound()
+    }
+
+    #[test]
+    fn test_infer_polynomial_log_degree_bound() {
+        let mut eval = InfoEvaluator::new(LOG_SIZE);
+        let [x, x_next] = eval.next_interaction_mask(1, [0, 1]);
+        eval.add_constraint(x_next - x * x * x);
+        assert_eq!(eval.max_constraint_log_degree_bound(), LOG_SIZE + 1);
+
+        eval.add_constraint(x * x * x * x - x);
+        assert_eq!(eval.max_constraint_log_degree_bound(), LOG_SIZE + 2);
+    }
+
+    #[test]
+    fn test_infer_boundary_log_degree_bound() {
+        let mut eval = InfoEvaluator::new(LOG_SIZE);
+        let x = eval.next_trace_mask();
+        eval.add_boundary_constraint(Boundary::First, x, BaseField::one());
+        assert_eq!(eval.max_constraint_log_degree_bound(), LOG_SIZE + 1);
+
+        eval.add_boundary_constraint(Boundary::Last, x * x, BaseField::one());
+        assert_eq!(eval.max_constraint_log_degree_bound(), LOG_SIZE + 2);
+        assert_eq!(
+            eval.boundaries().into_iter().collect::<Vec<_>>(),
+            [(LOG_SIZE, Boundary::First), (LOG_SIZE, Boundary::Last)]
+        );
+    }
+
+    #[test]
+    fn test_infer_logup_log_degree_bound() {
+        // A batch of `n` fractions gives a constraint of degree `n + 1`.
+        assert_eq!(logup_log_degree_bound::<1>(), LOG_SIZE + 1);
+        assert_eq!(logup_log_degree_bound::<2>(), LOG_SIZE + 1);
+        assert_eq!(logup_log_degree_bound::<3>(), LOG_SIZE + 2);
     }
 }
@@ -17,7 +17,8 @@ use crate::core::fields::secure_column::{SecureColumnByCoords, SECURE_EXTENSION_
 use crate::core::fields::FieldExpOps;
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
 use crate::core::poly::BitReversedOrder;
-use crate::core::ColumnVec;
+use crate::core::prover::Phase;
+use crate::core::{ColumnVec, InteractionElementId, InteractionElements, LookupError};
 
 /// Evaluates constraints for batched logups.
 /// These constraint enforce the sum of multiplicity_i / (z + sum_j alpha^j * x_j) = claimed_sum.
@@ -30,10 +31,12 @@ pub struct LogupAtRow<const BATCH_SIZE: usize, E: EvalAtRow> {
     pub queue: [(E::EF, E::EF); BATCH_SIZE],
     /// Number of fractions in the queue.
     pub queue_size: usize,
-    /// A constant to subtract from each row, to make the totall sum of the last column zero.
-    /// In other words, claimed_sum / 2^log_size.
+    /// The sum of the fractions over the trace. claimed_sum / 2^log_size is subtracted from each
+    /// row, to make the total sum of the last column zero, see [EvalAtRow::logup_cumsum_shift].
     /// This is used to make the constraint uniform.
-    pub cumsum_shift: SecureField,
+    pub claimed_sum: SecureField,
+    /// The log size of the trace domain.
+    pub log_size: u32,
     /// The evaluation of the last cumulative sum column.
     pub prev_col_cumsum: E::EF,
 }
@@ -41,9 +44,10 @@ impl<const BATCH_SIZE: usize, E: EvalAtRow> LogupAtRow<BATCH_SIZE, E> {
     pub fn new(interaction: usize, claimed_sum: SecureField, log_size: u32) -> Self {
         Self {
             interaction,
-            queue: [(E::EF::zero(), E::EF::zero()); BATCH_SIZE],
+            queue: std::array::from_fn(|_| (E::EF::zero(), E::EF::zero())),
             queue_size: 0,
-            cumsum_shift: claimed_sum / BaseField::from_u32_unchecked(1 << log_size),
+            claimed_sum,
+            log_size,
             prev_col_cumsum: E::EF::zero(),
         }
     }
@@ -54,7 +58,7 @@ impl<const BATCH_SIZE: usize, E: EvalAtRow> LogupAtRow<BATCH_SIZE, E> {
         values: &[E::F],
         lookup_elements: &LookupElements<N>,
     ) {
-        let shifted_value = lookup_elements.combine(values);
+        let shifted_value = eval.combine_lookup(lookup_elements, values);
         self.push_frac(eval, numerator, shifted_value);
     }
 
@@ -69,9 +73,9 @@ impl<const BATCH_SIZE: usize, E: EvalAtRow> LogupAtRow<BATCH_SIZE, E> {
         let (num, denom) = self
             .queue
             .iter()
-            .copied()
+            .cloned()
             .fold((E::EF::zero(), E::EF::one()), |(p0, q0), (pi, qi)| {
-                (p0 * qi + pi * q0, qi * q0)
+                (p0 * qi.clone() + pi * q0.clone(), qi * q0)
             });
 
         self.queue[0] = (numerator, denominator);
@@ -79,9 +83,10 @@ impl<const BATCH_SIZE: usize, E: EvalAtRow> LogupAtRow<BATCH_SIZE, E> {
 
         // Add a constraint that num / denom = diff.
         let cur_cumsum = E::combine_ef(std::array::from_fn(|_| {
-            eval.next_interaction_mask(self.interaction, [0])[0]
+            let [value] = eval.next_interaction_mask(self.interaction, [0]);
+            value
         }));
-        let diff = cur_cumsum - self.prev_col_cumsum;
+        let diff = cur_cumsum.clone() - self.prev_col_cumsum.clone();
         self.prev_col_cumsum = cur_cumsum;
         eval.add_constraint(diff * denom - num);
     }
@@ -89,21 +94,21 @@ impl<const BATCH_SIZE: usize, E: EvalAtRow> LogupAtRow<BATCH_SIZE, E> {
     pub fn finalize(self, eval: &mut E) {
         let (num, denom) = self.queue[0..self.queue_size]
             .iter()
-            .copied()
+            .cloned()
             .fold((E::EF::zero(), E::EF::one()), |(p0, q0), (pi, qi)| {
-                (p0 * qi + pi * q0, qi * q0)
+                (p0 * qi.clone() + pi * q0.clone(), qi * q0)
             });
 
         let cumsum_mask =
             std::array::from_fn(|_| eval.next_interaction_mask(self.interaction, [0, -1]));
-        let cur_cumsum = E::combine_ef(cumsum_mask.map(|[cur_row, _prev_row]| cur_row));
+        let cur_cumsum = E::combine_ef(cumsum_mask.clone().map(|[cur_row, _prev_row]| cur_row));
         let prev_row_cumsum = E::combine_ef(cumsum_mask.map(|[_cur_row, prev_row]| prev_row));
 
         let diff = cur_cumsum - prev_row_cumsum - self.prev_col_cumsum;
         // Instead of checking diff = num / denom, check diff = num / denom - cumsum_shift.
         // This makes (num / denom - cumsum_shift) have sum zero, which makes the constraint
         // uniform - apply on all rows.
-        let fixed_diff = diff + self.cumsum_shift;
+        let fixed_diff = diff + eval.logup_cumsum_shift(self.claimed_sum, self.log_size);
 
         eval.add_constraint(fixed_diff * denom - num);
     }
@@ -119,6 +124,30 @@ pub struct LookupElements<const N: usize> {
 impl<const N: usize> LookupElements<N> {
     pub fn draw(channel: &mut impl Channel) -> Self {
         let [z, alpha] = channel.draw_felts(2).try_into().unwrap();
+        Self::from_z_alpha(z, alpha)
+    }
+
+    /// Returns the ids of `z` and `alpha` of the lookup elements named `name`, in the order
+    /// [Self::draw] draws them. Declaring them as the challenges of a [Phase] draws the same
+    /// transcript as [Self::draw].
+    pub fn interaction_element_ids(name: &str) -> [InteractionElementId; 2] {
+        ["z", "alpha"].map(|element| InteractionElementId(format!("{name}_{element}")))
+    }
+
+    /// Returns the lookup elements named `name`, drawn by a [Phase] declaring
+    /// [Self::interaction_element_ids].
+    pub fn from_interaction_elements(
+        interaction_elements: &InteractionElements,
+        name: &str,
+    ) -> Result<Self, LookupError> {
+        let [z_id, alpha_id] = Self::interaction_element_ids(name);
+        Ok(Self::from_z_alpha(
+            interaction_elements.get(&z_id)?,
+            interaction_elements.get(&alpha_id)?,
+        ))
+    }
+
+    fn from_z_alpha(z: SecureField, alpha: SecureField) -> Self {
         let mut cur = SecureField::one();
         let alpha_powers = std::array::from_fn(|_| {
             let res = cur;
@@ -131,15 +160,15 @@ impl<const N: usize> LookupElements<N> {
             alpha_powers,
         }
     }
-    pub fn combine<F: Copy, EF>(&self, values: &[F]) -> EF
+    pub fn combine<F: Clone, EF>(&self, values: &[F]) -> EF
     where
-        EF: Copy + Zero + From<F> + From<SecureField> + Mul<F, Output = EF> + Sub<EF, Output = EF>,
+        EF: Clone + Zero + From<F> + From<SecureField> + Mul<F, Output = EF> + Sub<EF, Output = EF>,
     {
         values
             .iter()
             .zip(self.alpha_powers.iter())
-            .fold(EF::zero(), |acc, (&value, &power)| {
-                acc + EF::from(power) * value
+            .fold(EF::zero(), |acc, (value, &power)| {
+                acc + EF::from(power) * value.clone()
             })
             - EF::from(self.z)
     }
@@ -2,26 +2,76 @@
 mod assert;
 mod component;
 pub mod constant_columns;
+mod cpu_domain;
+pub mod expr;
+pub mod gkr;
 mod info;
 pub mod logup;
 mod point;
 mod simd_domain;
 
 use std::array;
-use std::fmt::Debug;
+use std::fmt::{self, Debug, Display};
 use std::ops::{Add, AddAssign, Mul, Neg, Sub};
 
-pub use assert::{assert_constraints, AssertEvaluator};
-pub use component::FrameworkComponent;
-pub use info::InfoEvaluator;
+pub use assert::{
+    assert_constraints, AssertEvaluator, ConstraintFailure, ConstraintReport, TraceEvals,
+};
+pub use component::{FrameworkComponent, FrameworkEval};
+use constant_columns::PreprocessedColumn;
+pub use cpu_domain::CpuDomainEvaluator;
+pub use info::{Degree, InfoEvaluator};
+use logup::LookupElements;
 use num_traits::{One, Zero};
 pub use point::PointEvaluator;
+use serde::{Deserialize, Serialize};
 pub use simd_domain::SimdDomainEvaluator;
 
+pub use crate::core::air::PREPROCESSED_TRACE_IDX;
+use crate::core::circle::CirclePoint;
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
 use crate::core::fields::FieldExpOps;
+use crate::core::poly::circle::CanonicCoset;
+
+/// The index of the tree holding the original trace of the components.
+pub const ORIGINAL_TRACE_IDX: usize = 1;
+/// The index of the tree holding the interaction trace of the components, e.g. logup columns.
+pub const INTERACTION_TRACE_IDX: usize = 2;
+
+/// The row of a trace domain a boundary constraint applies to, see
+/// [EvalAtRow::add_boundary_constraint]. Rows are indexed in the trace domain order, as in
+/// [PreprocessedColumn].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Boundary {
+    First,
+    Last,
+}
+
+impl Boundary {
+    /// Returns the index of the row in the trace domain of size `2^log_size`.
+    pub fn row(&self, log_size: u32) -> usize {
+        match self {
+            Self::First => 0,
+            Self::Last => (1 << log_size) - 1,
+        }
+    }
+
+    /// Returns the point of the row in the trace domain of size `2^log_size`.
+    pub fn point(&self, log_size: u32) -> CirclePoint<BaseField> {
+        CanonicCoset::new(log_size).at(self.row(log_size))
+    }
+}
+
+impl Display for Boundary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::First => write!(f, "first row"),
+            Self::Last => write!(f, "last row"),
+        }
+    }
+}
 
 /// A trait for evaluating expressions at some point or row.
 pub trait EvalAtRow {
@@ -30,7 +80,7 @@ pub trait EvalAtRow {
     /// constraints. It might be [BaseField] packed types, or even [SecureField], when evaluating
     /// the columns out of domain.
     type F: FieldExpOps
-        + Copy
+        + Clone
         + Debug
         + Zero
         + Neg<Output = Self::F>
@@ -47,7 +97,7 @@ pub trait EvalAtRow {
     /// A field type representing the closure of `F` with multiplying by [SecureField]. Constraints
     /// usually get multiplied by [SecureField] values for security.
     type EF: One
-        + Copy
+        + Clone
         + Debug
         + Zero
         + From<Self::F>
@@ -57,14 +107,27 @@ pub trait EvalAtRow {
         + Mul<SecureField, Output = Self::EF>
         + Add<Self::F, Output = Self::EF>
         + Mul<Self::F, Output = Self::EF>
+        + Add<Self::EF, Output = Self::EF>
         + Sub<Self::EF, Output = Self::EF>
         + Mul<Self::EF, Output = Self::EF>
         + From<SecureField>
         + From<Self::F>;
 
-    /// Returns the next mask value for the first interaction at offset 0.
+    /// Returns the next mask value for the original trace at offset 0.
     fn next_trace_mask(&mut self) -> Self::F {
-        let [mask_item] = self.next_interaction_mask(0, [0]);
+        let [mask_item] = self.next_interaction_mask(ORIGINAL_TRACE_IDX, [0]);
+        mask_item
+    }
+
+    /// Returns the value of a preprocessed column at offset 0.
+    /// Preprocessed columns are shared by all the components, in the tree at
+    /// [PREPROCESSED_TRACE_IDX]. The component maps the columns it reads, in order, to their index
+    /// in that tree, see [FrameworkEval::preprocessed_column_indices].
+    /// The default reads the next column of the component's preprocessed trace: `column` only
+    /// names it for the evaluators that record it, such as [InfoEvaluator], from which the
+    /// indices are computed with [constant_columns::PreprocessedColumnRegistry::add_component].
+    fn get_preprocessed_column(&mut self, _column: PreprocessedColumn) -> Self::F {
+        let [mask_item] = self.next_interaction_mask(PREPROCESSED_TRACE_IDX, [0]);
         mask_item
     }
 
@@ -83,7 +146,24 @@ pub trait EvalAtRow {
         offsets: [isize; N],
     ) -> [Self::EF; N] {
         let res_col_major = array::from_fn(|_| self.next_interaction_mask(interaction, offsets));
-        array::from_fn(|i| Self::combine_ef(res_col_major.map(|c| c[i])))
+        array::from_fn(|i| Self::combine_ef(res_col_major.each_ref().map(|c| c[i].clone())))
+    }
+
+    /// Combines `values` into a single value with the lookup elements, see
+    /// [LookupElements::combine].
+    fn combine_lookup<const N: usize>(
+        &mut self,
+        lookup_elements: &LookupElements<N>,
+        values: &[Self::F],
+    ) -> Self::EF {
+        lookup_elements.combine(values)
+    }
+
+    /// Returns the shift of each row of the last logup column, `claimed_sum / 2^log_size`, see
+    /// [logup::LogupAtRow]. Evaluators exporting the constraints keep the claimed sum symbolic,
+    /// as it differs from trace to trace.
+    fn logup_cumsum_shift(&mut self, claimed_sum: SecureField, log_size: u32) -> Self::EF {
+        Self::EF::from(claimed_sum / BaseField::from_u32_unchecked(1 << log_size))
     }
 
     /// Adds a constraint to the component.
@@ -91,6 +171,38 @@ pub trait EvalAtRow {
     where
         Self::EF: Mul<G, Output = Self::EF>;
 
+    /// Adds a constraint to the component, with a name used to report it when it is not satisfied.
+    fn add_constraint_named<G>(&mut self, _name: &str, constraint: G)
+    where
+        Self::EF: Mul<G, Output = Self::EF>,
+    {
+        self.add_constraint(constraint);
+    }
+
+    /// Adds a boundary constraint, requiring `value` to equal `public_value` at the `boundary` row
+    /// of the current trace domain, e.g. binding the trace to a public input of the
+    /// [crate::core::Statement].
+    /// Unlike [Self::add_constraint], the constraint only vanishes at the point of that row, and is
+    /// divided by its vanishing polynomial, see [crate::core::constraints::point_vanishing]. No
+    /// selector column such as [PreprocessedColumn::IsFirst] needs to be committed.
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    );
+
+    /// Moves the evaluation to the trace domain of size `2^log_size`.
+    /// Columns requested and constraints added after this call belong to that domain: mask offsets
+    /// are steps in it, and constraints are required to vanish on it. The evaluation starts on the
+    /// domain of [FrameworkEval::log_size].
+    ///
+    /// Evaluators of a single trace domain may keep the default, which panics: the components
+    /// moving to another domain can't be evaluated by them.
+    fn enter_domain(&mut self, log_size: u32) {
+        panic!("The evaluator does not support moving to the trace domain of log size {log_size}.");
+    }
+
     /// Combines 4 base field values into a single extension field value.
     fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF;
 }
@@ -1,10 +1,15 @@
 use std::ops::Mul;
 
-use super::EvalAtRow;
+use super::{Boundary, EvalAtRow};
 use crate::core::air::accumulation::PointEvaluationAccumulator;
+use crate::core::circle::CirclePoint;
+use crate::core::constraints::{coset_vanishing, point_vanishing};
+use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::fields::FieldExpOps;
 use crate::core::pcs::TreeVec;
+use crate::core::poly::circle::CanonicCoset;
 use crate::core::ColumnVec;
 
 /// Evaluates expressions at a point out of domain.
@@ -13,13 +18,18 @@ pub struct PointEvaluator<'a> {
     pub evaluation_accumulator: &'a mut PointEvaluationAccumulator,
     pub evaluation_hint: Vec<SecureField>,
     pub col_index: Vec<usize>,
+    pub point: CirclePoint<SecureField>,
+    /// The log size of the current trace domain.
+    pub log_size: u32,
+    /// The inverse of the vanishing polynomial of the current trace domain at `point`.
     pub denom_inverse: SecureField,
 }
 impl<'a> PointEvaluator<'a> {
     pub fn new(
         mask: TreeVec<&'a ColumnVec<Vec<SecureField>>>,
         evaluation_accumulator: &'a mut PointEvaluationAccumulator,
-        denom_inverse: SecureField,
+        point: CirclePoint<SecureField>,
+        log_size: u32,
     ) -> Self {
         let col_index = vec![0; mask.len()];
         Self {
@@ -27,7 +37,9 @@ impl<'a> PointEvaluator<'a> {
             evaluation_accumulator,
             evaluation_hint: vec![],
             col_index,
-            denom_inverse,
+            point,
+            log_size,
+            denom_inverse: domain_denom_inverse(log_size, point),
         }
     }
 }
@@ -54,7 +66,26 @@ impl EvalAtRow for PointEvaluator<'_> {
         self.evaluation_hint.push(evaluation);
         self.evaluation_accumulator.accumulate(evaluation);
     }
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    ) {
+        let denom = point_vanishing(boundary.point(self.log_size), self.point);
+        let evaluation = (value - public_value) / denom;
+        self.evaluation_hint.push(evaluation);
+        self.evaluation_accumulator.accumulate(evaluation);
+    }
+    fn enter_domain(&mut self, log_size: u32) {
+        self.log_size = log_size;
+        self.denom_inverse = domain_denom_inverse(log_size, self.point);
+    }
     fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
         SecureField::from_partial_evals(values)
     }
 }
+
+fn domain_denom_inverse(log_size: u32, point: CirclePoint<SecureField>) -> SecureField {
+    coset_vanishing(CanonicCoset::new(log_size).coset, point).inverse()
+}
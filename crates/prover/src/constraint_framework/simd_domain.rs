@@ -1,8 +1,9 @@
+use std::collections::BTreeMap;
 use std::ops::Mul;
 
 use num_traits::Zero;
 
-use super::EvalAtRow;
+use super::{Boundary, EvalAtRow};
 use crate::core::backend::simd::column::VeryPackedBaseColumn;
 use crate::core::backend::simd::m31::LOG_N_LANES;
 use crate::core::backend::simd::very_packed_m31::{
@@ -26,10 +27,22 @@ pub struct SimdDomainEvaluator<'a> {
     /// The row index of the simd-vector row to evaluate the constraints at.
     pub vec_row: usize,
     pub random_coeff_powers: &'a [SecureField],
+    /// The combined constraint quotients of the trace domains that were already left.
     pub row_res: VeryPackedSecureField,
+    /// The combined constraints of the current trace domain, not yet divided by its vanishing
+    /// polynomial.
+    pub domain_res: VeryPackedSecureField,
     pub constraint_index: usize,
     pub domain_log_size: u32,
     pub eval_domain_log_size: u32,
+    /// Inverses of the vanishing polynomial of each trace domain, by log size. For a trace domain
+    /// of log size `n`, holds the `2^(eval_domain_log_size - n)` distinct values on the evaluation
+    /// domain, in bit-reversed order.
+    pub denom_inv: &'a BTreeMap<u32, Vec<BaseField>>,
+    /// Inverses of the vanishing polynomial of the point of each boundary constraint, by trace
+    /// domain log size and boundary, on every point of the evaluation domain in bit-reversed
+    /// order.
+    pub boundary_denom_inv: &'a BTreeMap<(u32, Boundary), Vec<BaseField>>,
 }
 impl<'a> SimdDomainEvaluator<'a> {
     pub fn new(
@@ -38,6 +51,8 @@ impl<'a> SimdDomainEvaluator<'a> {
         random_coeff_powers: &'a [SecureField],
         domain_log_size: u32,
         eval_log_size: u32,
+        denom_inv: &'a BTreeMap<u32, Vec<BaseField>>,
+        boundary_denom_inv: &'a BTreeMap<(u32, Boundary), Vec<BaseField>>,
     ) -> Self {
         Self {
             trace_eval,
@@ -45,11 +60,31 @@ impl<'a> SimdDomainEvaluator<'a> {
             vec_row,
             random_coeff_powers,
             row_res: VeryPackedSecureField::zero(),
+            domain_res: VeryPackedSecureField::zero(),
             constraint_index: 0,
             domain_log_size,
             eval_domain_log_size: eval_log_size,
+            denom_inv,
+            boundary_denom_inv,
         }
     }
+
+    /// Returns the combined constraint quotients of all the trace domains at the row.
+    pub fn finalize(mut self) -> VeryPackedSecureField {
+        self.divide_domain_res();
+        self.row_res
+    }
+
+    /// Divides the constraints of the current trace domain by its vanishing polynomial, and moves
+    /// them to `row_res`.
+    fn divide_domain_res(&mut self) {
+        // The vanishing polynomial is constant on each run of `2^domain_log_size` points of the
+        // bit-reversed evaluation domain.
+        let denom_inv = self.denom_inv[&self.domain_log_size]
+            [self.vec_row >> (self.domain_log_size - LOG_N_LANES - LOG_N_VERY_PACKED_ELEMS)];
+        self.row_res += self.domain_res * VeryPackedBaseField::broadcast(denom_inv);
+        self.domain_res = VeryPackedSecureField::zero();
+    }
 }
 impl EvalAtRow for SimdDomainEvaluator<'_> {
     type F = VeryPackedBaseField;
@@ -94,12 +129,35 @@ impl EvalAtRow for SimdDomainEvaluator<'_> {
     where
         Self::EF: Mul<G, Output = Self::EF>,
     {
-        self.row_res +=
+        self.domain_res +=
             VeryPackedSecureField::broadcast(self.random_coeff_powers[self.constraint_index])
                 * constraint;
         self.constraint_index += 1;
     }
 
+    fn add_boundary_constraint(
+        &mut self,
+        boundary: Boundary,
+        value: Self::F,
+        public_value: BaseField,
+    ) {
+        let denom_inv = &self.boundary_denom_inv[&(self.domain_log_size, boundary)];
+        let row_denom_inv = VeryPackedBaseField::from_array(std::array::from_fn(|i| {
+            denom_inv[(self.vec_row << (LOG_N_LANES + LOG_N_VERY_PACKED_ELEMS)) + i]
+        }));
+        self.row_res +=
+            VeryPackedSecureField::broadcast(self.random_coeff_powers[self.constraint_index])
+                * ((value - VeryPackedBaseField::broadcast(public_value)) * row_denom_inv);
+        self.constraint_index += 1;
+    }
+
+    fn enter_domain(&mut self, log_size: u32) {
+        if log_size != self.domain_log_size {
+            self.divide_domain_res();
+            self.domain_log_size = log_size;
+        }
+    }
+
     fn combine_ef(values: [Self::F; SECURE_EXTENSION_DEGREE]) -> Self::EF {
         VeryPackedSecureField::from_very_packed_m31s(values)
     }
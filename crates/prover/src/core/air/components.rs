@@ -1,14 +1,19 @@
+use std::collections::BTreeSet;
+
 use itertools::{zip_eq, Itertools};
 
 use super::accumulation::{DomainEvaluationAccumulator, PointEvaluationAccumulator};
-use super::{Component, ComponentProver, ComponentTrace};
+use super::{
+    Component, ComponentProver, ComponentTrace, RelationClaimedSum, PREPROCESSED_TRACE_IDX,
+};
 use crate::core::backend::{Backend, BackendForChannel};
 use crate::core::channel::MerkleChannel;
 use crate::core::circle::CirclePoint;
 use crate::core::fields::qm31::SecureField;
+use crate::core::lookups::gkr_lookups::GkrLookup;
 use crate::core::pcs::{CommitmentTreeProver, TreeVec};
-use crate::core::poly::circle::SecureCirclePoly;
-use crate::core::{ColumnVec, InteractionElements, LookupValues};
+use crate::core::poly::circle::{CirclePoly, SecureCirclePoly};
+use crate::core::{ColumnVec, InteractionElements, LookupValueId, LookupValues};
 
 pub struct Components<'a>(pub Vec<&'a dyn Component>);
 
@@ -21,11 +26,26 @@ impl Components<'_> {
             .unwrap()
     }
 
+    /// Returns the number of columns in the preprocessed trace shared by the components.
+    pub fn n_preprocessed_columns(&self) -> usize {
+        self.0
+            .iter()
+            .flat_map(|component| component.preprocessed_column_indices())
+            .max()
+            .map_or(0, |max_index| max_index + 1)
+    }
+
     pub fn mask_points(
         &self,
         point: CirclePoint<SecureField>,
     ) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
-        TreeVec::concat_cols(self.0.iter().map(|component| component.mask_points(point)))
+        let mut mask_points =
+            TreeVec::concat_cols(self.0.iter().map(|component| component.mask_points(point)));
+        // Preprocessed columns are shared, and sampled once at the point.
+        let n_trees = mask_points.len().max(PREPROCESSED_TRACE_IDX + 1);
+        mask_points.resize(n_trees, vec![]);
+        mask_points[PREPROCESSED_TRACE_IDX] = vec![vec![point]; self.n_preprocessed_columns()];
+        mask_points
     }
 
     pub fn eval_composition_polynomial_at_point(
@@ -49,12 +69,62 @@ impl Components<'_> {
         evaluation_accumulator.finalize()
     }
 
+    /// Returns the claimed sums of each component.
+    pub fn claimed_sums(&self) -> Vec<Vec<RelationClaimedSum>> {
+        self.0
+            .iter()
+            .map(|component| component.claimed_sums())
+            .collect()
+    }
+
+    /// Returns the public claimed sums of all the components, see
+    /// [Component::public_claimed_sums].
+    pub fn public_claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        self.0
+            .iter()
+            .flat_map(|component| component.public_claimed_sums())
+            .collect()
+    }
+
+    /// Returns the ids of the lookup values read by the components.
+    pub fn lookup_value_ids(&self) -> BTreeSet<LookupValueId> {
+        self.0
+            .iter()
+            .flat_map(|component| component.lookup_value_ids())
+            .collect()
+    }
+
+    /// Returns the lookups of all the components proven with GKR, in order.
+    pub fn gkr_lookups(&self) -> Vec<GkrLookup> {
+        self.0
+            .iter()
+            .flat_map(|component| component.gkr_lookups())
+            .collect()
+    }
+
+    /// Returns the log sizes of the committed columns of each trace tree.
+    ///
+    /// Columns are committed extended to the composition log degree bound, since FRI folds a
+    /// single column size.
     pub fn column_log_sizes(&self) -> TreeVec<ColumnVec<u32>> {
-        TreeVec::concat_cols(
+        let composition_log_degree_bound = self.composition_log_degree_bound();
+        let mut column_log_sizes = TreeVec::concat_cols(
             self.0
                 .iter()
                 .map(|component| component.trace_log_degree_bounds()),
-        )
+        );
+        let n_trees = column_log_sizes.len().max(PREPROCESSED_TRACE_IDX + 1);
+        column_log_sizes.resize(n_trees, vec![]);
+        let mut preprocessed_log_sizes = vec![0; self.n_preprocessed_columns()];
+        for component in &self.0 {
+            let log_sizes = component.trace_log_degree_bounds();
+            let log_sizes = log_sizes.get(PREPROCESSED_TRACE_IDX).into_iter().flatten();
+            for (index, &log_size) in zip_eq(component.preprocessed_column_indices(), log_sizes) {
+                preprocessed_log_sizes[index] = preprocessed_log_sizes[index].max(log_size);
+            }
+        }
+        column_log_sizes[PREPROCESSED_TRACE_IDX] = preprocessed_log_sizes;
+        column_log_sizes.map_cols(|log_size| log_size.max(composition_log_degree_bound))
     }
 }
 
@@ -95,37 +165,61 @@ impl<B: Backend> ComponentProvers<'_, B> {
     where
         B: BackendForChannel<MC>,
     {
-        let mut poly_iters = trees
-            .iter()
-            .map(|tree| tree.polynomials.iter())
-            .collect_vec();
-        let mut eval_iters = trees
-            .iter()
-            .map(|tree| tree.evaluations.iter())
-            .collect_vec();
+        let polys = self.split_columns(&TreeVec::new(
+            trees
+                .iter()
+                .map(|tree| tree.polynomials.iter().collect())
+                .collect(),
+        ));
+        let evals = self.split_columns(&TreeVec::new(
+            trees
+                .iter()
+                .map(|tree| tree.evaluations.iter().collect())
+                .collect(),
+        ));
+        zip_eq(polys, evals)
+            .map(|(polys, evals)| ComponentTrace::new(polys, evals))
+            .collect()
+    }
+
+    /// Returns the polynomials of each component, out of the polynomials of the trace trees, e.g.
+    /// [crate::core::pcs::TreeBuilder::trace_polys].
+    pub fn component_polys<'b>(
+        &self,
+        trace_polys: &TreeVec<ColumnVec<&'b CirclePoly<B>>>,
+    ) -> Vec<TreeVec<ColumnVec<&'b CirclePoly<B>>>> {
+        self.split_columns(trace_polys)
+    }
+
+    /// Splits the columns of each tree between the components, in order.
+    fn split_columns<'b, T>(
+        &self,
+        trees: &TreeVec<ColumnVec<&'b T>>,
+    ) -> Vec<TreeVec<ColumnVec<&'b T>>> {
+        let mut column_iters = trees.iter().map(|tree| tree.iter().copied()).collect_vec();
 
         self.0
             .iter()
             .map(|component| {
-                let col_sizes_per_tree = component
-                    .trace_log_degree_bounds()
-                    .iter()
-                    .map(|col_sizes| col_sizes.len())
-                    .collect_vec();
-                let polys = col_sizes_per_tree
-                    .iter()
-                    .zip(poly_iters.iter_mut())
-                    .map(|(n_columns, iter)| iter.take(*n_columns).collect_vec())
-                    .collect_vec();
-                let evals = col_sizes_per_tree
-                    .iter()
-                    .zip(eval_iters.iter_mut())
-                    .map(|(n_columns, iter)| iter.take(*n_columns).collect_vec())
-                    .collect_vec();
-                ComponentTrace {
-                    polys: TreeVec::new(polys),
-                    evals: TreeVec::new(evals),
+                let mut columns = TreeVec::new(
+                    component
+                        .trace_log_degree_bounds()
+                        .iter()
+                        .zip(column_iters.iter_mut())
+                        .map(|(col_sizes, iter)| iter.take(col_sizes.len()).collect_vec())
+                        .collect_vec(),
+                );
+                // Preprocessed columns are shared, and picked by index.
+                if let Some(tree) = trees.get(PREPROCESSED_TRACE_IDX) {
+                    if columns.len() > PREPROCESSED_TRACE_IDX {
+                        columns[PREPROCESSED_TRACE_IDX] = component
+                            .preprocessed_column_indices()
+                            .iter()
+                            .map(|&i| tree[i])
+                            .collect();
+                    }
                 }
+                columns
             })
             .collect_vec()
     }
@@ -1,19 +1,30 @@
 pub use components::{ComponentProvers, Components};
+use serde::{Deserialize, Serialize};
 
 use self::accumulation::{DomainEvaluationAccumulator, PointEvaluationAccumulator};
 use super::backend::Backend;
 use super::circle::CirclePoint;
 use super::fields::m31::BaseField;
 use super::fields::qm31::SecureField;
+use super::lookups::gkr_lookups::GkrLookup;
 use super::pcs::TreeVec;
 use super::poly::circle::{CircleEvaluation, CirclePoly};
 use super::poly::BitReversedOrder;
-use super::{ColumnVec, InteractionElements, LookupValues};
+use super::{ColumnVec, InteractionElements, LookupValueId, LookupValues};
 
 pub mod accumulation;
 mod components;
 pub mod mask;
 
+/// The index of the tree holding the preprocessed trace. The preprocessed trace is shared by all
+/// the components, which refer to its columns by index, see
+/// [Component::preprocessed_column_indices].
+///
+/// The preprocessed trace is committed first, ahead of the original and the interaction traces.
+/// Components that read the constant columns from the last tree, after the original trace (tree 0)
+/// and the interaction trace (tree 1), must shift their tree indices.
+pub const PREPROCESSED_TRACE_IDX: usize = 0;
+
 /// Arithmetic Intermediate Representation (AIR).
 /// An Air instance is assumed to already contain all the information needed to
 /// evaluate the constraints.
@@ -28,6 +39,15 @@ pub trait AirProver<B: Backend>: Air {
     fn component_provers(&self) -> Vec<&dyn ComponentProver<B>>;
 }
 
+/// The sum a component claims for a lookup relation, e.g. the claimed sum of its logup columns.
+/// The claimed sums of all the components using a relation must add up to zero.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RelationClaimedSum {
+    /// The name of the lookup relation.
+    pub relation: String,
+    pub claimed_sum: SecureField,
+}
+
 /// A component is a set of trace columns of various sizes along with a set of
 /// constraints on them.
 pub trait Component {
@@ -39,6 +59,28 @@ pub trait Component {
     /// `n_interaction_phases`.
     fn trace_log_degree_bounds(&self) -> TreeVec<ColumnVec<u32>>;
 
+    /// Returns the index in the preprocessed trace of each column of the component's
+    /// [PREPROCESSED_TRACE_IDX] tree, in order. Unlike the other trees, the columns of that tree
+    /// are shared by all the components.
+    fn preprocessed_column_indices(&self) -> ColumnVec<usize>;
+
+    /// Returns the sums the component claims for the lookup relations it uses, from its trace.
+    /// They are recorded in the proof, and the verifier builds the component from them.
+    fn claimed_sums(&self) -> Vec<RelationClaimedSum>;
+
+    /// Returns the sums of the lookups of values known to the verifier, such as public inputs.
+    /// Unlike [Self::claimed_sums], the verifier computes them instead of reading them from the
+    /// proof.
+    fn public_claimed_sums(&self) -> Vec<RelationClaimedSum>;
+
+    /// Returns the ids of the lookup values the component reads, see
+    /// [ComponentProver::lookup_values].
+    fn lookup_value_ids(&self) -> Vec<LookupValueId>;
+
+    /// Returns the lookups the component proves with GKR instead of committed logup columns, in
+    /// the order of the instances of the GKR proof, see [crate::core::lookups::gkr_lookups].
+    fn gkr_lookups(&self) -> Vec<GkrLookup>;
+
     /// Returns the mask points for each trace column. The returned TreeVec should be of size
     /// `n_interaction_phases`.
     fn mask_points(
@@ -70,6 +112,18 @@ pub trait ComponentProver<B: Backend>: Component {
 
     /// Returns the values needed to evaluate the components lookup boundary constraints.
     fn lookup_values(&self, _trace: &ComponentTrace<'_, B>) -> LookupValues;
+
+    /// Checks the constraints row by row on the trace domain, and returns a report of the
+    /// unsatisfied ones. Used to debug a trace, as it is much cheaper than proving it. Only the
+    /// polynomials of the trace are needed, so the last trace tree can be checked before it is
+    /// committed.
+    /// Components that cannot evaluate their constraints on the trace domain do not check anything.
+    fn check_constraints_on_trace(
+        &self,
+        _trace_polys: &TreeVec<ColumnVec<&CirclePoly<B>>>,
+    ) -> Result<(), String> {
+        Ok(())
+    }
 }
 
 /// A component trace is a set of polynomials for each column on that component.
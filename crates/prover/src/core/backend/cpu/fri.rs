@@ -13,8 +13,9 @@ impl FriOps for CpuBackend {
         eval: &LineEvaluation<Self>,
         alpha: SecureField,
         _twiddles: &TwiddleTree<Self>,
+        fold_step: u32,
     ) -> LineEvaluation<Self> {
-        fold_line(eval, alpha)
+        fold_line(eval, alpha, fold_step)
     }
     fn fold_circle_into_line(
         dst: &mut LineEvaluation<Self>,
@@ -32,6 +32,10 @@ impl GkrOps for CpuBackend {
             Layer::LogUpSingles { denominators } => {
                 next_logup_layer(MleExpr::Constant(BaseField::one()), denominators)
             }
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => next_fraction_product_layer(numerators, denominators),
         }
     }
 
@@ -60,6 +64,10 @@ impl GkrOps for CpuBackend {
             Layer::LogUpSingles { denominators } => {
                 eval_logup_singles_sum(eq_evals, denominators, n_terms, lambda)
             }
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => eval_fraction_product_sum(eq_evals, numerators, denominators, n_terms, lambda),
         };
 
         eval_at_0 *= h.eq_fixed_var_correction;
@@ -105,6 +113,29 @@ fn eval_grand_product_sum(
     (eval_at_0, eval_at_2)
 }
 
+/// Evaluates `sum_x eq(({0}^|r|, 0, x), y) * (inp_numer(r, t, x, 0) * inp_numer(r, t, x, 1) +
+/// lambda * inp_denom(r, t, x, 0) * inp_denom(r, t, x, 1))` at `t=0` and `t=2`.
+///
+/// Output of the form: `(eval_at_0, eval_at_2)`.
+fn eval_fraction_product_sum(
+    eq_evals: &EqEvals<CpuBackend>,
+    input_numerators: &Mle<CpuBackend, SecureField>,
+    input_denominators: &Mle<CpuBackend, SecureField>,
+    n_terms: usize,
+    lambda: SecureField,
+) -> (SecureField, SecureField) {
+    // Both products are linear in the evaluations of `eq`, so they are summed separately.
+    let (numer_eval_at_0, numer_eval_at_2) =
+        eval_grand_product_sum(eq_evals, input_numerators, n_terms);
+    let (denom_eval_at_0, denom_eval_at_2) =
+        eval_grand_product_sum(eq_evals, input_denominators, n_terms);
+
+    (
+        numer_eval_at_0 + lambda * denom_eval_at_0,
+        numer_eval_at_2 + lambda * denom_eval_at_2,
+    )
+}
+
 /// Evaluates `sum_x eq(({0}^|r|, 0, x), y) * (inp_numer(r, t, x, 0) * inp_denom(r, t, x, 1) +
 /// inp_numer(r, t, x, 1) * inp_denom(r, t, x, 0) + lambda * inp_denom(r, t, x, 0) * inp_denom(r, t,
 /// x, 1))` at `t=0` and `t=2`.
@@ -258,6 +289,18 @@ fn next_grand_product_layer(layer: &Mle<CpuBackend, SecureField>) -> Layer<CpuBa
     Layer::GrandProduct(Mle::new(res))
 }
 
+fn next_fraction_product_layer(
+    numerators: &Mle<CpuBackend, SecureField>,
+    denominators: &Mle<CpuBackend, SecureField>,
+) -> Layer<CpuBackend> {
+    let next_numerators = numerators.array_chunks().map(|&[a, b]| a * b).collect();
+    let next_denominators = denominators.array_chunks().map(|&[a, b]| a * b).collect();
+    Layer::FractionProduct {
+        numerators: Mle::new(next_numerators),
+        denominators: Mle::new(next_denominators),
+    }
+}
+
 fn next_logup_layer<F>(
     numerators: MleExpr<'_, F>,
     denominators: &Mle<CpuBackend, SecureField>,
@@ -421,13 +464,13 @@ mod tests {
             ood_point,
             claims_to_verify_by_instance,
             n_variables_by_instance: _,
-        } = partially_verify_batch(vec![Gate::LogUp], &proof, &mut test_channel())?;
+        } = partially_verify_batch(vec![Gate::LogUpSingles], &proof, &mut test_channel())?;
 
         assert_eq!(claims_to_verify_by_instance.len(), 1);
         assert_eq!(proof.output_claims_by_instance.len(), 1);
         assert_eq!(
             claims_to_verify_by_instance[0],
-            [SecureField::one(), denominators.eval_at_point(&ood_point)]
+            [denominators.eval_at_point(&ood_point)]
         );
         assert_eq!(
             proof.output_claims_by_instance[0],
@@ -474,4 +517,60 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn logup_with_singles_trace_fails_with_logup_gate() {
+        const N: usize = 1 << 5;
+        let denominators = test_channel().draw_felts(N);
+        let top_layer = Layer::LogUpSingles {
+            denominators: Mle::<CpuBackend, SecureField>::new(denominators),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let result = partially_verify_batch(vec![Gate::LogUp], &proof, &mut test_channel());
+
+        assert!(matches!(
+            result,
+            Err(GkrError::InvalidMask {
+                instance: 0,
+                instance_layer: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn fraction_product_works() -> Result<(), GkrError> {
+        const N: usize = 1 << 5;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let numerator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let denominator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let numerator_product = numerator_values.iter().product::<SecureField>();
+        let denominator_product = denominator_values.iter().product::<SecureField>();
+        let numerators = Mle::<CpuBackend, SecureField>::new(numerator_values);
+        let denominators = Mle::<CpuBackend, SecureField>::new(denominator_values);
+        let top_layer = Layer::FractionProduct {
+            numerators: numerators.clone(),
+            denominators: denominators.clone(),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let GkrArtifact {
+            ood_point,
+            claims_to_verify_by_instance,
+            n_variables_by_instance: _,
+        } = partially_verify_batch(vec![Gate::FractionProduct], &proof, &mut test_channel())?;
+
+        assert_eq!(
+            proof.output_claims_by_instance,
+            [vec![numerator_product, denominator_product]]
+        );
+        assert_eq!(
+            claims_to_verify_by_instance,
+            [vec![
+                numerators.eval_at_point(&ood_point),
+                denominators.eval_at_point(&ood_point)
+            ]]
+        );
+        Ok(())
+    }
 }
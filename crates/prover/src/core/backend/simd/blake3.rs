@@ -1,7 +1,19 @@
+//! A lane-parallel BLAKE3 Merkle commitment, hashing [N_LANES] nodes at once.
+//!
+//! Each lane of a [u32x16] holds a little-endian message or state word of a different node, so the
+//! compression function runs on all the lanes with [std::simd] (avx512, avx2, neon etc.). Every
+//! hashed message fits a single BLAKE3 chunk.
+
+use std::array;
+use std::simd::cmp::SimdOrd;
+use std::simd::{u32x16, Simd};
+
 use itertools::Itertools;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+use super::m31::{LOG_N_LANES, MODULUS, N_LANES};
+use super::utils::{bws_encoding_len, shl_or_zero, shr_or_zero};
 use crate::core::backend::simd::column::BaseColumn;
 use crate::core::backend::simd::SimdBackend;
 use crate::core::backend::{Column, ColumnOps};
@@ -9,6 +21,19 @@ use crate::core::vcs::blake3_hash::Blake3Hash;
 use crate::core::vcs::blake3_merkle::Blake3MerkleHasher;
 use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
 
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const ROOT: u32 = 1 << 3;
+
+/// The chaining value words of [N_LANES] hashes, one hash per lane.
+type HashWords = [u32x16; 8];
+
 impl ColumnOps<Blake3Hash> for SimdBackend {
     type Column = Vec<Blake3Hash>;
 
@@ -17,25 +42,278 @@ impl ColumnOps<Blake3Hash> for SimdBackend {
     }
 }
 
-// TODO(BWS): not simd at all
 impl MerkleOps<Blake3MerkleHasher> for SimdBackend {
     fn commit_on_layer(
         log_size: u32,
         prev_layer: Option<&Vec<Blake3Hash>>,
         columns: &[&BaseColumn],
     ) -> Vec<Blake3Hash> {
+        // Layers smaller than a vector are hashed node by node.
+        if log_size < LOG_N_LANES {
+            return (0..1 << log_size)
+                .map(|i| {
+                    Blake3MerkleHasher::hash_node(
+                        prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column.at(i)).collect_vec(),
+                    )
+                })
+                .collect();
+        }
+
+        let mut res = vec![Blake3Hash::default(); 1 << log_size];
+
         #[cfg(not(feature = "parallel"))]
-        let iter = 0..1 << log_size;
+        let iter = res.chunks_mut(N_LANES);
 
         #[cfg(feature = "parallel")]
-        let iter = (0..1 << log_size).into_par_iter();
+        let iter = res.par_chunks_mut(N_LANES);
+
+        iter.enumerate().for_each(|(i, chunk)| {
+            let children = prev_layer.map(|prev_layer| {
+                let children = &prev_layer[2 * i * N_LANES..2 * (i + 1) * N_LANES];
+                (
+                    load_hashes(|lane| children[2 * lane]),
+                    load_hashes(|lane| children[2 * lane + 1]),
+                )
+            });
+            let column_values = columns.iter().map(|column| {
+                let values = column.data[i].into_simd();
+                // Packed values are in the range `[0, P]`.
+                values.simd_min(values - MODULUS)
+            });
+            store_hashes(hash_nodes(children, column_values), chunk);
+        });
+        res
+    }
+}
+
+/// Hashes [N_LANES] nodes, as [Blake3MerkleHasher::hash_node].
+fn hash_nodes(
+    children: Option<(HashWords, HashWords)>,
+    column_values: impl DoubleEndedIterator<Item = u32x16>,
+) -> HashWords {
+    // The column values are hash-chained from the last one.
+    let column_hash = column_values.rev().fold(None, |column_hash, values| {
+        Some(hash_encoded_value(values, column_hash))
+    });
+
+    match (children, column_hash) {
+        (Some((left, right)), Some(column_hash)) => {
+            hash_words(&[left, column_hash, right].concat())
+        }
+        (Some((left, right)), None) => hash_words(&[left, right].concat()),
+        (None, Some(column_hash)) => hash_words(&column_hash),
+        (None, None) => hash_words(&[]),
+    }
+}
+
+/// Hashes the [bws_num_to_bytes] encoding of `values`, followed by `suffix` if given.
+///
+/// The encoding takes up to 4 bytes, so the message fits a single block, where its hash is shifted
+/// by the encoding length of each lane.
+///
+/// [bws_num_to_bytes]: crate::core::utils::bws_num_to_bytes
+fn hash_encoded_value(values: u32x16, suffix: Option<HashWords>) -> HashWords {
+    let zero = u32x16::splat(0);
+
+    let encoding_len = bws_encoding_len(values);
+    let (suffix_len, suffix) = suffix.map_or((0, [zero; 8]), |suffix| (32, suffix));
+
+    // The bytes beyond the encoding are zero, so the value is the encoding in little-endian order.
+    let left_shift = encoding_len << u32x16::splat(3);
+    let right_shift = u32x16::splat(u32::BITS) - left_shift;
+    let mut block = [zero; 16];
+    block[0] = values | shl_or_zero(suffix[0], left_shift);
+    for i in 1..suffix.len() {
+        block[i] = shr_or_zero(suffix[i - 1], right_shift) | shl_or_zero(suffix[i], left_shift);
+    }
+    block[suffix.len()] = shr_or_zero(suffix[suffix.len() - 1], right_shift);
+
+    let block_len = encoding_len + u32x16::splat(suffix_len);
+    compress(
+        IV.map(u32x16::splat),
+        block,
+        block_len,
+        CHUNK_START | CHUNK_END | ROOT,
+    )
+}
+
+/// Hashes a message of whole words.
+fn hash_words(words: &[u32x16]) -> HashWords {
+    let n_blocks = words.len().div_ceil(16).max(1);
+    let mut message = words.to_vec();
+    message.resize(16 * n_blocks, u32x16::splat(0));
 
-        iter.map(|i| {
-            Blake3MerkleHasher::hash_node(
-                prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
-                &columns.iter().map(|column| column.at(i)).collect_vec(),
+    message.chunks_exact(16).enumerate().fold(
+        IV.map(u32x16::splat),
+        |chaining_value, (i, block)| {
+            let block_len = (words.len() - 16 * i).min(16) as u32 * 4;
+            let mut flags = 0;
+            if i == 0 {
+                flags |= CHUNK_START;
+            }
+            if i == n_blocks - 1 {
+                flags |= CHUNK_END | ROOT;
+            }
+            compress(
+                chaining_value,
+                block.try_into().unwrap(),
+                u32x16::splat(block_len),
+                flags,
             )
-        })
-        .collect()
+        },
+    )
+}
+
+/// The BLAKE3 compression function of a block of the first chunk, applied to each lane.
+///
+/// Returns the next chaining value, which is the hash for the last block of a root chunk.
+fn compress(
+    chaining_value: HashWords,
+    mut block: [u32x16; 16],
+    block_len: u32x16,
+    flags: u32,
+) -> HashWords {
+    let zero = u32x16::splat(0);
+    let [a, b, c, d, e, f, g, h] = chaining_value;
+    let mut v = [
+        a,
+        b,
+        c,
+        d,
+        e,
+        f,
+        g,
+        h,
+        u32x16::splat(IV[0]),
+        u32x16::splat(IV[1]),
+        u32x16::splat(IV[2]),
+        u32x16::splat(IV[3]),
+        // The chunk counter, which is always zero.
+        zero,
+        zero,
+        block_len,
+        u32x16::splat(flags),
+    ];
+
+    for round in 0..7 {
+        if round > 0 {
+            block = MSG_PERMUTATION.map(|i| block[i]);
+        }
+        // Mix the columns.
+        g_mix(&mut v, [0, 4, 8, 12], block[0], block[1]);
+        g_mix(&mut v, [1, 5, 9, 13], block[2], block[3]);
+        g_mix(&mut v, [2, 6, 10, 14], block[4], block[5]);
+        g_mix(&mut v, [3, 7, 11, 15], block[6], block[7]);
+        // Mix the diagonals.
+        g_mix(&mut v, [0, 5, 10, 15], block[8], block[9]);
+        g_mix(&mut v, [1, 6, 11, 12], block[10], block[11]);
+        g_mix(&mut v, [2, 7, 8, 13], block[12], block[13]);
+        g_mix(&mut v, [3, 4, 9, 14], block[14], block[15]);
+    }
+
+    array::from_fn(|i| v[i] ^ v[i + 8])
+}
+
+/// The quarter-round of BLAKE3 on the state words at `indices`.
+fn g_mix(v: &mut [u32x16; 16], [a, b, c, d]: [usize; 4], mx: u32x16, my: u32x16) {
+    v[a] = v[a] + v[b] + mx;
+    v[d] = rotr(v[d] ^ v[a], 16);
+    v[c] = v[c] + v[d];
+    v[b] = rotr(v[b] ^ v[c], 12);
+    v[a] = v[a] + v[b] + my;
+    v[d] = rotr(v[d] ^ v[a], 8);
+    v[c] = v[c] + v[d];
+    v[b] = rotr(v[b] ^ v[c], 7);
+}
+
+fn rotr(x: u32x16, n: u32) -> u32x16 {
+    (x >> Simd::splat(n)) | (x << Simd::splat(u32::BITS - n))
+}
+
+/// Transposes the hashes of the lanes to their words.
+fn load_hashes(hash: impl Fn(usize) -> Blake3Hash) -> HashWords {
+    let hashes: [Blake3Hash; N_LANES] = array::from_fn(hash);
+    array::from_fn(|word| {
+        u32x16::from_array(
+            hashes.map(|hash| {
+                u32::from_le_bytes(hash.0[4 * word..4 * (word + 1)].try_into().unwrap())
+            }),
+        )
+    })
+}
+
+/// Transposes the words of the hashes to the hashes of the lanes.
+fn store_hashes(words: HashWords, dst: &mut [Blake3Hash]) {
+    let words = words.map(|word| word.to_array());
+    for (lane, hash) in dst.iter_mut().enumerate() {
+        for (word, bytes) in hash.0.chunks_exact_mut(4).enumerate() {
+            bytes.copy_from_slice(&words[word][lane].to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{hash_nodes, load_hashes, store_hashes};
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::m31::N_LANES;
+    use crate::core::backend::simd::utils::test_utils::{self, random_hash};
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::vcs::blake3_hash::Blake3Hash;
+    use crate::core::vcs::blake3_merkle::Blake3MerkleHasher;
+    use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
+
+    #[test]
+    fn test_hash_nodes_matches_hash_node() {
+        test_utils::test_hash_nodes_matches_hash_node::<Blake3MerkleHasher>(
+            |children, column_values| {
+                let children = children
+                    .map(|(left, right)| (load_hashes(|i| left[i]), load_hashes(|i| right[i])));
+                let mut res = [Blake3Hash::default(); N_LANES];
+                store_hashes(
+                    hash_nodes(children, column_values.iter().copied()),
+                    &mut res,
+                );
+                res
+            },
+        );
+    }
+
+    #[test]
+    fn test_commit_on_layer_matches_hash_node() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for log_size in [2, 5] {
+            let prev_layer = (0..2 << log_size)
+                .map(|_| random_hash::<Blake3MerkleHasher>(&mut rng))
+                .collect_vec();
+            let columns = (0..5)
+                .map(|_| (0..1 << log_size).map(|_| rng.gen()).collect_vec())
+                .collect_vec();
+            let simd_columns = columns
+                .iter()
+                .map(|column| column.iter().copied().collect::<BaseColumn>())
+                .collect_vec();
+
+            let layer = <SimdBackend as MerkleOps<Blake3MerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&prev_layer),
+                &simd_columns.iter().collect_vec(),
+            );
+
+            let expected = (0..1 << log_size)
+                .map(|i| {
+                    Blake3MerkleHasher::hash_node(
+                        Some((prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column[i]).collect_vec(),
+                    )
+                })
+                .collect_vec();
+            assert_eq!(layer, expected);
+        }
     }
 }
@@ -12,6 +12,7 @@ use crate::core::backend::Column;
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::SecureColumnByCoords;
+use crate::core::fields::FieldExpOps;
 use crate::core::fri::{self, FriOps};
 use crate::core::poly::circle::SecureEvaluation;
 use crate::core::poly::line::LineEvaluation;
@@ -23,37 +24,16 @@ impl FriOps for SimdBackend {
         eval: &LineEvaluation<Self>,
         alpha: SecureField,
         twiddles: &TwiddleTree<Self>,
+        fold_step: u32,
     ) -> LineEvaluation<Self> {
-        let log_size = eval.len().ilog2();
-        if log_size <= LOG_N_LANES {
-            let eval = fri::fold_line(&eval.to_cpu(), alpha);
-            return LineEvaluation::new(eval.domain(), eval.values.into_iter().collect());
+        assert!(fold_step > 0, "Invalid fold step");
+        let mut folded_eval = fold_line_once(eval, alpha, twiddles);
+        let mut alpha = alpha;
+        for _ in 1..fold_step {
+            alpha = alpha.square();
+            folded_eval = fold_line_once(&folded_eval, alpha, twiddles);
         }
-
-        let domain = eval.domain();
-        let itwiddles = domain_line_twiddles_from_tree(domain, &twiddles.itwiddles)[0];
-
-        let mut folded_values = SecureColumnByCoords::<Self>::zeros(1 << (log_size - 1));
-
-        for vec_index in 0..(1 << (log_size - 1 - LOG_N_LANES)) {
-            let value = {
-                let twiddle_dbl = u32x16::from_array(array::from_fn(|i| unsafe {
-                    *itwiddles.get_unchecked(vec_index * 16 + i)
-                }));
-                let val0 = unsafe { eval.values.packed_at(vec_index * 2) }.into_packed_m31s();
-                let val1 = unsafe { eval.values.packed_at(vec_index * 2 + 1) }.into_packed_m31s();
-                let pairs: [_; 4] = array::from_fn(|i| {
-                    let (a, b) = val0[i].deinterleave(val1[i]);
-                    simd_ibutterfly(a, b, twiddle_dbl)
-                });
-                let val0 = PackedSecureField::from_packed_m31s(array::from_fn(|i| pairs[i].0));
-                let val1 = PackedSecureField::from_packed_m31s(array::from_fn(|i| pairs[i].1));
-                val0 + PackedSecureField::broadcast(alpha) * val1
-            };
-            unsafe { folded_values.set_packed(vec_index, value) };
-        }
-
-        LineEvaluation::new(domain.double(), folded_values)
+        folded_eval
     }
 
     fn fold_circle_into_line(
@@ -121,6 +101,44 @@ impl FriOps for SimdBackend {
     }
 }
 
+/// Folds a degree `d` polynomial into a degree `d/2` polynomial.
+fn fold_line_once(
+    eval: &LineEvaluation<SimdBackend>,
+    alpha: SecureField,
+    twiddles: &TwiddleTree<SimdBackend>,
+) -> LineEvaluation<SimdBackend> {
+    let log_size = eval.len().ilog2();
+    if log_size <= LOG_N_LANES {
+        let eval = fri::fold_line(&eval.to_cpu(), alpha, 1);
+        return LineEvaluation::new(eval.domain(), eval.values.into_iter().collect());
+    }
+
+    let domain = eval.domain();
+    let itwiddles = domain_line_twiddles_from_tree(domain, &twiddles.itwiddles)[0];
+
+    let mut folded_values = SecureColumnByCoords::<SimdBackend>::zeros(1 << (log_size - 1));
+
+    for vec_index in 0..(1 << (log_size - 1 - LOG_N_LANES)) {
+        let value = {
+            let twiddle_dbl = u32x16::from_array(array::from_fn(|i| unsafe {
+                *itwiddles.get_unchecked(vec_index * 16 + i)
+            }));
+            let val0 = unsafe { eval.values.packed_at(vec_index * 2) }.into_packed_m31s();
+            let val1 = unsafe { eval.values.packed_at(vec_index * 2 + 1) }.into_packed_m31s();
+            let pairs: [_; 4] = array::from_fn(|i| {
+                let (a, b) = val0[i].deinterleave(val1[i]);
+                simd_ibutterfly(a, b, twiddle_dbl)
+            });
+            let val0 = PackedSecureField::from_packed_m31s(array::from_fn(|i| pairs[i].0));
+            let val1 = PackedSecureField::from_packed_m31s(array::from_fn(|i| pairs[i].1));
+            val0 + PackedSecureField::broadcast(alpha) * val1
+        };
+        unsafe { folded_values.set_packed(vec_index, value) };
+    }
+
+    LineEvaluation::new(domain.double(), folded_values)
+}
+
 /// See [`decomposition_coefficient`].
 ///
 /// [`decomposition_coefficient`]: crate::core::backend::cpu::CpuBackend::decomposition_coefficient
@@ -181,14 +199,42 @@ mod tests {
             &LineEvaluation::new(domain, values.iter().copied().collect()),
             alpha,
             &CpuBackend::precompute_twiddles(domain.coset()),
+            1,
+        );
+
+        let avx_fold = SimdBackend::fold_line(
+            &LineEvaluation::new(domain, values.iter().copied().collect()),
+            alpha,
+            &SimdBackend::precompute_twiddles(domain.coset()),
+            1,
+        );
+
+        assert_eq!(cpu_fold.values.to_vec(), avx_fold.values.to_vec());
+    }
+
+    #[test]
+    fn test_fold_line_with_fold_step() {
+        const LOG_SIZE: u32 = 7;
+        const FOLD_STEP: u32 = 3;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect_vec();
+        let alpha = qm31!(1, 3, 5, 7);
+        let domain = LineDomain::new(CanonicCoset::new(LOG_SIZE + 1).half_coset());
+        let cpu_fold = CpuBackend::fold_line(
+            &LineEvaluation::new(domain, values.iter().copied().collect()),
+            alpha,
+            &CpuBackend::precompute_twiddles(domain.coset()),
+            FOLD_STEP,
         );
 
         let avx_fold = SimdBackend::fold_line(
             &LineEvaluation::new(domain, values.iter().copied().collect()),
             alpha,
             &SimdBackend::precompute_twiddles(domain.coset()),
+            FOLD_STEP,
         );
 
+        assert_eq!(cpu_fold.len(), 1 << (LOG_SIZE - FOLD_STEP));
         assert_eq!(cpu_fold.values.to_vec(), avx_fold.values.to_vec());
     }
 
@@ -1,6 +1,6 @@
 use std::iter::zip;
 
-use num_traits::Zero;
+use num_traits::{One, Zero};
 
 use crate::core::backend::cpu::lookups::gkr::gen_eq_evals as cpu_gen_eq_evals;
 use crate::core::backend::simd::column::SecureColumn;
@@ -61,14 +61,23 @@ impl GkrOps for SimdBackend {
         match layer {
             Layer::GrandProduct(col) => next_grand_product_layer(col),
             Layer::LogUpGeneric {
-                numerators: _,
-                denominators: _,
-            } => todo!(),
+                numerators,
+                denominators,
+            } => next_logup_layer(|i| numerators.data[i], denominators),
             Layer::LogUpMultiplicities {
-                numerators: _,
-                denominators: _,
-            } => todo!(),
-            Layer::LogUpSingles { denominators: _ } => todo!(),
+                numerators,
+                denominators,
+            } => next_logup_layer(|i| numerators.data[i].into(), denominators),
+            Layer::LogUpSingles { denominators } => {
+                next_logup_layer(|_| PackedSecureField::one(), denominators)
+            }
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => Layer::FractionProduct {
+                numerators: next_product_column(numerators),
+                denominators: next_product_column(denominators),
+            },
         }
     }
 
@@ -89,17 +98,52 @@ impl GkrOps for SimdBackend {
 
         let n_packed_terms = n_terms / N_LANES;
 
+        let lambda = h.lambda;
+
         let (mut eval_at_0, mut eval_at_2) = match &h.input_layer {
             Layer::GrandProduct(col) => eval_grand_product_sum(eq_evals, col, n_packed_terms),
             Layer::LogUpGeneric {
-                numerators: _,
-                denominators: _,
-            } => todo!(),
+                numerators,
+                denominators,
+            } => eval_logup_sum(
+                eq_evals,
+                |i| numerators.data[i],
+                denominators,
+                n_packed_terms,
+                lambda,
+            ),
             Layer::LogUpMultiplicities {
-                numerators: _,
-                denominators: _,
-            } => todo!(),
-            Layer::LogUpSingles { denominators: _ } => todo!(),
+                numerators,
+                denominators,
+            } => eval_logup_sum(
+                eq_evals,
+                |i| numerators.data[i].into(),
+                denominators,
+                n_packed_terms,
+                lambda,
+            ),
+            Layer::LogUpSingles { denominators } => eval_logup_sum(
+                eq_evals,
+                |_| PackedSecureField::one(),
+                denominators,
+                n_packed_terms,
+                lambda,
+            ),
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => {
+                // Both products are linear in the evaluations of `eq`, so they are summed
+                // separately.
+                let (numer_eval_at_0, numer_eval_at_2) =
+                    eval_grand_product_sum(eq_evals, numerators, n_packed_terms);
+                let (denom_eval_at_0, denom_eval_at_2) =
+                    eval_grand_product_sum(eq_evals, denominators, n_packed_terms);
+                (
+                    numer_eval_at_0 + lambda * denom_eval_at_0,
+                    numer_eval_at_2 + lambda * denom_eval_at_2,
+                )
+            }
         };
 
         eval_at_0 *= h.eq_fixed_var_correction;
@@ -112,10 +156,17 @@ impl GkrOps for SimdBackend {
 ///
 /// Assumption: `len(layer) > N_LANES`.
 fn next_grand_product_layer(layer: &Mle<SimdBackend, SecureField>) -> Layer<SimdBackend> {
-    assert!(layer.len() > N_LANES);
-    let next_layer_len = layer.len() / 2;
+    Layer::GrandProduct(next_product_column(layer))
+}
 
-    let data = layer
+/// Returns the products of the adjacent pairs of values of a column.
+///
+/// Assumption: `len(col) > N_LANES`.
+fn next_product_column(col: &Mle<SimdBackend, SecureField>) -> Mle<SimdBackend, SecureField> {
+    assert!(col.len() > N_LANES);
+    let next_len = col.len() / 2;
+
+    let data = col
         .data
         .array_chunks()
         .map(|&[a, b]| {
@@ -124,10 +175,43 @@ fn next_grand_product_layer(layer: &Mle<SimdBackend, SecureField>) -> Layer<Simd
         })
         .collect();
 
-    Layer::GrandProduct(Mle::new(SecureColumn {
+    Mle::new(SecureColumn {
         data,
-        length: next_layer_len,
-    }))
+        length: next_len,
+    })
+}
+
+/// Generates the next GKR layer for LogUp, given the packed numerators at each packed index.
+///
+/// Assumption: `len(denominators) > N_LANES`.
+fn next_logup_layer(
+    numerators: impl Fn(usize) -> PackedSecureField,
+    denominators: &Mle<SimdBackend, SecureField>,
+) -> Layer<SimdBackend> {
+    assert!(denominators.len() > N_LANES);
+    let next_len = denominators.len() / 2;
+    let next_packed_len = denominators.data.len() / 2;
+    let mut next_numerators = Vec::with_capacity(next_packed_len);
+    let mut next_denominators = Vec::with_capacity(next_packed_len);
+
+    for i in 0..next_packed_len {
+        let (numer_evens, numer_odds) = numerators(i * 2).deinterleave(numerators(i * 2 + 1));
+        let (denom_evens, denom_odds) =
+            denominators.data[i * 2].deinterleave(denominators.data[i * 2 + 1]);
+        next_numerators.push(numer_evens * denom_odds + numer_odds * denom_evens);
+        next_denominators.push(denom_evens * denom_odds);
+    }
+
+    Layer::LogUpGeneric {
+        numerators: Mle::new(SecureColumn {
+            data: next_numerators,
+            length: next_len,
+        }),
+        denominators: Mle::new(SecureColumn {
+            data: next_denominators,
+            length: next_len,
+        }),
+    }
 }
 
 /// Evaluates `sum_x eq(({0}^|r|, 0, x), y) * inp(r, t, x, 0) * inp(r, t, x, 1)` at `t=0` and `t=2`.
@@ -168,6 +252,62 @@ fn eval_grand_product_sum(
     )
 }
 
+/// Evaluates `sum_x eq(({0}^|r|, 0, x), y) * (inp_numer(r, t, x, 0) * inp_denom(r, t, x, 1) +
+/// inp_numer(r, t, x, 1) * inp_denom(r, t, x, 0) + lambda * inp_denom(r, t, x, 0) * inp_denom(r, t,
+/// x, 1))` at `t=0` and `t=2`, given the packed numerators at each packed index.
+///
+/// Output of the form: `(eval_at_0, eval_at_2)`.
+fn eval_logup_sum(
+    eq_evals: &EqEvals<SimdBackend>,
+    numerators: impl Fn(usize) -> PackedSecureField,
+    denominators: &Mle<SimdBackend, SecureField>,
+    n_packed_terms: usize,
+    lambda: SecureField,
+) -> (SecureField, SecureField) {
+    let packed_lambda = PackedSecureField::broadcast(lambda);
+    let mut packed_eval_at_0 = PackedSecureField::zero();
+    let mut packed_eval_at_2 = PackedSecureField::zero();
+
+    for i in 0..n_packed_terms {
+        // Input polynomials at points `(r, {0, 1, 2}, bits(i), v, {0, 1})`
+        // for all `v` in `{0, 1}^LOG_N_SIMD_LANES`.
+        let (inp_numer_at_r0iv0, inp_numer_at_r0iv1) =
+            numerators(i * 2).deinterleave(numerators(i * 2 + 1));
+        let (inp_denom_at_r0iv0, inp_denom_at_r0iv1) =
+            denominators.data[i * 2].deinterleave(denominators.data[i * 2 + 1]);
+        let (inp_numer_at_r1iv0, inp_numer_at_r1iv1) = numerators((n_packed_terms + i) * 2)
+            .deinterleave(numerators((n_packed_terms + i) * 2 + 1));
+        let (inp_denom_at_r1iv0, inp_denom_at_r1iv1) = denominators.data[(n_packed_terms + i) * 2]
+            .deinterleave(denominators.data[(n_packed_terms + i) * 2 + 1]);
+        // Note `inp_denom(r, t, x) = eq(t, 0) * inp_denom(r, 0, x) + eq(t, 1) * inp_denom(r, 1, x)`
+        //   => `inp_denom(r, 2, x) = 2 * inp_denom(r, 1, x) - inp_denom(r, 0, x)`
+        let inp_numer_at_r2iv0 = inp_numer_at_r1iv0.double() - inp_numer_at_r0iv0;
+        let inp_denom_at_r2iv0 = inp_denom_at_r1iv0.double() - inp_denom_at_r0iv0;
+        let inp_numer_at_r2iv1 = inp_numer_at_r1iv1.double() - inp_numer_at_r0iv1;
+        let inp_denom_at_r2iv1 = inp_denom_at_r1iv1.double() - inp_denom_at_r0iv1;
+
+        // Fraction addition polynomials:
+        // - `numer(x) = inp_numer(x, 0) * inp_denom(x, 1) + inp_numer(x, 1) * inp_denom(x, 0)`
+        // - `denom(x) = inp_denom(x, 1) * inp_denom(x, 0)`
+        // at points `(r, {0, 2}, bits(i), v)` for all `v` in `{0, 1}^LOG_N_SIMD_LANES`.
+        let numer_at_r0iv =
+            inp_numer_at_r0iv0 * inp_denom_at_r0iv1 + inp_numer_at_r0iv1 * inp_denom_at_r0iv0;
+        let denom_at_r0iv = inp_denom_at_r0iv0 * inp_denom_at_r0iv1;
+        let numer_at_r2iv =
+            inp_numer_at_r2iv0 * inp_denom_at_r2iv1 + inp_numer_at_r2iv1 * inp_denom_at_r2iv0;
+        let denom_at_r2iv = inp_denom_at_r2iv0 * inp_denom_at_r2iv1;
+
+        let eq_eval_at_0iv = eq_evals.data[i];
+        packed_eval_at_0 += eq_eval_at_0iv * (numer_at_r0iv + packed_lambda * denom_at_r0iv);
+        packed_eval_at_2 += eq_eval_at_0iv * (numer_at_r2iv + packed_lambda * denom_at_r2iv);
+    }
+
+    (
+        packed_eval_at_0.pointwise_sum(),
+        packed_eval_at_2.pointwise_sum(),
+    )
+}
+
 fn into_simd_layer(cpu_layer: Layer<CpuBackend>) -> Layer<SimdBackend> {
     match cpu_layer {
         Layer::GrandProduct(mle) => {
@@ -190,11 +330,25 @@ fn into_simd_layer(cpu_layer: Layer<CpuBackend>) -> Layer<SimdBackend> {
         Layer::LogUpSingles { denominators } => Layer::LogUpSingles {
             denominators: Mle::new(denominators.into_evals().into_iter().collect()),
         },
+        Layer::FractionProduct {
+            numerators,
+            denominators,
+        } => Layer::FractionProduct {
+            numerators: Mle::new(numerators.into_evals().into_iter().collect()),
+            denominators: Mle::new(denominators.into_evals().into_iter().collect()),
+        },
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::iter::zip;
+
+    use itertools::Itertools;
+    use num_traits::One;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
     use crate::core::backend::simd::SimdBackend;
     use crate::core::backend::{Column, CpuBackend};
     use crate::core::channel::Channel;
@@ -203,6 +357,7 @@ mod tests {
     use crate::core::lookups::gkr_prover::{prove_batch, GkrOps, Layer};
     use crate::core::lookups::gkr_verifier::{partially_verify_batch, Gate, GkrArtifact, GkrError};
     use crate::core::lookups::mle::Mle;
+    use crate::core::lookups::utils::Fraction;
     use crate::core::test_utils::test_channel;
 
     #[test]
@@ -249,4 +404,205 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn logup_with_generic_trace_works() -> Result<(), GkrError> {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let numerator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let denominator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let sum = zip(&numerator_values, &denominator_values)
+            .map(|(&n, &d)| Fraction::new(n, d))
+            .sum::<Fraction<SecureField>>();
+        let numerators =
+            Mle::<SimdBackend, SecureField>::new(numerator_values.into_iter().collect());
+        let denominators =
+            Mle::<SimdBackend, SecureField>::new(denominator_values.into_iter().collect());
+        let top_layer = Layer::LogUpGeneric {
+            numerators: numerators.clone(),
+            denominators: denominators.clone(),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let GkrArtifact {
+            ood_point,
+            claims_to_verify_by_instance,
+            n_variables_by_instance: _,
+        } = partially_verify_batch(vec![Gate::LogUp], &proof, &mut test_channel())?;
+
+        assert_eq!(
+            proof.output_claims_by_instance,
+            [vec![sum.numerator, sum.denominator]]
+        );
+        assert_eq!(
+            claims_to_verify_by_instance,
+            [vec![
+                numerators.eval_at_point(&ood_point),
+                denominators.eval_at_point(&ood_point)
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn logup_with_singles_trace_works() -> Result<(), GkrError> {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let denominator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let sum = denominator_values
+            .iter()
+            .map(|&d| Fraction::new(SecureField::one(), d))
+            .sum::<Fraction<SecureField>>();
+        let denominators =
+            Mle::<SimdBackend, SecureField>::new(denominator_values.into_iter().collect());
+        let top_layer = Layer::LogUpSingles {
+            denominators: denominators.clone(),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let GkrArtifact {
+            ood_point,
+            claims_to_verify_by_instance,
+            n_variables_by_instance: _,
+        } = partially_verify_batch(vec![Gate::LogUpSingles], &proof, &mut test_channel())?;
+
+        assert_eq!(
+            proof.output_claims_by_instance,
+            [vec![sum.numerator, sum.denominator]]
+        );
+        assert_eq!(
+            claims_to_verify_by_instance,
+            [vec![denominators.eval_at_point(&ood_point)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn logup_with_multiplicities_trace_works() -> Result<(), GkrError> {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let numerator_values = (0..N).map(|_| rng.gen()).collect::<Vec<BaseField>>();
+        let denominator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let sum = zip(&numerator_values, &denominator_values)
+            .map(|(&n, &d)| Fraction::new(n.into(), d))
+            .sum::<Fraction<SecureField>>();
+        let numerators = Mle::<SimdBackend, BaseField>::new(numerator_values.into_iter().collect());
+        let denominators =
+            Mle::<SimdBackend, SecureField>::new(denominator_values.into_iter().collect());
+        let top_layer = Layer::LogUpMultiplicities {
+            numerators: numerators.clone(),
+            denominators: denominators.clone(),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let GkrArtifact {
+            ood_point,
+            claims_to_verify_by_instance,
+            n_variables_by_instance: _,
+        } = partially_verify_batch(vec![Gate::LogUp], &proof, &mut test_channel())?;
+
+        assert_eq!(
+            proof.output_claims_by_instance,
+            [vec![sum.numerator, sum.denominator]]
+        );
+        assert_eq!(
+            claims_to_verify_by_instance,
+            [vec![
+                numerators.eval_at_point(&ood_point),
+                denominators.eval_at_point(&ood_point)
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fraction_product_works() -> Result<(), GkrError> {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let numerator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let denominator_values = (0..N).map(|_| rng.gen()).collect::<Vec<SecureField>>();
+        let numerator_product = numerator_values.iter().product::<SecureField>();
+        let denominator_product = denominator_values.iter().product::<SecureField>();
+        let numerators =
+            Mle::<SimdBackend, SecureField>::new(numerator_values.into_iter().collect());
+        let denominators =
+            Mle::<SimdBackend, SecureField>::new(denominator_values.into_iter().collect());
+        let top_layer = Layer::FractionProduct {
+            numerators: numerators.clone(),
+            denominators: denominators.clone(),
+        };
+        let (proof, _) = prove_batch(&mut test_channel(), vec![top_layer]);
+
+        let GkrArtifact {
+            ood_point,
+            claims_to_verify_by_instance,
+            n_variables_by_instance: _,
+        } = partially_verify_batch(vec![Gate::FractionProduct], &proof, &mut test_channel())?;
+
+        assert_eq!(
+            proof.output_claims_by_instance,
+            [vec![numerator_product, denominator_product]]
+        );
+        assert_eq!(
+            claims_to_verify_by_instance,
+            [vec![
+                numerators.eval_at_point(&ood_point),
+                denominators.eval_at_point(&ood_point)
+            ]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn batch_with_all_gates_matches_cpu() -> Result<(), GkrError> {
+        const LOG_N: usize = 7;
+        let mut channel = test_channel();
+        let numerators = channel.draw_felts(1 << LOG_N);
+        let denominators = channel.draw_felts(1 << LOG_N);
+        let multiplicities = (0..1usize << LOG_N)
+            .map(BaseField::from)
+            .collect::<Vec<_>>();
+        let simd_layers: Vec<Layer<SimdBackend>> = vec![
+            Layer::GrandProduct(Mle::new(numerators.iter().copied().collect())),
+            Layer::LogUpGeneric {
+                numerators: Mle::new(numerators.iter().copied().collect()),
+                denominators: Mle::new(denominators.iter().copied().collect()),
+            },
+            Layer::LogUpMultiplicities {
+                numerators: Mle::new(multiplicities.iter().copied().collect()),
+                denominators: Mle::new(denominators.iter().copied().collect()),
+            },
+            Layer::LogUpSingles {
+                denominators: Mle::new(denominators.iter().copied().collect()),
+            },
+            Layer::FractionProduct {
+                numerators: Mle::new(numerators.iter().copied().collect()),
+                denominators: Mle::new(denominators.iter().copied().collect()),
+            },
+        ];
+        let cpu_layers = simd_layers.iter().map(Layer::to_cpu).collect_vec();
+        let gates = vec![
+            Gate::GrandProduct,
+            Gate::LogUp,
+            Gate::LogUp,
+            Gate::LogUpSingles,
+            Gate::FractionProduct,
+        ];
+
+        let (simd_proof, simd_artifact) = prove_batch(&mut test_channel(), simd_layers);
+        let (cpu_proof, cpu_artifact) = prove_batch(&mut test_channel(), cpu_layers);
+
+        assert_eq!(
+            simd_proof.output_claims_by_instance,
+            cpu_proof.output_claims_by_instance
+        );
+        assert_eq!(simd_artifact.ood_point, cpu_artifact.ood_point);
+        assert_eq!(
+            simd_artifact.claims_to_verify_by_instance,
+            cpu_artifact.claims_to_verify_by_instance
+        );
+        let artifact = partially_verify_batch(gates, &simd_proof, &mut test_channel())?;
+        assert_eq!(artifact.ood_point, simd_artifact.ood_point);
+        Ok(())
+    }
 }
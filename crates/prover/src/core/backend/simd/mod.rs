@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use super::{Backend, BackendForChannel};
 use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleChannel;
 use crate::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
 use crate::core::vcs::sha256_merkle::Sha256MerkleChannel;
 
@@ -17,6 +19,8 @@ pub mod fri;
 mod grind;
 pub mod lookups;
 pub mod m31;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod poseidon252;
 pub mod poseidon31;
 pub mod prefix_sum;
 pub mod qm31;
@@ -32,3 +36,5 @@ impl Backend for SimdBackend {}
 impl BackendForChannel<Sha256MerkleChannel> for SimdBackend {}
 impl BackendForChannel<Blake3MerkleChannel> for SimdBackend {}
 impl BackendForChannel<Poseidon31MerkleChannel> for SimdBackend {}
+#[cfg(not(target_arch = "wasm32"))]
+impl BackendForChannel<Poseidon252MerkleChannel> for SimdBackend {}
@@ -0,0 +1,112 @@
+use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use starknet_ff::FieldElement as FieldElement252;
+
+use super::m31::{LOG_N_LANES, N_LANES};
+use crate::core::backend::simd::column::BaseColumn;
+use crate::core::backend::simd::SimdBackend;
+use crate::core::backend::{Column, ColumnOps};
+use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
+use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleHasher;
+
+impl ColumnOps<FieldElement252> for SimdBackend {
+    type Column = Vec<FieldElement252>;
+
+    fn bit_reverse_column(_column: &mut Self::Column) {
+        unimplemented!()
+    }
+}
+
+impl MerkleOps<Poseidon252MerkleHasher> for SimdBackend {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Vec<FieldElement252>>,
+        columns: &[&BaseColumn],
+    ) -> Vec<FieldElement252> {
+        // Layers smaller than a vector are hashed node by node.
+        if log_size < LOG_N_LANES {
+            return (0..1 << log_size)
+                .map(|i| {
+                    Poseidon252MerkleHasher::hash_node(
+                        prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column.at(i)).collect_vec(),
+                    )
+                })
+                .collect();
+        }
+
+        let mut res = vec![FieldElement252::default(); 1 << log_size];
+
+        #[cfg(not(feature = "parallel"))]
+        let iter = res.chunks_mut(N_LANES);
+
+        #[cfg(feature = "parallel")]
+        let iter = res.par_chunks_mut(N_LANES);
+
+        // Unpacks the column values once per vector of nodes. The hasher packs them into felts.
+        iter.enumerate().for_each(|(i, chunk)| {
+            let column_values = columns
+                .iter()
+                .map(|column| column.data[i].to_array())
+                .collect_vec();
+            for (lane, hash) in chunk.iter_mut().enumerate() {
+                let node = i * N_LANES + lane;
+                *hash = Poseidon252MerkleHasher::hash_node(
+                    prev_layer.map(|prev_layer| (prev_layer[2 * node], prev_layer[2 * node + 1])),
+                    &column_values
+                        .iter()
+                        .map(|values| values[lane])
+                        .collect_vec(),
+                );
+            }
+        });
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use starknet_ff::FieldElement as FieldElement252;
+
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
+    use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleHasher;
+
+    #[test]
+    fn test_commit_on_layer_matches_hash_node() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for log_size in [2, 5] {
+            let prev_layer = (0..2 << log_size)
+                .map(|_| FieldElement252::from(rng.gen::<u64>()))
+                .collect_vec();
+            let columns = (0..11)
+                .map(|_| (0..1 << log_size).map(|_| rng.gen()).collect_vec())
+                .collect_vec();
+            let simd_columns = columns
+                .iter()
+                .map(|column| column.iter().copied().collect::<BaseColumn>())
+                .collect_vec();
+
+            let layer = <SimdBackend as MerkleOps<Poseidon252MerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&prev_layer),
+                &simd_columns.iter().collect_vec(),
+            );
+
+            let expected = (0..1 << log_size)
+                .map(|i| {
+                    Poseidon252MerkleHasher::hash_node(
+                        Some((prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column[i]).collect_vec(),
+                    )
+                })
+                .collect_vec();
+            assert_eq!(layer, expected);
+        }
+    }
+}
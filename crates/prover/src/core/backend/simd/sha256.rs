@@ -1,7 +1,19 @@
+//! A lane-parallel SHA-256 Merkle commitment, hashing [N_LANES] nodes at once.
+//!
+//! Each lane of a [u32x16] holds a big-endian message or state word of a different node, so the
+//! compression function runs on all the lanes with [std::simd] (avx512, avx2, neon etc.).
+
+use std::array;
+use std::simd::cmp::SimdOrd;
+use std::simd::num::SimdUint;
+use std::simd::{u32x16, Simd};
+
 use itertools::Itertools;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+use super::m31::{LOG_N_LANES, MODULUS, N_LANES};
+use super::utils::{bws_encoding_len, shl_or_zero, shr_or_zero};
 use crate::core::backend::simd::column::BaseColumn;
 use crate::core::backend::simd::SimdBackend;
 use crate::core::backend::{Column, ColumnOps};
@@ -9,6 +21,27 @@ use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
 use crate::core::vcs::sha256_hash::Sha256Hash;
 use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
 
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// The first padding word of a message made of whole words.
+const PADDING_START: u32 = 0x80000000;
+
+/// The state words of [N_LANES] hashes, one hash per lane.
+type HashWords = [u32x16; 8];
+
 impl ColumnOps<Sha256Hash> for SimdBackend {
     type Column = Vec<Sha256Hash>;
 
@@ -17,25 +50,244 @@ impl ColumnOps<Sha256Hash> for SimdBackend {
     }
 }
 
-// TODO(BWS): not simd at all
 impl MerkleOps<Sha256MerkleHasher> for SimdBackend {
     fn commit_on_layer(
         log_size: u32,
         prev_layer: Option<&Vec<Sha256Hash>>,
         columns: &[&BaseColumn],
     ) -> Vec<Sha256Hash> {
+        // Layers smaller than a vector are hashed node by node.
+        if log_size < LOG_N_LANES {
+            return (0..1 << log_size)
+                .map(|i| {
+                    Sha256MerkleHasher::hash_node(
+                        prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column.at(i)).collect_vec(),
+                    )
+                })
+                .collect();
+        }
+
+        let mut res = vec![Sha256Hash::default(); 1 << log_size];
+
         #[cfg(not(feature = "parallel"))]
-        let iter = 0..1 << log_size;
+        let iter = res.chunks_mut(N_LANES);
 
         #[cfg(feature = "parallel")]
-        let iter = (0..1 << log_size).into_par_iter();
+        let iter = res.par_chunks_mut(N_LANES);
+
+        iter.enumerate().for_each(|(i, chunk)| {
+            let children = prev_layer.map(|prev_layer| {
+                let children = &prev_layer[2 * i * N_LANES..2 * (i + 1) * N_LANES];
+                (
+                    load_hashes(|lane| children[2 * lane]),
+                    load_hashes(|lane| children[2 * lane + 1]),
+                )
+            });
+            let column_values = columns.iter().map(|column| {
+                let values = column.data[i].into_simd();
+                // Packed values are in the range `[0, P]`.
+                values.simd_min(values - MODULUS)
+            });
+            store_hashes(hash_nodes(children, column_values), chunk);
+        });
+        res
+    }
+}
+
+/// Hashes [N_LANES] nodes, as [Sha256MerkleHasher::hash_node].
+fn hash_nodes(
+    children: Option<(HashWords, HashWords)>,
+    column_values: impl DoubleEndedIterator<Item = u32x16>,
+) -> HashWords {
+    // The column values are hash-chained from the last one.
+    let column_hash = column_values.rev().fold(None, |column_hash, values| {
+        Some(hash_encoded_value(values, column_hash))
+    });
+
+    match (children, column_hash) {
+        (Some((left, right)), Some(column_hash)) => {
+            hash_words(&[left, column_hash, right].concat())
+        }
+        (Some((left, right)), None) => hash_words(&[left, right].concat()),
+        (None, Some(column_hash)) => hash_words(&column_hash),
+        (None, None) => hash_words(&[]),
+    }
+}
+
+/// Hashes the [bws_num_to_bytes] encoding of `values`, followed by `suffix` if given.
+///
+/// The encoding takes up to 4 bytes, so the message fits a single block, where its hash is shifted
+/// by the encoding length of each lane.
+///
+/// [bws_num_to_bytes]: crate::core::utils::bws_num_to_bytes
+fn hash_encoded_value(values: u32x16, suffix: Option<HashWords>) -> HashWords {
+    let zero = u32x16::splat(0);
+
+    let encoding_len = bws_encoding_len(values);
+    // The bytes beyond the encoding are zero, so this is the encoding in big-endian order.
+    let encoding_word = values.swap_bytes();
+
+    // The words following the encoding, starting with the suffix and the padding.
+    let (suffix_len, suffix) = suffix.map_or((0, [zero; 8]), |suffix| (32, suffix));
+    let mut tail = [zero; 10];
+    let n_suffix_words = suffix_len / 4;
+    tail[..n_suffix_words].copy_from_slice(&suffix[..n_suffix_words]);
+    tail[n_suffix_words] = u32x16::splat(PADDING_START);
+
+    let right_shift = encoding_len << u32x16::splat(3);
+    let left_shift = u32x16::splat(u32::BITS) - right_shift;
+    let mut block = [zero; 16];
+    block[0] = encoding_word | shr_or_zero(tail[0], right_shift);
+    for i in 1..tail.len() {
+        block[i] = shl_or_zero(tail[i - 1], left_shift) | shr_or_zero(tail[i], right_shift);
+    }
+    block[15] = (encoding_len + u32x16::splat(suffix_len as u32)) << u32x16::splat(3);
+
+    compress(IV.map(u32x16::splat), block)
+}
 
-        iter.map(|i| {
-            Sha256MerkleHasher::hash_node(
-                prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
-                &columns.iter().map(|column| column.at(i)).collect_vec(),
-            )
+/// Hashes a message of whole words.
+fn hash_words(words: &[u32x16]) -> HashWords {
+    let mut message = words.to_vec();
+    message.push(u32x16::splat(PADDING_START));
+    // The message ends with its 64-bit length in bits.
+    message.resize((words.len() + 3).next_multiple_of(16), u32x16::splat(0));
+    *message.last_mut().unwrap() = u32x16::splat(words.len() as u32 * u32::BITS);
+
+    message
+        .chunks_exact(16)
+        .fold(IV.map(u32x16::splat), |state, block| {
+            compress(state, block.try_into().unwrap())
         })
-        .collect()
+}
+
+/// The SHA-256 compression function, applied to each lane.
+fn compress(state: HashWords, block: [u32x16; 16]) -> HashWords {
+    let mut w = [u32x16::splat(0); 64];
+    w[..16].copy_from_slice(&block);
+    for i in 16..64 {
+        let s0 = rotr(w[i - 15], 7) ^ rotr(w[i - 15], 18) ^ (w[i - 15] >> u32x16::splat(3));
+        let s1 = rotr(w[i - 2], 17) ^ rotr(w[i - 2], 19) ^ (w[i - 2] >> u32x16::splat(10));
+        w[i] = w[i - 16] + s0 + w[i - 7] + s1;
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for i in 0..64 {
+        let s1 = rotr(e, 6) ^ rotr(e, 11) ^ rotr(e, 25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h + s1 + ch + u32x16::splat(K[i]) + w[i];
+        let s0 = rotr(a, 2) ^ rotr(a, 13) ^ rotr(a, 22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0 + maj;
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+
+    let [s0, s1, s2, s3, s4, s5, s6, s7] = state;
+    [
+        s0 + a,
+        s1 + b,
+        s2 + c,
+        s3 + d,
+        s4 + e,
+        s5 + f,
+        s6 + g,
+        s7 + h,
+    ]
+}
+
+fn rotr(x: u32x16, n: u32) -> u32x16 {
+    (x >> Simd::splat(n)) | (x << Simd::splat(u32::BITS - n))
+}
+
+/// Transposes the hashes of the lanes to their words.
+fn load_hashes(hash: impl Fn(usize) -> Sha256Hash) -> HashWords {
+    let hashes: [Sha256Hash; N_LANES] = array::from_fn(hash);
+    array::from_fn(|word| {
+        u32x16::from_array(
+            hashes.map(|hash| {
+                u32::from_be_bytes(hash.0[4 * word..4 * (word + 1)].try_into().unwrap())
+            }),
+        )
+    })
+}
+
+/// Transposes the words of the hashes to the hashes of the lanes.
+fn store_hashes(words: HashWords, dst: &mut [Sha256Hash]) {
+    let words = words.map(|word| word.to_array());
+    for (lane, hash) in dst.iter_mut().enumerate() {
+        for (word, bytes) in hash.0.chunks_exact_mut(4).enumerate() {
+            bytes.copy_from_slice(&words[word][lane].to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{hash_nodes, load_hashes, store_hashes};
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::m31::N_LANES;
+    use crate::core::backend::simd::utils::test_utils::{self, random_hash};
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    use crate::core::vcs::ops::MerkleOps;
+    use crate::core::vcs::sha256_hash::Sha256Hash;
+    use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+
+    #[test]
+    fn test_hash_nodes_matches_hash_node() {
+        test_utils::test_hash_nodes_matches_hash_node::<Sha256MerkleHasher>(
+            |children, column_values| {
+                let children = children
+                    .map(|(left, right)| (load_hashes(|i| left[i]), load_hashes(|i| right[i])));
+                let mut res = [Sha256Hash::default(); N_LANES];
+                store_hashes(
+                    hash_nodes(children, column_values.iter().copied()),
+                    &mut res,
+                );
+                res
+            },
+        );
+    }
+
+    #[test]
+    fn test_commit_on_layer_matches_cpu() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for log_size in [2, 5] {
+            let prev_layer = (0..2 << log_size)
+                .map(|_| random_hash::<Sha256MerkleHasher>(&mut rng))
+                .collect_vec();
+            let cpu_columns = (0..5)
+                .map(|_| (0..1 << log_size).map(|_| rng.gen()).collect_vec())
+                .collect_vec();
+            let simd_columns = cpu_columns
+                .iter()
+                .map(|column| column.iter().copied().collect::<BaseColumn>())
+                .collect_vec();
+
+            let cpu_layer = <CpuBackend as MerkleOps<Sha256MerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&prev_layer),
+                &cpu_columns.iter().collect_vec(),
+            );
+            let simd_layer = <SimdBackend as MerkleOps<Sha256MerkleHasher>>::commit_on_layer(
+                log_size,
+                Some(&prev_layer),
+                &simd_columns.iter().collect_vec(),
+            );
+
+            assert_eq!(simd_layer, cpu_layer);
+        }
     }
 }
@@ -1,3 +1,7 @@
+use std::simd::cmp::{SimdPartialEq, SimdPartialOrd};
+use std::simd::num::SimdUint;
+use std::simd::u32x16;
+
 // TODO(andrew): Examine usage of unsafe in SIMD FFT.
 pub struct UnsafeMut<T: ?Sized>(pub *mut T);
 impl<T: ?Sized> UnsafeMut<T> {
@@ -25,6 +29,34 @@ impl<T> UnsafeConst<T> {
 unsafe impl<T> Send for UnsafeConst<T> {}
 unsafe impl<T> Sync for UnsafeConst<T> {}
 
+/// Shifts each lane left by `n` bits, where shifting by 32 bits or more gives zero.
+pub fn shl_or_zero(x: u32x16, n: u32x16) -> u32x16 {
+    n.simd_lt(u32x16::splat(u32::BITS))
+        .select(x << n, u32x16::splat(0))
+}
+
+/// Shifts each lane right by `n` bits, where shifting by 32 bits or more gives zero.
+pub fn shr_or_zero(x: u32x16, n: u32x16) -> u32x16 {
+    n.simd_lt(u32x16::splat(u32::BITS))
+        .select(x >> n, u32x16::splat(0))
+}
+
+/// Returns the length in bytes of the [bws_num_to_bytes] encoding of each lane, for values below
+/// 2^31.
+///
+/// The encoding is the minimal little-endian bytes of the value, with an extra zero byte if the
+/// last one has its top bit set, so that's at most 4 bytes, and the bytes of the value beyond the
+/// encoding are zero.
+///
+/// [bws_num_to_bytes]: crate::core::utils::bws_num_to_bytes
+pub fn bws_encoding_len(values: u32x16) -> u32x16 {
+    let bit_len = u32x16::splat(u32::BITS) - values.leading_zeros();
+    values.simd_eq(u32x16::splat(0)).select(
+        u32x16::splat(0),
+        (bit_len + u32x16::splat(8)) >> u32x16::splat(3),
+    )
+}
+
 #[cfg(not(any(
     all(target_arch = "aarch64", target_feature = "neon"),
     all(target_arch = "wasm32", target_feature = "simd128")
@@ -82,3 +114,85 @@ pub mod swizzle {
         }
     }
 }
+
+#[cfg(test)]
+pub mod test_utils {
+    use std::simd::u32x16;
+
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::core::backend::simd::m31::N_LANES;
+    use crate::core::fields::m31::{BaseField, P};
+    use crate::core::vcs::ops::MerkleHasher;
+
+    /// Values of every encoding length, with and without the extra zero byte.
+    pub const EDGE_VALUES: [u32; N_LANES] = [
+        0,
+        1,
+        0x7f,
+        0x80,
+        0xff,
+        0x100,
+        0x7fff,
+        0x8000,
+        0xffff,
+        0x10000,
+        0x7fffff,
+        0x800000,
+        0xffffff,
+        0x1000000,
+        0x7ffffff,
+        P - 1,
+    ];
+
+    pub fn random_hash<H: MerkleHasher>(rng: &mut SmallRng) -> H::Hash
+    where
+        H::Hash: From<Vec<u8>>,
+    {
+        rng.gen::<[u8; 32]>().to_vec().into()
+    }
+
+    /// Checks that `hash_nodes`, which hashes a node per lane from the children hashes of each
+    /// lane and the packed column values, matches [MerkleHasher::hash_node] on every lane.
+    pub fn test_hash_nodes_matches_hash_node<H: MerkleHasher>(
+        hash_nodes: impl Fn(Option<(&[H::Hash], &[H::Hash])>, &[u32x16]) -> [H::Hash; N_LANES],
+    ) where
+        H::Hash: From<Vec<u8>>,
+    {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let left = (0..N_LANES)
+            .map(|_| random_hash::<H>(&mut rng))
+            .collect_vec();
+        let right = (0..N_LANES)
+            .map(|_| random_hash::<H>(&mut rng))
+            .collect_vec();
+        let mut columns = vec![EDGE_VALUES];
+        columns.extend((0..3).map(|_| EDGE_VALUES.map(|_| rng.gen_range(0..P))));
+
+        for with_children in [false, true] {
+            for n_columns in 0..=columns.len() {
+                let column_values = columns[..n_columns]
+                    .iter()
+                    .map(|&c| u32x16::from_array(c))
+                    .collect_vec();
+                let res = hash_nodes(
+                    with_children.then_some((left.as_slice(), right.as_slice())),
+                    &column_values,
+                );
+
+                for (lane, hash) in res.into_iter().enumerate() {
+                    let expected = H::hash_node(
+                        with_children.then(|| (left[lane], right[lane])),
+                        &columns[..n_columns]
+                            .iter()
+                            .map(|column| BaseField::from_u32_unchecked(column[lane]))
+                            .collect_vec(),
+                    );
+                    assert_eq!(hash, expected, "n_columns: {n_columns}, lane: {lane}");
+                }
+            }
+        }
+    }
+}
@@ -58,6 +58,14 @@ pub trait MerkleChannel: Default {
     type C: Channel;
     type H: MerkleHasher;
     fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash);
+
+    /// Mixes the nodes of a Merkle cap in order, see [crate::core::vcs::prover::MerkleProver::cap].
+    /// A cap of a single node is mixed as its root.
+    fn mix_cap(channel: &mut Self::C, cap: &[<Self::H as MerkleHasher>::Hash]) {
+        for &node in cap {
+            Self::mix_root(channel, node);
+        }
+    }
 }
 
 pub(crate) fn extract_common(hash: &[u8]) -> M31 {
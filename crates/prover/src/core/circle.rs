@@ -16,7 +16,9 @@ pub struct CirclePoint<F> {
     pub y: F,
 }
 
-impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>> CirclePoint<F> {
+impl<F: Zero + Add<Output = F> + FieldExpOps + Copy + Sub<Output = F> + Neg<Output = F>>
+    CirclePoint<F>
+{
     pub fn zero() -> Self {
         Self {
             x: F::one(),
@@ -120,7 +122,7 @@ impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>
     }
 }
 
-impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>> Add
+impl<F: Zero + Add<Output = F> + FieldExpOps + Copy + Sub<Output = F> + Neg<Output = F>> Add
     for CirclePoint<F>
 {
     type Output = Self;
@@ -132,7 +134,7 @@ impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>
     }
 }
 
-impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>> Neg
+impl<F: Zero + Add<Output = F> + FieldExpOps + Copy + Sub<Output = F> + Neg<Output = F>> Neg
     for CirclePoint<F>
 {
     type Output = Self;
@@ -142,7 +144,7 @@ impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>
     }
 }
 
-impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>> Sub
+impl<F: Zero + Add<Output = F> + FieldExpOps + Copy + Sub<Output = F> + Neg<Output = F>> Sub
     for CirclePoint<F>
 {
     type Output = Self;
@@ -185,7 +185,7 @@ macro_rules! m31 {
 /// let v = BaseField::from(19);
 /// assert_eq!(pow2147483645(v), v.pow(2147483645));
 /// ```
-pub fn pow2147483645<T: FieldExpOps>(v: T) -> T {
+pub fn pow2147483645<T: FieldExpOps + Copy>(v: T) -> T {
     let t0 = sqn::<2, T>(v) * v;
     let t1 = sqn::<1, T>(t0) * t0;
     let t2 = sqn::<3, T>(t1) * t0;
@@ -196,7 +196,7 @@ pub fn pow2147483645<T: FieldExpOps>(v: T) -> T {
 }
 
 /// Computes `v^(2*n)`.
-fn sqn<const N: usize, T: FieldExpOps>(mut v: T) -> T {
+fn sqn<const N: usize, T: FieldExpOps + Copy>(mut v: T) -> T {
     for _ in 0..N {
         v = v.square();
     }
@@ -16,18 +16,18 @@ pub trait FieldOps<F: Field>: ColumnOps<F> {
     fn batch_inverse(column: &Self::Column, dst: &mut Self::Column);
 }
 
-pub trait FieldExpOps: Mul<Output = Self> + MulAssign + Sized + One + Copy {
+pub trait FieldExpOps: Mul<Output = Self> + MulAssign + Sized + One + Clone {
     fn square(&self) -> Self {
-        (*self) * (*self)
+        self.clone() * self.clone()
     }
 
     fn pow(&self, exp: u128) -> Self {
         let mut res = Self::one();
-        let mut base = *self;
+        let mut base = self.clone();
         let mut exp = exp;
         while exp > 0 {
             if exp & 1 == 1 {
-                res *= base;
+                res *= base.clone();
             }
             base = base.square();
             exp >>= 1;
@@ -38,7 +38,10 @@ pub trait FieldExpOps: Mul<Output = Self> + MulAssign + Sized + One + Copy {
     fn inverse(&self) -> Self;
 
     /// Inverts a batch of elements using Montgomery's trick.
-    fn batch_inverse(column: &[Self], dst: &mut [Self]) {
+    fn batch_inverse(column: &[Self], dst: &mut [Self])
+    where
+        Self: Copy,
+    {
         const WIDTH: usize = 4;
         let n = column.len();
         debug_assert!(dst.len() >= n);
@@ -72,7 +75,7 @@ pub trait FieldExpOps: Mul<Output = Self> + MulAssign + Sized + One + Copy {
 }
 
 /// Assumes dst is initialized and of the same length as column.
-fn batch_inverse_classic<T: FieldExpOps>(column: &[T], dst: &mut [T]) {
+fn batch_inverse_classic<T: FieldExpOps + Copy>(column: &[T], dst: &mut [T]) {
     let n = column.len();
     debug_assert!(dst.len() >= n);
 
@@ -32,13 +32,15 @@ use crate::core::vcs::prover::{MerkleDecommitment, MerkleProver};
 use crate::core::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
 
 /// FRI proof config
-// TODO(andrew): Support different step sizes.
 #[derive(Debug, Clone, Copy)]
 pub struct FriConfig {
     pub log_blowup_factor: u32,
     pub log_last_layer_degree_bound: u32,
     pub n_queries: usize,
-    // TODO(andrew): fold_steps.
+    /// Number of folds applied to each inner FRI layer, in order, see [Self::with_fold_steps].
+    /// Only the first `n_fold_steps` entries are used.
+    fold_steps: [u32; Self::MAX_N_FOLD_STEPS],
+    n_fold_steps: usize,
 }
 
 impl FriConfig {
@@ -47,12 +49,17 @@ impl FriConfig {
     const LOG_LAST_LAYER_DEGREE_BOUND_RANGE: RangeInclusive<u32> =
         Self::LOG_MIN_LAST_LAYER_DEGREE_BOUND..=Self::LOG_MAX_LAST_LAYER_DEGREE_BOUND;
 
-    const LOG_MIN_BLOWUP_FACTOR: u32 = 1;
-    const LOG_MAX_BLOWUP_FACTOR: u32 = 16;
+    pub const LOG_MIN_BLOWUP_FACTOR: u32 = 1;
+    pub const LOG_MAX_BLOWUP_FACTOR: u32 = 16;
     const LOG_BLOWUP_FACTOR_RANGE: RangeInclusive<u32> =
         Self::LOG_MIN_BLOWUP_FACTOR..=Self::LOG_MAX_BLOWUP_FACTOR;
 
-    /// Creates a new FRI configuration.
+    const MIN_FOLD_STEP: u32 = 1;
+    const MAX_FOLD_STEP: u32 = 4;
+    const FOLD_STEP_RANGE: RangeInclusive<u32> = Self::MIN_FOLD_STEP..=Self::MAX_FOLD_STEP;
+    const MAX_N_FOLD_STEPS: usize = 32;
+
+    /// Creates a new FRI configuration that folds each inner layer by a factor of two.
     ///
     /// # Panics
     ///
@@ -66,29 +73,90 @@ impl FriConfig {
             log_blowup_factor,
             log_last_layer_degree_bound,
             n_queries,
+            fold_steps: [Self::MIN_FOLD_STEP; Self::MAX_N_FOLD_STEPS],
+            n_fold_steps: 1,
         }
     }
 
+    /// Returns the configuration with each inner layer folded by a factor of `2^fold_step`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fold_step` is equal to zero or greater than 4.
+    pub fn with_fold_step(self, fold_step: u32) -> Self {
+        self.with_fold_steps(&[fold_step])
+    }
+
+    /// Returns the configuration with the `i`-th inner layer folded by a factor of
+    /// `2^fold_steps[i]`. The layers past the end of the schedule fold by its last step, and the
+    /// layer before the last layer folds less if needed.
+    ///
+    /// Larger steps give fewer inner layers, and therefore fewer merkle roots and decommitments in
+    /// the proof, at the cost of more evaluations decommitted per layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if:
+    /// * `fold_steps` is empty or has more than 32 steps.
+    /// * A step is equal to zero or greater than 4.
+    pub fn with_fold_steps(self, fold_steps: &[u32]) -> Self {
+        assert!((1..=Self::MAX_N_FOLD_STEPS).contains(&fold_steps.len()));
+        assert!(fold_steps
+            .iter()
+            .all(|step| Self::FOLD_STEP_RANGE.contains(step)));
+        let mut steps = [Self::MIN_FOLD_STEP; Self::MAX_N_FOLD_STEPS];
+        steps[..fold_steps.len()].copy_from_slice(fold_steps);
+        Self {
+            fold_steps: steps,
+            n_fold_steps: fold_steps.len(),
+            ..self
+        }
+    }
+
+    /// Returns the fold-step schedule of the inner layers, see [Self::with_fold_steps].
+    pub fn fold_steps(&self) -> &[u32] {
+        &self.fold_steps[..self.n_fold_steps]
+    }
+
+    /// Returns the conjectured bits of security of the queries, see [crate::core::pcs::security].
+    pub const fn security_bits(&self) -> u32 {
+        self.log_blowup_factor * self.n_queries as u32
+    }
+
     fn last_layer_domain_size(&self) -> usize {
         1 << (self.log_last_layer_degree_bound + self.log_blowup_factor)
     }
+
+    /// Returns the number of folds applied to the `layer_index`-th inner layer, of the given log
+    /// degree bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the degree bound doesn't exceed the last layer's degree bound.
+    fn inner_layer_fold_step(&self, layer_index: usize, log_degree_bound: u32) -> u32 {
+        assert!(log_degree_bound > self.log_last_layer_degree_bound);
+        let fold_steps = self.fold_steps();
+        let fold_step = fold_steps[layer_index.min(fold_steps.len() - 1)];
+        fold_step.min(log_degree_bound - self.log_last_layer_degree_bound)
+    }
 }
 
 pub trait FriOps: FieldOps<BaseField> + PolyOps + Sized + FieldOps<SecureField> {
-    /// Folds a degree `d` polynomial into a degree `d/2` polynomial.
+    /// Folds a degree `d` polynomial into a degree `d/2^fold_step` polynomial.
     ///
     /// Let `eval` be a polynomial evaluated on a [LineDomain] `E`, `alpha` be a random field
-    /// element and `pi(x) = 2x^2 - 1` be the circle's x-coordinate doubling map. This function
+    /// element and `pi(x) = 2x^2 - 1` be the circle's x-coordinate doubling map. A single fold
     /// returns `f' = f0 + alpha * f1` evaluated on `pi(E)` such that `2f(x) = f0(pi(x)) + x *
-    /// f1(pi(x))`.
+    /// f1(pi(x))`. The `i`-th of the `fold_step` folds is applied with `alpha^(2^i)`.
     ///
     /// # Panics
     ///
-    /// Panics if there are less than two evaluations.
+    /// Panics if `fold_step` is zero or there are less than `2^fold_step` evaluations.
     fn fold_line(
         eval: &LineEvaluation<Self>,
         alpha: SecureField,
         twiddles: &TwiddleTree<Self>,
+        fold_step: u32,
     ) -> LineEvaluation<Self>;
 
     /// Folds and accumulates a degree `d` circle polynomial into a degree `d/2` univariate
@@ -141,6 +209,9 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
     /// polynomials so combining of evaluations can be taken care of efficiently at the appropriate
     /// FRI layer. All evaluations must be taken over canonic [`CircleDomain`]s.
     ///
+    /// Each inner layer is committed to with a Merkle cap of `2^log_merkle_cap_size` nodes, see
+    /// [`MerkleProver::cap`].
+    ///
     /// # Panics
     ///
     /// Panics if:
@@ -154,6 +225,7 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
     pub fn commit(
         channel: &mut MC::C,
         config: FriConfig,
+        log_merkle_cap_size: u32,
         columns: &[SecureEvaluation<B>],
         twiddles: &TwiddleTree<B>,
     ) -> Self {
@@ -162,7 +234,7 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
         assert!(columns.is_sorted_by_key(|e| Reverse(e.len())), "not sorted");
         assert!(columns.iter().all(|e| e.domain.is_canonic()), "not canonic");
         let (inner_layers, last_layer_evaluation) =
-            Self::commit_inner_layers(channel, config, columns, twiddles);
+            Self::commit_inner_layers(channel, config, log_merkle_cap_size, columns, twiddles);
         let last_layer_poly = Self::commit_last_layer(channel, config, last_layer_evaluation);
 
         let column_log_sizes = columns
@@ -186,6 +258,7 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
     fn commit_inner_layers(
         channel: &mut MC::C,
         config: FriConfig,
+        log_merkle_cap_size: u32,
         columns: &[SecureEvaluation<B>],
         twiddles: &TwiddleTree<B>,
     ) -> (Vec<FriLayerProver<B, MC::H>>, LineEvaluation<B>) {
@@ -212,10 +285,13 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
         );
 
         while layer_evaluation.len() > config.last_layer_domain_size() {
-            let layer = FriLayerProver::new(layer_evaluation);
-            MC::mix_root(channel, layer.merkle_tree.root());
+            let log_degree_bound = layer_evaluation.len().ilog2() - config.log_blowup_factor;
+            let fold_step = config.inner_layer_fold_step(layers.len(), log_degree_bound);
+            let layer = FriLayerProver::new(layer_evaluation, fold_step, log_merkle_cap_size);
+            MC::mix_cap(channel, &layer.merkle_tree.cap());
             let folding_alpha = channel.draw_felt();
-            let folded_layer_evaluation = B::fold_line(&layer.evaluation, folding_alpha, twiddles);
+            let folded_layer_evaluation =
+                B::fold_line(&layer.evaluation, folding_alpha, twiddles, fold_step);
 
             layer_evaluation = folded_layer_evaluation;
             layers.push(layer);
@@ -279,8 +355,9 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
             .inner_layers
             .into_iter()
             .scan(first_layer_queries, |layer_queries, layer| {
+                let fold_step = layer.fold_step;
                 let layer_proof = layer.decommit(layer_queries);
-                *layer_queries = layer_queries.fold(FOLD_STEP);
+                *layer_queries = layer_queries.fold(fold_step);
                 Some(layer_proof)
             })
             .collect();
@@ -314,11 +391,14 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
     /// Verifies the commitment stage of FRI.
     ///
     /// `column_bounds` should be the committed circle polynomial degree bounds in descending order.
+    /// Each inner layer is expected to be committed to with a Merkle cap of
+    /// `2^log_merkle_cap_size` nodes, lowered to the layer size if needed, see [FriProver::commit].
     ///
     /// # Errors
     ///
     /// An `Err` will be returned if:
     /// * The proof contains an invalid number of FRI layers.
+    /// * An inner layer commitment doesn't have the expected cap size.
     /// * The degree of the last layer polynomial is too high.
     ///
     /// # Panics
@@ -330,6 +410,7 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
     pub fn commit(
         channel: &mut MC::C,
         config: FriConfig,
+        log_merkle_cap_size: u32,
         proof: FriProof<MC::H>,
         column_bounds: Vec<CirclePolyDegreeBound>,
     ) -> Result<Self, FriVerificationError> {
@@ -349,22 +430,38 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
         ));
 
         for (layer_index, proof) in proof.inner_layers.into_iter().enumerate() {
-            MC::mix_root(channel, proof.commitment);
+            if layer_bound.log_degree_bound <= config.log_last_layer_degree_bound {
+                return Err(FriVerificationError::InvalidNumFriLayers);
+            }
+
+            // The layer is committed to as `SECURE_EXTENSION_DEGREE` columns of the domain size.
+            let log_cap_size = log_merkle_cap_size.min(layer_domain.log_size());
+            if proof.commitment.len() != 1 << log_cap_size {
+                return Err(FriVerificationError::InnerLayerCommitmentInvalid {
+                    layer: layer_index,
+                    error: MerkleVerificationError::InvalidCapSize,
+                });
+            }
+
+            MC::mix_cap(channel, &proof.commitment);
 
             let folding_alpha = channel.draw_felt();
+            let fold_step = config.inner_layer_fold_step(layer_index, layer_bound.log_degree_bound);
 
             inner_layers.push(FriLayerVerifier {
                 degree_bound: layer_bound,
                 domain: layer_domain,
                 folding_alpha,
+                fold_step,
+                log_cap_size,
                 layer_index,
                 proof,
             });
 
             layer_bound = layer_bound
-                .fold(FOLD_STEP)
+                .fold(fold_step)
                 .ok_or(FriVerificationError::InvalidNumFriLayers)?;
-            layer_domain = layer_domain.double();
+            layer_domain = layer_domain.repeated_double(fold_step);
         }
 
         if layer_bound.log_degree_bound != config.log_last_layer_degree_bound {
@@ -524,11 +621,17 @@ pub fn get_opening_positions(
     assert!(prev_log_size == queries.log_domain_size);
     let mut prev_queries = queries.clone();
     let mut positions = BTreeMap::new();
-    positions.insert(prev_log_size, prev_queries.opening_positions(FOLD_STEP));
+    positions.insert(
+        prev_log_size,
+        prev_queries.opening_positions(CIRCLE_TO_LINE_FOLD_STEP),
+    );
     for log_size in column_log_sizes.iter().skip(1) {
         let n_folds = prev_log_size - log_size;
         let queries = prev_queries.fold(n_folds);
-        positions.insert(*log_size, queries.opening_positions(FOLD_STEP));
+        positions.insert(
+            *log_size,
+            queries.opening_positions(CIRCLE_TO_LINE_FOLD_STEP),
+        );
         prev_log_size = *log_size;
         prev_queries = queries;
     }
@@ -622,10 +725,6 @@ pub struct FriProof<H: MerkleHasher> {
     pub last_layer_poly: LinePoly,
 }
 
-/// Number of folds for univariate polynomials.
-// TODO(andrew): Support different step sizes.
-pub const FOLD_STEP: u32 = 1;
-
 /// Number of folds when folding a circle polynomial to univariate polynomial.
 pub const CIRCLE_TO_LINE_FOLD_STEP: u32 = 1;
 
@@ -638,13 +737,18 @@ pub struct FriLayerProof<H: MerkleHasher> {
     /// to fold and verify the merkle decommitment.
     pub evals_subset: Vec<SecureField>,
     pub decommitment: MerkleDecommitment<H>,
-    pub commitment: H::Hash,
+    /// The Merkle cap of the layer, see [`MerkleProver::cap`].
+    pub commitment: Vec<H::Hash>,
 }
 
 pub struct FriLayerVerifier<H: MerkleHasher> {
     pub degree_bound: LinePolyDegreeBound,
     pub domain: LineDomain,
     pub folding_alpha: SecureField,
+    /// Number of folds applied to the layer, see [`FriConfig::with_fold_steps`].
+    pub fold_step: u32,
+    /// Log size of the Merkle cap the layer is committed to.
+    pub log_cap_size: u32,
     pub layer_index: usize,
     pub proof: FriLayerProof<H>,
 }
@@ -667,7 +771,7 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
         evals_at_queries: Vec<SecureField>,
     ) -> Result<(Queries, Vec<SecureField>), FriVerificationError> {
         let decommitment = self.proof.decommitment.clone();
-        let commitment = self.proof.commitment;
+        let commitment = self.proof.commitment.clone();
 
         // Extract the evals needed for decommitment and folding.
         let sparse_evaluation = self.extract_evaluation(&queries, &evals_at_queries)?;
@@ -679,21 +783,18 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             .flat_map(|e| e.values.into_iter())
             .collect();
 
-        let folded_queries = queries.fold(FOLD_STEP);
+        let folded_queries = queries.fold(self.fold_step);
 
         // Positions of all the decommitment evals.
-        let decommitment_positions = folded_queries
-            .iter()
-            .flat_map(|folded_query| {
-                let start = folded_query << FOLD_STEP;
-                let end = start + (1 << FOLD_STEP);
-                start..end
-            })
+        let decommitment_positions = queries
+            .folding_cosets(self.fold_step)
+            .flat_map(|(subline_positions, _)| subline_positions)
             .collect::<Vec<usize>>();
 
         let merkle_verifier = MerkleVerifier::new(
             commitment,
             vec![self.domain.log_size(); SECURE_EXTENSION_DEGREE],
+            self.log_cap_size,
         );
         // TODO(spapini): Propagate error.
         merkle_verifier
@@ -737,15 +838,14 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
         let mut all_subline_evals = Vec::new();
 
         // Group queries by the subline they reside in.
-        for subline_queries in queries.chunk_by(|a, b| a >> FOLD_STEP == b >> FOLD_STEP) {
-            let subline_start = (subline_queries[0] >> FOLD_STEP) << FOLD_STEP;
-            let subline_end = subline_start + (1 << FOLD_STEP);
+        for (subline_positions, subline_queries) in queries.folding_cosets(self.fold_step) {
+            let subline_start = subline_positions.start;
 
             let mut subline_evals = Vec::new();
             let mut subline_queries = subline_queries.iter().peekable();
 
             // Insert the evals.
-            for eval_position in subline_start..subline_end {
+            for eval_position in subline_positions {
                 let eval = match subline_queries.next_if_eq(&&eval_position) {
                     Some(_) => evals_at_queries.next().unwrap(),
                     None => proof_evals.next().ok_or(
@@ -762,7 +862,7 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             // TODO(andrew): Create a constructor for LineDomain.
             let subline_initial_index = bit_reverse_index(subline_start, self.domain.log_size());
             let subline_initial = self.domain.coset().index_at(subline_initial_index);
-            let subline_domain = LineDomain::new(Coset::new(subline_initial, FOLD_STEP));
+            let subline_domain = LineDomain::new(Coset::new(subline_initial, self.fold_step));
 
             all_subline_evals.push(LineEvaluation::new(
                 subline_domain,
@@ -777,29 +877,33 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             });
         }
 
-        Ok(SparseLineEvaluation::new(all_subline_evals))
+        Ok(SparseLineEvaluation::new(all_subline_evals, self.fold_step))
     }
 }
 
 /// A FRI layer comprises of a merkle tree that commits to evaluations of a polynomial.
 ///
 /// The polynomial evaluations are viewed as evaluation of a polynomial on multiple distinct cosets
-/// of size two. Each leaf of the merkle tree commits to a single coset evaluation.
-// TODO(andrew): Support different step sizes.
+/// of size `2^fold_step`. Each coset gets folded into a single evaluation of the next layer.
 struct FriLayerProver<B: FriOps + MerkleOps<H>, H: MerkleHasher> {
     evaluation: LineEvaluation<B>,
     merkle_tree: MerkleProver<B, H>,
+    fold_step: u32,
 }
 
 impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
-    fn new(evaluation: LineEvaluation<B>) -> Self {
+    fn new(evaluation: LineEvaluation<B>, fold_step: u32, log_merkle_cap_size: u32) -> Self {
         // TODO(spapini): Commit on slice.
         // TODO(spapini): Merkle tree in backend.
-        let merkle_tree = MerkleProver::commit(evaluation.values.columns.iter().collect_vec());
+        let merkle_tree = MerkleProver::commit_with_cap(
+            evaluation.values.columns.iter().collect_vec(),
+            log_merkle_cap_size,
+        );
         #[allow(unreachable_code)]
         FriLayerProver {
             evaluation,
             merkle_tree,
+            fold_step,
         }
     }
 
@@ -810,13 +914,10 @@ impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
 
         // Group queries by the subline they reside in.
         // TODO(andrew): Explain what a "subline" is at the top of the module.
-        for query_group in queries.chunk_by(|a, b| a >> FOLD_STEP == b >> FOLD_STEP) {
-            let subline_start = (query_group[0] >> FOLD_STEP) << FOLD_STEP;
-            let subline_end = subline_start + (1 << FOLD_STEP);
-
-            let mut subline_queries = query_group.iter().peekable();
+        for (subline_positions, subline_queries) in queries.folding_cosets(self.fold_step) {
+            let mut subline_queries = subline_queries.iter().peekable();
 
-            for eval_position in subline_start..subline_end {
+            for eval_position in subline_positions {
                 // Add decommitment position.
                 decommit_positions.push(eval_position);
 
@@ -830,7 +931,7 @@ impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
             }
         }
 
-        let commitment = self.merkle_tree.root();
+        let commitment = self.merkle_tree.cap();
         // TODO(spapini): Use _evals.
         let (_evals, decommitment) = self.merkle_tree.decommit(
             [(self.evaluation.len().ilog2(), decommit_positions)]
@@ -900,31 +1001,51 @@ impl<'a> IntoIterator for &'a mut SparseCircleEvaluation {
 #[derive(Debug, Clone)]
 struct SparseLineEvaluation {
     subline_evals: Vec<LineEvaluation<CpuBackend>>,
+    fold_step: u32,
 }
 
 impl SparseLineEvaluation {
     /// # Panics
     ///
-    /// Panics if the evaluation domain sizes don't equal the folding factor.
-    fn new(subline_evals: Vec<LineEvaluation<CpuBackend>>) -> Self {
-        let folding_factor = 1 << FOLD_STEP;
+    /// Panics if the evaluation domain sizes don't equal the folding factor `2^fold_step`.
+    fn new(subline_evals: Vec<LineEvaluation<CpuBackend>>, fold_step: u32) -> Self {
+        let folding_factor = 1 << fold_step;
         assert!(subline_evals.iter().all(|e| e.len() == folding_factor));
-        Self { subline_evals }
+        Self {
+            subline_evals,
+            fold_step,
+        }
     }
 
     fn fold(self, alpha: SecureField) -> Vec<SecureField> {
         self.subline_evals
             .into_iter()
-            .map(|e| fold_line(&e, alpha).values.at(0))
+            .map(|e| fold_line(&e, alpha, self.fold_step).values.at(0))
             .collect()
     }
 }
 
-/// Folds a degree `d` polynomial into a degree `d/2` polynomial.
+/// Folds a degree `d` polynomial into a degree `d/2^fold_step` polynomial.
 /// See [`FriOps::fold_line`].
 pub fn fold_line(
     eval: &LineEvaluation<CpuBackend>,
     alpha: SecureField,
+    fold_step: u32,
+) -> LineEvaluation<CpuBackend> {
+    assert!(fold_step > 0, "Invalid fold step");
+    let mut folded_eval = fold_line_once(eval, alpha);
+    let mut alpha = alpha;
+    for _ in 1..fold_step {
+        alpha = alpha.square();
+        folded_eval = fold_line_once(&folded_eval, alpha);
+    }
+    folded_eval
+}
+
+/// Folds a degree `d` polynomial into a degree `d/2` polynomial.
+fn fold_line_once(
+    eval: &LineEvaluation<CpuBackend>,
+    alpha: SecureField,
 ) -> LineEvaluation<CpuBackend> {
     let n = eval.len();
     assert!(n >= 2, "Evaluation too small");
@@ -938,7 +1059,7 @@ pub fn fold_line(
         .enumerate()
         .map(|(i, [f_x, f_neg_x])| {
             // TODO(andrew): Inefficient. Update when domain twiddles get stored in a buffer.
-            let x = domain.at(bit_reverse_index(i << FOLD_STEP, domain.log_size()));
+            let x = domain.at(bit_reverse_index(i << 1, domain.log_size()));
 
             let (mut f0, mut f1) = (f_x, f_neg_x);
             ibutterfly(&mut f0, &mut f1, x.inverse());
@@ -1039,7 +1160,7 @@ mod tests {
         CpuBackend::bit_reverse_column(&mut values);
         let evals = LineEvaluation::new(domain, values.into_iter().collect());
 
-        let drp_evals = fold_line(&evals, alpha);
+        let drp_evals = fold_line(&evals, alpha, 1);
         let mut drp_evals = drp_evals.values.into_iter().collect_vec();
         CpuBackend::bit_reverse_column(&mut drp_evals);
 
@@ -1051,6 +1172,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn fold_line_with_fold_step_works() {
+        const LOG_DEGREE: u32 = 6;
+        const FOLD_STEP: u32 = 3;
+        let coeffs = (1..=1 << LOG_DEGREE)
+            .map(BaseField::from_u32_unchecked)
+            .map(SecureField::from);
+        let poly = LinePoly::new(coeffs.collect());
+        let domain = LineDomain::new(Coset::half_odds(LOG_DEGREE + LOG_BLOWUP_FACTOR));
+        let mut values = domain
+            .iter()
+            .map(|p| poly.eval_at_point(p.into()))
+            .collect();
+        CpuBackend::bit_reverse_column(&mut values);
+        let evals = LineEvaluation::new(domain, values.into_iter().collect());
+        let alpha = BaseField::from_u32_unchecked(19283).into();
+
+        let folded_evals = fold_line(&evals, alpha, FOLD_STEP);
+
+        let folded_domain = domain.repeated_double(FOLD_STEP);
+        assert_eq!(folded_evals.domain().coset(), folded_domain.coset());
+        assert_eq!(log_degree_bound(folded_evals), LOG_DEGREE - FOLD_STEP);
+    }
+
     #[test]
     fn fold_circle_to_line_works() {
         const LOG_DEGREE: u32 = 4;
@@ -1078,6 +1223,7 @@ mod tests {
         FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1096,6 +1242,7 @@ mod tests {
         FriProver::commit(
             &mut test_channel(),
             FriConfig::new(2, 2, 3),
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1112,12 +1259,13 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
         let proof = prover.decommit_on_queries(&queries);
         let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
 
         verifier.decommit_on_queries(&queries, vec![decommitment_value])
     }
@@ -1135,16 +1283,163 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
         let proof = prover.decommit_on_queries(&queries);
         let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
 
         verifier.decommit_on_queries(&queries, vec![decommitment_value])
     }
 
+    #[test]
+    fn valid_proof_with_fold_step_passes_verification() -> Result<(), FriVerificationError> {
+        const LOG_DEGREE: u32 = 6;
+        const FOLD_STEP: u32 = 3;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![1, 7, 8, 70], log_domain_size);
+        let config = FriConfig::new(1, LOG_BLOWUP_FACTOR, queries.len()).with_fold_step(FOLD_STEP);
+        let decommitment_value = query_polynomial(&evaluation, &queries);
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            0,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        // The line polynomial of degree `2^5` is folded by `2^3` and then by `2^1`.
+        assert_eq!(proof.inner_layers.len(), 2);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
+
+        verifier.decommit_on_queries(&queries, vec![decommitment_value])
+    }
+
+    #[test]
+    fn valid_proof_with_fold_step_schedule_passes_verification() -> Result<(), FriVerificationError>
+    {
+        const LOG_DEGREE: u32 = 8;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![1, 7, 8, 70, 300], log_domain_size);
+        let config =
+            FriConfig::new(0, LOG_BLOWUP_FACTOR, queries.len()).with_fold_steps(&[3, 1, 2]);
+        let decommitment_value = query_polynomial(&evaluation, &queries);
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            0,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        // The line polynomial of degree `2^7` is folded by `2^3, 2^1, 2^2` and then by `2^1`.
+        assert_eq!(proof.inner_layers.len(), 4);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
+
+        verifier.decommit_on_queries(&queries, vec![decommitment_value])
+    }
+
+    #[test]
+    #[should_panic]
+    fn fold_step_schedule_with_invalid_step_panics() {
+        FriConfig::new(0, LOG_BLOWUP_FACTOR, 1).with_fold_steps(&[2, 5]);
+    }
+
+    #[test]
+    fn valid_proof_with_merkle_cap_passes_verification() -> Result<(), FriVerificationError> {
+        const LOG_DEGREE: u32 = 6;
+        const LOG_MERKLE_CAP_SIZE: u32 = 2;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![1, 7, 8, 70], log_domain_size);
+        let config = FriConfig::new(1, LOG_BLOWUP_FACTOR, queries.len());
+        let decommitment_value = query_polynomial(&evaluation, &queries);
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            LOG_MERKLE_CAP_SIZE,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        assert!(proof
+            .inner_layers
+            .iter()
+            .all(|layer| layer.commitment.len() == 1 << LOG_MERKLE_CAP_SIZE));
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        let verifier = FriVerifier::commit(
+            &mut test_channel(),
+            config,
+            LOG_MERKLE_CAP_SIZE,
+            proof,
+            bound,
+        )
+        .unwrap();
+
+        verifier.decommit_on_queries(&queries, vec![decommitment_value])
+    }
+
+    #[test]
+    fn proof_with_unexpected_merkle_cap_size_fails_verification() {
+        const LOG_DEGREE: u32 = 6;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![5], log_domain_size);
+        let config = FriConfig::new(1, LOG_BLOWUP_FACTOR, queries.len());
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            2,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound);
+
+        assert!(matches!(
+            verifier,
+            Err(FriVerificationError::InnerLayerCommitmentInvalid {
+                layer: 0,
+                error: MerkleVerificationError::InvalidCapSize
+            })
+        ));
+    }
+
+    #[test]
+    fn proof_with_mismatched_fold_step_fails_verification() {
+        const LOG_DEGREE: u32 = 6;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![5], log_domain_size);
+        let config = FriConfig::new(0, LOG_BLOWUP_FACTOR, queries.len()).with_fold_step(2);
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            0,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        // The prover folds by `2^2, 2^2, 2^1` and the verifier expects `2^3, 2^2`.
+        let invalid_config = config.with_fold_step(3);
+
+        let verifier = FriVerifier::commit(&mut test_channel(), invalid_config, 0, proof, bound);
+
+        assert!(matches!(
+            verifier,
+            Err(FriVerificationError::InvalidNumFriLayers)
+        ));
+    }
+
     #[test]
     fn proof_with_removed_layer_fails_verification() {
         const LOG_DEGREE: u32 = 6;
@@ -1155,6 +1450,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1164,7 +1460,7 @@ mod tests {
         let mut invalid_config = config;
         invalid_config.log_last_layer_degree_bound -= 1;
 
-        let verifier = FriVerifier::commit(&mut test_channel(), invalid_config, proof, bound);
+        let verifier = FriVerifier::commit(&mut test_channel(), invalid_config, 0, proof, bound);
 
         assert!(matches!(
             verifier,
@@ -1182,6 +1478,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1191,7 +1488,7 @@ mod tests {
         let mut invalid_config = config;
         invalid_config.log_last_layer_degree_bound += 1;
 
-        let verifier = FriVerifier::commit(&mut test_channel(), invalid_config, proof, bound);
+        let verifier = FriVerifier::commit(&mut test_channel(), invalid_config, 0, proof, bound);
 
         assert!(matches!(
             verifier,
@@ -1210,6 +1507,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1217,7 +1515,7 @@ mod tests {
         let mut proof = prover.decommit_on_queries(&queries);
         // Remove an evaluation from the second layer's proof.
         proof.inner_layers[1].evals_subset.pop();
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
 
         let verification_result = verifier.decommit_on_queries(&queries, vec![decommitment_value]);
 
@@ -1238,6 +1536,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1245,7 +1544,7 @@ mod tests {
         let mut proof = prover.decommit_on_queries(&queries);
         // Modify the committed values in the second layer.
         proof.inner_layers[1].evals_subset[0] += BaseField::one();
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
 
         let verification_result = verifier.decommit_on_queries(&queries, vec![decommitment_value]);
 
@@ -1266,6 +1565,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1274,7 +1574,7 @@ mod tests {
         let bad_last_layer_coeffs = vec![One::one(); 1 << (LOG_MAX_LAST_LAYER_DEGREE + 1)];
         proof.last_layer_poly = LinePoly::new(bad_last_layer_coeffs);
 
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound);
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound);
 
         assert!(matches!(
             verifier,
@@ -1293,6 +1593,7 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
@@ -1300,7 +1601,7 @@ mod tests {
         let mut proof = prover.decommit_on_queries(&queries);
         // Compromise the last layer polynomial's first coefficient.
         proof.last_layer_poly[0] += BaseField::one();
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
 
         let verification_result = verifier.decommit_on_queries(&queries, vec![decommitment_value]);
 
@@ -1322,12 +1623,13 @@ mod tests {
         let prover = FriProver::commit(
             &mut test_channel(),
             config,
+            0,
             &[evaluation.clone()],
             &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
         );
         let proof = prover.decommit_on_queries(&queries);
         let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
-        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound).unwrap();
+        let verifier = FriVerifier::commit(&mut test_channel(), config, 0, proof, bound).unwrap();
         // Simulate the verifier sampling queries on a smaller domain.
         let mut invalid_queries = queries.clone();
         invalid_queries.log_domain_size -= 1;
@@ -0,0 +1,201 @@
+//! Lookups proven with a batch GKR proof between the trace commitments, instead of committed logup
+//! cumulative sum columns.
+//!
+//! Each row of a lookup contributes the fraction `numerator / denominator` to its relation. The
+//! fractions of all the lookups are summed by a batch GKR proof, see [prove_lookups], which reduces
+//! the sums to claims on the multilinear extensions of the numerators and denominators of each
+//! lookup, at a random point, see [GkrLookupClaims]. The components check these claims in their
+//! constraints, see [crate::constraint_framework::gkr].
+use std::ops::Range;
+
+use itertools::Itertools;
+use num_traits::{One, Zero};
+
+use super::gkr_prover::{prove_batch, GkrOps, Layer};
+use super::gkr_verifier::{partially_verify_batch, Gate, GkrArtifact, GkrBatchProof, GkrError};
+use crate::core::channel::Channel;
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::FieldExpOps;
+
+/// A lookup proven with GKR, as declared by a component, see
+/// [crate::core::air::Component::gkr_lookups].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkrLookup {
+    /// [Gate::LogUpSingles] if the input layer of the lookup is [Layer::LogUpSingles], and
+    /// [Gate::LogUp] otherwise.
+    pub gate: Gate,
+    /// The log size of the trace domain of the lookup.
+    pub log_size: u32,
+}
+
+/// The outcome of a batch GKR proof of lookups, shared by the prover and the verifier.
+pub struct GkrLookups {
+    artifact: GkrArtifact,
+    claimed_sums: Vec<SecureField>,
+    lambda: SecureField,
+}
+
+impl GkrLookups {
+    /// Returns the sum of the fractions of each lookup, given by the outputs of the circuit.
+    pub fn claimed_sums(&self) -> &[SecureField] {
+        &self.claimed_sums
+    }
+
+    /// Returns the claims left on the lookups `instances`, checked together by a component.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lookups are not of the same size.
+    pub fn claims(&self, instances: Range<usize>) -> GkrLookupClaims {
+        let n_variables = self.artifact.n_variables_by_instance[instances.clone()]
+            .iter()
+            .all_equal_value()
+            .expect("Lookups checked together must be of the same size.");
+        // Smaller instances are reduced to the last variables of the point.
+        let ood_point = &self.artifact.ood_point;
+        GkrLookupClaims {
+            point: ood_point[ood_point.len() - n_variables..].to_vec(),
+            claims: self.artifact.claims_to_verify_by_instance[instances]
+                .iter()
+                .map(|claims| match claims[..] {
+                    // The numerators of [Gate::LogUpSingles] lookups are one.
+                    [denominator] => [SecureField::one(), denominator],
+                    [numerator, denominator] => [numerator, denominator],
+                    _ => unreachable!(),
+                })
+                .collect(),
+            lambda: self.lambda,
+        }
+    }
+}
+
+/// The claims a batch GKR proof leaves on lookups of the same size, checked by the constraints of
+/// the component reading them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GkrLookupClaims {
+    /// The point the multilinear extensions of the lookups are evaluated at.
+    pub point: Vec<SecureField>,
+    /// The evaluations of the numerators and the denominators of each lookup at the point.
+    pub claims: Vec<[SecureField; 2]>,
+    /// The random coefficient the claims are combined with, drawn after the GKR proof.
+    pub lambda: SecureField,
+}
+
+impl GkrLookupClaims {
+    pub fn log_size(&self) -> u32 {
+        self.point.len() as u32
+    }
+
+    /// Returns the coefficients of the numerator and the denominator of lookup `index` in the
+    /// combined claim.
+    pub fn coefficients(&self, index: usize) -> [SecureField; 2] {
+        let numerator_coeff = self.lambda.pow(2 * index as u128);
+        [numerator_coeff, numerator_coeff * self.lambda]
+    }
+
+    /// Returns the claims combined with powers of `lambda`, i.e. the evaluation of the combined
+    /// lookup terms at the point.
+    pub fn combined_claim(&self) -> SecureField {
+        self.claims
+            .iter()
+            .enumerate()
+            .map(|(index, &[numerator, denominator])| {
+                let [numerator_coeff, denominator_coeff] = self.coefficients(index);
+                numerator * numerator_coeff + denominator * denominator_coeff
+            })
+            .sum()
+    }
+
+    /// Returns claims on `n_lookups` lookups on the trace domain of size `2^log_size`, with
+    /// placeholder values. Used to derive the layout of a component before the GKR proof.
+    pub fn dummy(log_size: u32, n_lookups: usize) -> Self {
+        Self {
+            point: vec![SecureField::zero(); log_size as usize],
+            claims: vec![[SecureField::one(); 2]; n_lookups],
+            lambda: SecureField::one(),
+        }
+    }
+}
+
+/// Proves the sums of the lookups with a batch GKR proof. The lookups are expected to be read from
+/// committed columns, and their lookup elements drawn after the commitment.
+///
+/// Returns the proof, and the claims left on the lookups with the coefficient combining them,
+/// drawn from the channel.
+///
+/// # Panics
+///
+/// Panics if a layer is not a logup layer.
+pub fn prove_lookups<B: GkrOps>(
+    channel: &mut impl Channel,
+    input_layers: Vec<Layer<B>>,
+) -> (GkrBatchProof, GkrLookups) {
+    assert!(
+        input_layers.iter().all(|layer| matches!(
+            layer,
+            Layer::LogUpGeneric { .. }
+                | Layer::LogUpMultiplicities { .. }
+                | Layer::LogUpSingles { .. }
+        )),
+        "Lookups are proven with logup layers."
+    );
+    let (proof, artifact) = prove_batch(channel, input_layers);
+    let claimed_sums = claimed_sums(&proof).expect("A lookup has a zero denominator.");
+    let lambda = channel.draw_felt();
+    let lookups = GkrLookups {
+        artifact,
+        claimed_sums,
+        lambda,
+    };
+    (proof, lookups)
+}
+
+/// Verifies the batch GKR proof of `lookups`, in the transcript of [prove_lookups]. The returned
+/// claims are left to the constraints of the components.
+///
+/// # Panics
+///
+/// Panics if a gate is not a logup gate.
+pub fn verify_lookups(
+    channel: &mut impl Channel,
+    lookups: &[GkrLookup],
+    proof: &GkrBatchProof,
+) -> Result<GkrLookups, GkrError> {
+    assert!(
+        lookups
+            .iter()
+            .all(|lookup| matches!(lookup.gate, Gate::LogUp | Gate::LogUpSingles)),
+        "Lookups are proven with logup gates."
+    );
+    let gate_by_instance = lookups.iter().map(|lookup| lookup.gate).collect();
+    let artifact = partially_verify_batch(gate_by_instance, proof, channel)?;
+    let n_variables = lookups.iter().map(|lookup| lookup.log_size as usize);
+    if !artifact
+        .n_variables_by_instance
+        .iter()
+        .copied()
+        .eq(n_variables)
+    {
+        return Err(GkrError::MalformedProof);
+    }
+    let claimed_sums = claimed_sums(proof).ok_or(GkrError::MalformedProof)?;
+    let lambda = channel.draw_felt();
+    Ok(GkrLookups {
+        artifact,
+        claimed_sums,
+        lambda,
+    })
+}
+
+/// Returns the sum of the fractions of each lookup from the outputs of the circuit, or `None` if
+/// an output is not a fraction with a non-zero denominator.
+fn claimed_sums(proof: &GkrBatchProof) -> Option<Vec<SecureField>> {
+    proof
+        .output_claims_by_instance
+        .iter()
+        .map(|output| match output[..] {
+            [numerator, denominator] if !denominator.is_zero() => Some(numerator / denominator),
+            _ => None,
+        })
+        .collect()
+}
@@ -1,4 +1,4 @@
-//! GKR batch prover for Grand Product and LogUp lookup arguments.
+//! GKR batch prover for Grand Product, Fraction Product and LogUp lookup arguments.
 use std::borrow::Cow;
 use std::iter::{successors, zip};
 use std::ops::Deref;
@@ -103,6 +103,12 @@ pub enum Layer<B: GkrOps> {
     LogUpSingles {
         denominators: Mle<B, SecureField>,
     },
+    /// Multiplies the fractions `numerators / denominators`, for example the ratio of the read and
+    /// write sets of a memory checking argument.
+    FractionProduct {
+        numerators: Mle<B, SecureField>,
+        denominators: Mle<B, SecureField>,
+    },
 }
 
 impl<B: GkrOps> Layer<B> {
@@ -116,6 +122,9 @@ impl<B: GkrOps> Layer<B> {
             }
             | Self::LogUpGeneric {
                 denominators: mle, ..
+            }
+            | Self::FractionProduct {
+                denominators: mle, ..
             } => mle.n_variables(),
         }
     }
@@ -167,6 +176,14 @@ impl<B: GkrOps> Layer<B> {
             Layer::GrandProduct(col) => {
                 vec![col.at(0)]
             }
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => {
+                let numerator = numerators.at(0);
+                let denominator = denominators.at(0);
+                vec![numerator, denominator]
+            }
         })
     }
 
@@ -195,6 +212,13 @@ impl<B: GkrOps> Layer<B> {
             Self::LogUpSingles { denominators } => Self::LogUpSingles {
                 denominators: denominators.fix_first_variable(x0),
             },
+            Self::FractionProduct {
+                numerators,
+                denominators,
+            } => Self::FractionProduct {
+                numerators: numerators.fix_first_variable(x0),
+                denominators: denominators.fix_first_variable(x0),
+            },
         }
     }
 
@@ -263,6 +287,13 @@ impl<B: GkrOps> Layer<B> {
             Layer::LogUpSingles { denominators } => Layer::LogUpSingles {
                 denominators: Mle::new(denominators.to_cpu()),
             },
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => Layer::FractionProduct {
+                numerators: Mle::new(numerators.to_cpu()),
+                denominators: Mle::new(denominators.to_cpu()),
+            },
         }
     }
 }
@@ -288,12 +319,23 @@ struct NotOutputLayerError;
 ///
 /// P(x) = eq(x, y) * (numer(x) + lambda * denom(x))
 /// ```
+///
+/// When the input layer is [`Layer::FractionProduct`] (represented by multilinear columns
+/// `inp_numer` and `inp_denom`) the polynomial represents:
+///
+/// ```text
+/// numer(x) = inp_numer(x, 0) * inp_numer(x, 1)
+/// denom(x) = inp_denom(x, 0) * inp_denom(x, 1)
+///
+/// P(x) = eq(x, y) * (numer(x) + lambda * denom(x))
+/// ```
 pub struct GkrMultivariatePolyOracle<'a, B: GkrOps> {
     /// `eq_evals` passed by `Layer::into_multivariate_poly()`.
     pub eq_evals: Cow<'a, EqEvals<B>>,
     pub input_layer: Layer<B>,
     pub eq_fixed_var_correction: SecureField,
-    /// Used by LogUp to perform a random linear combination of the numerators and denominators.
+    /// Used by LogUp and Fraction Product to perform a random linear combination of the numerators
+    /// and denominators.
     pub lambda: SecureField,
 }
 
@@ -355,8 +397,13 @@ impl<'a, B: GkrOps> GkrMultivariatePolyOracle<'a, B> {
             }
             // Should never get called.
             Layer::LogUpMultiplicities { .. } => unimplemented!(),
-            Layer::LogUpSingles { denominators } => {
-                let numerators = [SecureField::one(); 2];
+            // Numerators are implicitly one, see `Gate::LogUpSingles`.
+            Layer::LogUpSingles { denominators } => vec![denominators.to_cpu().try_into().unwrap()],
+            Layer::FractionProduct {
+                numerators,
+                denominators,
+            } => {
+                let numerators = numerators.to_cpu().try_into().unwrap();
                 let denominators = denominators.to_cpu().try_into().unwrap();
                 vec![numerators, denominators]
             }
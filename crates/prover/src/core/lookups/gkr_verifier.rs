@@ -1,4 +1,6 @@
-//! GKR batch verifier for Grand Product and LogUp lookup arguments.
+//! GKR batch verifier for Grand Product, Fraction Product and LogUp lookup arguments.
+use num_traits::One;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::sumcheck::{SumcheckError, SumcheckProof};
@@ -93,13 +95,14 @@ pub fn partially_verify_batch(
             let n_unused = n_layers - instance_n_layers(instance);
             let mask = &layer_masks_by_instance[instance][layer - n_unused];
             let gate = &gate_by_instance[instance];
-            let gate_output = gate.eval(mask).map_err(|InvalidNumMaskColumnsError| {
-                let instance_layer = instance_n_layers(layer) - n_remaining_layers;
-                GkrError::InvalidMask {
-                    instance,
-                    instance_layer,
-                }
-            })?;
+            let instance_layer = layer - n_unused;
+            let is_input_layer = instance_layer == instance_n_layers(instance) - 1;
+            let gate_output =
+                gate.eval(mask, is_input_layer)
+                    .map_err(|InvalidNumMaskColumnsError| GkrError::InvalidMask {
+                        instance,
+                        instance_layer,
+                    })?;
             // TODO: Consider simplifying the code by just using the same eq eval for all instances
             // regardless of size.
             let eq_eval = eq(&ood_point[n_unused..], &sumcheck_ood_point[n_unused..]);
@@ -149,6 +152,7 @@ pub fn partially_verify_batch(
 }
 
 /// Batch GKR proof.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GkrBatchProof {
     /// Sum-check proof for each layer.
     pub sumcheck_proofs: Vec<SumcheckProof>,
@@ -175,18 +179,47 @@ pub struct GkrArtifact {
 /// the circuits defined in [Thaler13] which allow for efficient linear time (linear in size of the
 /// circuit) GKR prover implementations.
 ///
+/// The gate of an instance also determines the mask of its input layer, see [`Layer`].
+///
 /// [Thaler13]: https://eprint.iacr.org/2013/351.pdf
-#[derive(Debug, Clone, Copy)]
+/// [`Layer`]: super::gkr_prover::Layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Gate {
+    /// Adds fractions, with generic or base field numerators in the input layer.
     LogUp,
+    /// Adds fractions whose numerators are all one in the input layer. The mask of the input layer
+    /// only holds the denominators.
+    LogUpSingles,
     GrandProduct,
+    /// Multiplies fractions, for example to compare the read and write sets of a memory checking
+    /// argument.
+    FractionProduct,
 }
 
 impl Gate {
     /// Returns the output after applying the gate to the mask.
-    fn eval(&self, mask: &GkrMask) -> Result<Vec<SecureField>, InvalidNumMaskColumnsError> {
+    ///
+    /// `is_input_layer` is set when the mask is of the input layer of the instance.
+    fn eval(
+        &self,
+        mask: &GkrMask,
+        is_input_layer: bool,
+    ) -> Result<Vec<SecureField>, InvalidNumMaskColumnsError> {
         Ok(match self {
-            Self::LogUp => {
+            Self::LogUpSingles if is_input_layer => {
+                if mask.columns().len() != 1 {
+                    return Err(InvalidNumMaskColumnsError);
+                }
+
+                let [denominator_a, denominator_b] = mask.columns()[0];
+
+                let a = Fraction::new(SecureField::one(), denominator_a);
+                let b = Fraction::new(SecureField::one(), denominator_b);
+                let res = a + b;
+
+                vec![res.numerator, res.denominator]
+            }
+            Self::LogUp | Self::LogUpSingles => {
                 if mask.columns().len() != 2 {
                     return Err(InvalidNumMaskColumnsError);
                 }
@@ -208,6 +241,15 @@ impl Gate {
                 let [a, b] = mask.columns()[0];
                 vec![a * b]
             }
+            Self::FractionProduct => {
+                if mask.columns().len() != 2 {
+                    return Err(InvalidNumMaskColumnsError);
+                }
+
+                let [numerator_a, numerator_b] = mask.columns()[0];
+                let [denominator_a, denominator_b] = mask.columns()[1];
+                vec![numerator_a * numerator_b, denominator_a * denominator_b]
+            }
         })
     }
 }
@@ -217,7 +259,7 @@ impl Gate {
 struct InvalidNumMaskColumnsError;
 
 /// Stores two evaluations of each column in a GKR layer.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GkrMask {
     columns: Vec<[SecureField; 2]>,
 }
@@ -245,7 +287,7 @@ impl GkrMask {
 }
 
 /// Error encountered during GKR protocol verification.
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum GkrError {
     /// The proof is malformed.
     #[error("proof data is invalid")]
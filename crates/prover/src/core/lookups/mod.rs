@@ -1,3 +1,4 @@
+pub mod gkr_lookups;
 pub mod gkr_prover;
 pub mod gkr_verifier;
 pub mod mle;
@@ -8,6 +8,7 @@ use std::iter::zip;
 
 use itertools::Itertools;
 use num_traits::{One, Zero};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::utils::UnivariatePoly;
@@ -177,7 +178,7 @@ pub fn partially_verify(
     Ok((assignment, claim))
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SumcheckProof {
     pub round_polys: Vec<UnivariatePoly<SecureField>>,
 }
@@ -186,7 +187,7 @@ pub struct SumcheckProof {
 pub const MAX_DEGREE: usize = 3;
 
 /// Sum-check protocol verification error.
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone)]
 pub enum SumcheckError {
     #[error("degree of the polynomial in round {round} is too high")]
     DegreeInvalid { round: RoundIndex },
@@ -1,9 +1,13 @@
 use std::collections::BTreeMap;
-use std::ops::{Deref, DerefMut, Index};
+use std::fmt::{self, Display};
+use std::ops::{Deref, DerefMut};
 
 use fields::m31::BaseField;
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use self::channel::Channel;
 use self::fields::qm31::SecureField;
 
 pub mod air;
@@ -64,46 +68,121 @@ impl<T> DerefMut for ComponentVec<T> {
     }
 }
 
+/// Identifies an element of [InteractionElements].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct InteractionElementId(pub String);
+
+impl Display for InteractionElementId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Identifies a value of [LookupValues].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct LookupValueId(pub String);
+
+impl Display for LookupValueId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Identifies a public input of a [Statement].
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct PublicInputId(pub String);
+
+impl Display for PublicInputId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum LookupError {
+    #[error("Missing interaction element {0}.")]
+    MissingInteractionElement(InteractionElementId),
+    #[error("Missing lookup value {0}.")]
+    MissingLookupValue(LookupValueId),
+    #[error("Missing public input {0}.")]
+    MissingPublicInput(PublicInputId),
+}
+
 #[derive(Default, Debug)]
-pub struct InteractionElements(BTreeMap<String, SecureField>);
+pub struct InteractionElements(BTreeMap<InteractionElementId, SecureField>);
 
 impl InteractionElements {
-    pub fn new(elements: BTreeMap<String, SecureField>) -> Self {
+    pub fn new(elements: BTreeMap<InteractionElementId, SecureField>) -> Self {
         Self(elements)
     }
 
+    pub fn get(&self, id: &InteractionElementId) -> Result<SecureField, LookupError> {
+        self.0
+            .get(id)
+            .copied()
+            .ok_or_else(|| LookupError::MissingInteractionElement(id.clone()))
+    }
+
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
-}
 
-impl Index<&str> for InteractionElements {
-    type Output = SecureField;
-
-    fn index(&self, index: &str) -> &Self::Output {
-        // TODO(AlonH): Return an error if the key is not found.
-        &self.0[index]
+    pub fn extend(&mut self, other: Self) {
+        self.0.extend(other.0);
     }
 }
 
-#[derive(Default, Clone, Debug, Serialize, Deserialize)]
-pub struct LookupValues(pub BTreeMap<String, BaseField>);
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LookupValues(pub BTreeMap<LookupValueId, BaseField>);
 
 impl LookupValues {
-    pub fn new(values: BTreeMap<String, BaseField>) -> Self {
+    pub fn new(values: BTreeMap<LookupValueId, BaseField>) -> Self {
         Self(values)
     }
 
+    pub fn get(&self, id: &LookupValueId) -> Result<BaseField, LookupError> {
+        self.0
+            .get(id)
+            .copied()
+            .ok_or_else(|| LookupError::MissingLookupValue(id.clone()))
+    }
+
     pub fn extend(&mut self, other: Self) {
         self.0.extend(other.0);
     }
 }
 
-impl Index<&str> for LookupValues {
-    type Output = BaseField;
+/// The public inputs of a proof, e.g. the values its trace is bound to with
+/// [crate::constraint_framework::EvalAtRow::add_boundary_constraint].
+/// The statement is mixed into the channel before the first commitment, so that everything drawn
+/// from the channel depends on it, see [Self::mix_into].
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Statement(pub BTreeMap<PublicInputId, BaseField>);
+
+impl Statement {
+    pub fn new(public_inputs: BTreeMap<PublicInputId, BaseField>) -> Self {
+        Self(public_inputs)
+    }
+
+    pub fn get(&self, id: &PublicInputId) -> Result<BaseField, LookupError> {
+        self.0
+            .get(id)
+            .copied()
+            .ok_or_else(|| LookupError::MissingPublicInput(id.clone()))
+    }
 
-    fn index(&self, index: &str) -> &Self::Output {
-        // TODO(AlonH): Return an error if the key is not found.
-        &self.0[index]
+    /// Mixes the public inputs into the channel: their number, then the id and the value of each,
+    /// in the order of their ids. An empty statement leaves the channel as is.
+    pub fn mix_into(&self, channel: &mut impl Channel) {
+        if self.0.is_empty() {
+            return;
+        }
+        let mut felts = vec![BaseField::from(self.0.len())];
+        for (PublicInputId(id), &value) in &self.0 {
+            felts.push(BaseField::from(id.len()));
+            felts.extend(id.bytes().map(|byte| BaseField::from(byte as u32)));
+            felts.push(value);
+        }
+        channel.mix_felts(&felts.into_iter().map(SecureField::from).collect_vec());
     }
 }
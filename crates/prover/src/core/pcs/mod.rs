@@ -6,8 +6,10 @@
 //! the existence of such polynomials, and are ok with having a small decoding list.
 //! Note: Opened points cannot come from the commitment domain.
 
+pub mod opening;
 mod prover;
 pub mod quotients;
+pub mod security;
 mod utils;
 mod verifier;
 
@@ -29,12 +31,50 @@ pub struct TreeColumnSpan {
 pub struct PcsConfig {
     pub pow_bits: u32,
     pub fri_config: FriConfig,
+    /// Log size of the Merkle caps sent instead of the roots of the commitment trees and the FRI
+    /// layers, see [MerkleProver::cap]. Larger caps shorten all the decommitment paths by that
+    /// many nodes, at the cost of sending `2^log_merkle_cap_size` nodes per tree. Zero sends
+    /// the roots.
+    ///
+    /// [MerkleProver::cap]: crate::core::vcs::prover::MerkleProver::cap
+    pub log_merkle_cap_size: u32,
 }
-impl Default for PcsConfig {
-    fn default() -> Self {
+impl PcsConfig {
+    /// Returns the conjectured bits of security of the queries and the proof of work. The security
+    /// of a proof is also bounded by the size of the field, see [security::estimate_security].
+    pub const fn security_bits(&self) -> u32 {
+        self.pow_bits + self.fri_config.security_bits()
+    }
+
+    /// A blowup factor of `2^10`, for the smallest proofs and the slowest proving.
+    pub fn standard() -> Self {
         Self {
             pow_bits: 20,
             fri_config: FriConfig::new(0, 10, 8),
+            log_merkle_cap_size: 0,
+        }
+    }
+
+    /// A blowup factor of `2^5`, trading proof size for proving time.
+    pub fn small() -> Self {
+        Self {
+            pow_bits: 20,
+            fri_config: FriConfig::new(0, 5, 16),
+            log_merkle_cap_size: 0,
         }
     }
+
+    /// A blowup factor of `2^2`, for the fastest proving and the largest proofs.
+    pub fn tiny() -> Self {
+        Self {
+            pow_bits: 20,
+            fri_config: FriConfig::new(0, 2, 40),
+            log_merkle_cap_size: 0,
+        }
+    }
+}
+impl Default for PcsConfig {
+    fn default() -> Self {
+        Self::standard()
+    }
 }
@@ -0,0 +1,527 @@
+//! A standalone polynomial commitment API over the FRI commitment scheme, for protocols other than
+//! the STARK of [crate::core::prover].
+//!
+//! The prover commits on sets of base or secure field circle polynomials, one commitment tree per
+//! set, and proves their values at arbitrary points. The verifier reads the commitments in the
+//! same order and verifies the proven values:
+//!
+//! 1. [PolynomialCommitmentProver::commit] or [PolynomialCommitmentProver::commit_secure] for each
+//!    set of polynomials, sending the returned Merkle cap to the verifier.
+//! 2. [PolynomialCommitmentProver::open] on the opening points of each polynomial, producing an
+//!    [OpeningProof].
+//! 3. [PolynomialCommitmentVerifier::commit] or [PolynomialCommitmentVerifier::commit_secure] for
+//!    each commitment of [OpeningProof::commitments], followed by
+//!    [PolynomialCommitmentVerifier::verify], which returns the proven values.
+//!
+//! The opening points may be drawn from the channel after the commitments. All the committed
+//! polynomials must have the same log size, and the opening points must be out of the complex
+//! subfield, see [is_valid_opening_point]. As for the STARK, the proof bounds the degree of the
+//! polynomials only in the list decoding regime, see [super].
+
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    CommitmentSchemeProof, CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig, TreeVec,
+};
+use crate::core::backend::BackendForChannel;
+use crate::core::channel::MerkleChannel;
+use crate::core::circle::CirclePoint;
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::fields::ComplexOf;
+use crate::core::poly::circle::{CirclePoly, SecureCirclePoly};
+use crate::core::poly::twiddles::TwiddleTree;
+use crate::core::prover::VerificationError;
+use crate::core::vcs::ops::MerkleHasher;
+use crate::core::ColumnVec;
+
+/// A proof of the values of committed polynomials at a set of points.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpeningProof<H: MerkleHasher> {
+    /// The Merkle cap of each committed tree, in commitment order.
+    pub commitments: TreeVec<Vec<H::Hash>>,
+    pub commitment_scheme_proof: CommitmentSchemeProof<H>,
+}
+
+/// The prover side of the polynomial commitment scheme. See the [module docs](self).
+pub struct PolynomialCommitmentProver<'a, B: BackendForChannel<MC>, MC: MerkleChannel> {
+    commitment_scheme: CommitmentSchemeProver<'a, B, MC>,
+    /// Whether each tree commits on secure polynomials, as [SECURE_EXTENSION_DEGREE] coordinate
+    /// columns each.
+    secure_trees: TreeVec<bool>,
+}
+
+impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> PolynomialCommitmentProver<'a, B, MC> {
+    /// `twiddles` must be precomputed for the evaluation domain of the polynomials, i.e. their log
+    /// size plus the blowup factor of `config`.
+    pub fn new(config: PcsConfig, twiddles: &'a TwiddleTree<B>) -> Self {
+        Self {
+            commitment_scheme: CommitmentSchemeProver::new(config, twiddles),
+            secure_trees: TreeVec::default(),
+        }
+    }
+
+    /// Commits on base field polynomials and mixes the commitment into the channel.
+    ///
+    /// Returns the Merkle cap the verifier reads with [PolynomialCommitmentVerifier::commit].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomials are not of the same log size as the committed ones.
+    pub fn commit(
+        &mut self,
+        polys: ColumnVec<CirclePoly<B>>,
+        channel: &mut MC::C,
+    ) -> Vec<<MC::H as MerkleHasher>::Hash> {
+        self.commit_tree(polys, false, channel)
+    }
+
+    /// Commits on secure field polynomials and mixes the commitment into the channel.
+    ///
+    /// Returns the Merkle cap the verifier reads with
+    /// [PolynomialCommitmentVerifier::commit_secure].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the polynomials are not of the same log size as the committed ones.
+    pub fn commit_secure(
+        &mut self,
+        polys: ColumnVec<SecureCirclePoly<B>>,
+        channel: &mut MC::C,
+    ) -> Vec<<MC::H as MerkleHasher>::Hash> {
+        let coordinate_polys = polys.into_iter().flat_map(|poly| poly.0).collect();
+        self.commit_tree(coordinate_polys, true, channel)
+    }
+
+    fn commit_tree(
+        &mut self,
+        polys: ColumnVec<CirclePoly<B>>,
+        is_secure: bool,
+        channel: &mut MC::C,
+    ) -> Vec<<MC::H as MerkleHasher>::Hash> {
+        let committed_log_size = self
+            .commitment_scheme
+            .polynomials()
+            .flatten()
+            .first()
+            .map(|p| p.log_size());
+        let log_size = committed_log_size.or(polys.first().map(|p| p.log_size()));
+        assert!(
+            polys.iter().all(|p| Some(p.log_size()) == log_size),
+            "polynomials of different log sizes"
+        );
+
+        let mut tree_builder = self.commitment_scheme.tree_builder();
+        tree_builder.extend_polys(polys);
+        tree_builder.commit(channel);
+        self.secure_trees.push(is_secure);
+        self.commitment_scheme
+            .trees
+            .last()
+            .unwrap()
+            .commitment
+            .cap()
+    }
+
+    /// Proves the values of the committed polynomials at `points`, given for each polynomial of
+    /// each tree in commitment order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `points` doesn't match the committed polynomials, or an opening point is invalid,
+    /// see [is_valid_opening_point].
+    pub fn open(
+        &self,
+        points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        channel: &mut MC::C,
+    ) -> OpeningProof<MC::H> {
+        assert!(
+            points
+                .iter()
+                .flatten()
+                .flatten()
+                .copied()
+                .all(is_valid_opening_point),
+            "invalid opening point"
+        );
+        let column_points = column_points(&self.secure_trees, points);
+        OpeningProof {
+            commitments: self.commitment_scheme.caps(),
+            commitment_scheme_proof: self.commitment_scheme.prove_values(column_points, channel),
+        }
+    }
+}
+
+/// The verifier side of the polynomial commitment scheme. See the [module docs](self).
+pub struct PolynomialCommitmentVerifier<MC: MerkleChannel> {
+    commitment_scheme: CommitmentSchemeVerifier<MC>,
+    /// Whether each tree commits on secure polynomials, see [PolynomialCommitmentProver].
+    secure_trees: TreeVec<bool>,
+}
+
+impl<MC: MerkleChannel> PolynomialCommitmentVerifier<MC> {
+    pub fn new(config: PcsConfig) -> Self {
+        Self {
+            commitment_scheme: CommitmentSchemeVerifier::new(config),
+            secure_trees: TreeVec::default(),
+        }
+    }
+
+    /// Reads a commitment on base field polynomials of the given log sizes.
+    pub fn commit(
+        &mut self,
+        commitment: Vec<<MC::H as MerkleHasher>::Hash>,
+        log_sizes: &[u32],
+        channel: &mut MC::C,
+    ) {
+        self.commitment_scheme
+            .commit(commitment, log_sizes, channel);
+        self.secure_trees.push(false);
+    }
+
+    /// Reads a commitment on secure field polynomials of the given log sizes.
+    pub fn commit_secure(
+        &mut self,
+        commitment: Vec<<MC::H as MerkleHasher>::Hash>,
+        log_sizes: &[u32],
+        channel: &mut MC::C,
+    ) {
+        let coordinate_log_sizes = log_sizes
+            .iter()
+            .flat_map(|&log_size| [log_size; SECURE_EXTENSION_DEGREE])
+            .collect::<Vec<_>>();
+        self.commitment_scheme
+            .commit(commitment, &coordinate_log_sizes, channel);
+        self.secure_trees.push(true);
+    }
+
+    /// Verifies the values of the committed polynomials at `points`, given for each polynomial of
+    /// each tree in commitment order.
+    ///
+    /// Returns the proven values, in the layout of `points`.
+    pub fn verify(
+        &self,
+        points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        proof: OpeningProof<MC::H>,
+        channel: &mut MC::C,
+    ) -> Result<TreeVec<ColumnVec<Vec<SecureField>>>, VerificationError> {
+        if !points
+            .iter()
+            .flatten()
+            .flatten()
+            .copied()
+            .all(is_valid_opening_point)
+        {
+            return Err(VerificationError::InvalidStructure(
+                "Invalid opening point".to_string(),
+            ));
+        }
+        self.check_layout(&points, &proof)?;
+
+        let values = poly_values(&self.secure_trees, &proof.commitment_scheme_proof);
+        let column_points = column_points(&self.secure_trees, points);
+        self.commitment_scheme.verify_values(
+            column_points,
+            proof.commitment_scheme_proof,
+            channel,
+        )?;
+        Ok(values)
+    }
+
+    /// Checks that the trees and columns of `proof` match the committed polynomials and `points`.
+    fn check_layout(
+        &self,
+        points: &TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        proof: &OpeningProof<MC::H>,
+    ) -> Result<(), VerificationError> {
+        let n_trees = self.secure_trees.len();
+        let pcs_proof = &proof.commitment_scheme_proof;
+        for n_tree_items in [
+            points.len(),
+            pcs_proof.sampled_values.len(),
+            pcs_proof.queried_values.len(),
+            pcs_proof.decommitments.len(),
+        ] {
+            if n_tree_items != n_trees {
+                return Err(VerificationError::InvalidCommitmentCount {
+                    expected: n_trees,
+                    actual: n_tree_items,
+                });
+            }
+        }
+
+        let column_log_sizes = self.commitment_scheme.column_log_sizes();
+        for (((tree_points, tree_values), log_sizes), &is_secure) in points
+            .iter()
+            .zip(pcs_proof.sampled_values.iter())
+            .zip(column_log_sizes.iter())
+            .zip(self.secure_trees.iter())
+        {
+            let n_coordinates = if is_secure {
+                SECURE_EXTENSION_DEGREE
+            } else {
+                1
+            };
+            if tree_points.len() * n_coordinates != log_sizes.len()
+                || tree_values.len() != log_sizes.len()
+            {
+                return Err(VerificationError::InvalidStructure(
+                    "Unexpected number of polynomials".to_string(),
+                ));
+            }
+
+            let column_points = tree_points
+                .iter()
+                .flat_map(|poly_points| std::iter::repeat(poly_points).take(n_coordinates));
+            if !column_points
+                .zip(tree_values)
+                .all(|(points, values)| points.len() == values.len())
+            {
+                return Err(VerificationError::InvalidStructure(
+                    "Unexpected sampled_values structure".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether the polynomials can be opened at `point`.
+///
+/// The quotients of the commitment scheme divide by the line through `point` and its conjugate,
+/// which requires the `y` coordinate of `point` to be out of the complex subfield. In particular,
+/// points of the commitment domain can't be opened. Random points are valid with overwhelming
+/// probability.
+pub fn is_valid_opening_point(point: CirclePoint<SecureField>) -> bool {
+    !point.y.get_imag().is_zero()
+}
+
+/// Maps the opening points of each polynomial to its columns in the commitment scheme.
+fn column_points(
+    secure_trees: &TreeVec<bool>,
+    points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+) -> TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>> {
+    TreeVec::new(
+        points
+            .0
+            .into_iter()
+            .zip(secure_trees.iter())
+            .map(|(tree_points, &is_secure)| match is_secure {
+                true => tree_points
+                    .into_iter()
+                    .flat_map(|poly_points| vec![poly_points; SECURE_EXTENSION_DEGREE])
+                    .collect(),
+                false => tree_points,
+            })
+            .collect(),
+    )
+}
+
+/// Returns the values of each polynomial at its opening points, combining the sampled values of
+/// the coordinate columns of secure polynomials.
+fn poly_values<H: MerkleHasher>(
+    secure_trees: &TreeVec<bool>,
+    proof: &CommitmentSchemeProof<H>,
+) -> TreeVec<ColumnVec<Vec<SecureField>>> {
+    TreeVec::new(
+        proof
+            .sampled_values
+            .iter()
+            .zip(secure_trees.iter())
+            .map(|(tree_values, &is_secure)| match is_secure {
+                true => tree_values
+                    .chunks_exact(SECURE_EXTENSION_DEGREE)
+                    .map(|coordinate_values| {
+                        (0..coordinate_values[0].len())
+                            .map(|i| {
+                                SecureField::from_partial_evals(std::array::from_fn(|j| {
+                                    coordinate_values[j][i]
+                                }))
+                            })
+                            .collect()
+                    })
+                    .collect(),
+                false => tree_values.clone(),
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use num_traits::One;
+
+    use super::{OpeningProof, PolynomialCommitmentProver, PolynomialCommitmentVerifier};
+    use crate::core::backend::cpu::CpuCirclePoly;
+    use crate::core::backend::{BackendForChannel, CpuBackend};
+    use crate::core::channel::{Blake3Channel, MerkleChannel};
+    use crate::core::circle::{CirclePoint, SECURE_FIELD_CIRCLE_GEN};
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::fri::FriConfig;
+    use crate::core::pcs::{PcsConfig, TreeVec};
+    use crate::core::poly::circle::{CanonicCoset, PolyOps, SecureCirclePoly};
+    use crate::core::prover::VerificationError;
+    use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+    use crate::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
+
+    const LOG_SIZE: u32 = 5;
+
+    fn config() -> PcsConfig {
+        PcsConfig {
+            pow_bits: 5,
+            fri_config: FriConfig::new(0, 2, 10),
+            log_merkle_cap_size: 1,
+        }
+    }
+
+    fn test_poly(seed: u32) -> CpuCirclePoly {
+        CpuCirclePoly::new(
+            (0..1 << LOG_SIZE)
+                .map(|i| BaseField::from_u32_unchecked(seed * i + 1))
+                .collect(),
+        )
+    }
+
+    fn test_secure_poly(seed: u32) -> SecureCirclePoly<CpuBackend> {
+        SecureCirclePoly(std::array::from_fn(|i| test_poly(seed + i as u32)))
+    }
+
+    /// Commits on two base polynomials and one secure polynomial, and opens them at random points.
+    /// Returns the proof, the opening points and the committed polynomials evaluated at them.
+    #[allow(clippy::type_complexity)]
+    fn prove<MC: MerkleChannel>(
+        points_on_commitment_domain: bool,
+    ) -> (
+        OpeningProof<MC::H>,
+        TreeVec<Vec<Vec<CirclePoint<SecureField>>>>,
+        TreeVec<Vec<Vec<SecureField>>>,
+    )
+    where
+        CpuBackend: BackendForChannel<MC>,
+    {
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + config().fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut MC::C::default();
+        let mut prover = PolynomialCommitmentProver::<CpuBackend, MC>::new(config(), &twiddles);
+        let polys = vec![test_poly(3), test_poly(5)];
+        let secure_poly = test_secure_poly(7);
+        prover.commit(polys.clone(), channel);
+        prover.commit_secure(vec![secure_poly.clone()], channel);
+
+        let random_point = CirclePoint::get_random_point(channel);
+        let fixed_point = match points_on_commitment_domain {
+            true => CanonicCoset::new(LOG_SIZE).at(1).into_ef(),
+            false => SECURE_FIELD_CIRCLE_GEN,
+        };
+        let points = TreeVec::new(vec![
+            vec![vec![random_point, fixed_point], vec![]],
+            vec![vec![random_point]],
+        ]);
+        let values = TreeVec::new(vec![
+            vec![
+                vec![
+                    polys[0].eval_at_point(random_point),
+                    polys[0].eval_at_point(fixed_point),
+                ],
+                vec![],
+            ],
+            vec![vec![secure_poly.eval_at_point(random_point)]],
+        ]);
+
+        let proof = prover.open(points.clone(), channel);
+        (proof, points, values)
+    }
+
+    fn verify<MC: MerkleChannel>(
+        proof: OpeningProof<MC::H>,
+        points: TreeVec<Vec<Vec<CirclePoint<SecureField>>>>,
+    ) -> Result<TreeVec<Vec<Vec<SecureField>>>, VerificationError> {
+        let channel = &mut MC::C::default();
+        let mut verifier = PolynomialCommitmentVerifier::<MC>::new(config());
+        verifier.commit(proof.commitments[0].clone(), &[LOG_SIZE; 2], channel);
+        verifier.commit_secure(proof.commitments[1].clone(), &[LOG_SIZE], channel);
+        // Draw the random point as the prover did.
+        let _: CirclePoint<SecureField> = CirclePoint::get_random_point(channel);
+        verifier.verify(points, proof, channel)
+    }
+
+    #[test]
+    fn test_opening_proof_verifies() {
+        let (proof, points, expected_values) = prove::<Blake3MerkleChannel>(false);
+
+        let values = verify::<Blake3MerkleChannel>(proof, points).unwrap();
+
+        assert_eq!(values.0, expected_values.0);
+    }
+
+    #[test]
+    fn test_opening_proof_with_poseidon31_verifies() {
+        let (proof, points, expected_values) = prove::<Poseidon31MerkleChannel>(false);
+
+        let values = verify::<Poseidon31MerkleChannel>(proof, points).unwrap();
+
+        assert_eq!(values.0, expected_values.0);
+    }
+
+    #[test]
+    fn test_opening_proof_serde_round_trip_verifies() {
+        let (proof, points, _) = prove::<Blake3MerkleChannel>(false);
+
+        let serialized = serde_json::to_string(&proof).unwrap();
+        let proof = serde_json::from_str(&serialized).unwrap();
+
+        verify::<Blake3MerkleChannel>(proof, points).unwrap();
+    }
+
+    #[test]
+    fn test_opening_proof_with_wrong_value_fails_verification() {
+        let (mut proof, points, _) = prove::<Blake3MerkleChannel>(false);
+        proof.commitment_scheme_proof.sampled_values[1][2][0] += SecureField::one();
+
+        let result = verify::<Blake3MerkleChannel>(proof, points);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_opening_proof_with_missing_values_fails_verification() {
+        let (mut proof, points, _) = prove::<Blake3MerkleChannel>(false);
+        proof.commitment_scheme_proof.sampled_values[0][0].pop();
+
+        let result = verify::<Blake3MerkleChannel>(proof, points);
+
+        assert!(matches!(
+            result,
+            Err(VerificationError::InvalidStructure(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid opening point")]
+    fn test_open_at_commitment_domain_point_panics() {
+        prove::<Blake3MerkleChannel>(true);
+    }
+
+    #[test]
+    #[should_panic(expected = "polynomials of different log sizes")]
+    fn test_commit_on_polys_of_different_log_sizes_panics() {
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + 1 + config().fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let mut prover =
+            PolynomialCommitmentProver::<CpuBackend, Blake3MerkleChannel>::new(config(), &twiddles);
+        prover.commit(vec![test_poly(3)], channel);
+
+        let larger_poly =
+            CpuCirclePoly::new(vec![BaseField::from_u32_unchecked(1); 1 << (LOG_SIZE + 1)]);
+        prover.commit(vec![larger_poly], channel);
+    }
+}
@@ -38,14 +38,26 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
     }
 
     fn commit(&mut self, polynomials: ColumnVec<CirclePoly<B>>, channel: &mut MC::C) {
+        let tree = self.build_tree(polynomials, channel);
+        self.trees.push(tree);
+    }
+
+    /// Commits on `polynomials` and mixes the commitment into the channel, like a [TreeBuilder],
+    /// but returns the tree instead of adding it to the scheme. The trees of the scheme can thus
+    /// stay borrowed until the tree is added, see [Self::prove_values_with_tree].
+    pub fn build_tree(
+        &self,
+        polynomials: ColumnVec<CirclePoly<B>>,
+        channel: &mut MC::C,
+    ) -> CommitmentTreeProver<B, MC> {
         let _span = span!(Level::INFO, "Commitment").entered();
-        let tree = CommitmentTreeProver::new(
+        CommitmentTreeProver::new(
             polynomials,
             self.config.fri_config.log_blowup_factor,
+            self.config.log_merkle_cap_size,
             channel,
             self.twiddles,
-        );
-        self.trees.push(tree);
+        )
     }
 
     pub fn tree_builder(&mut self) -> TreeBuilder<'_, 'a, B, MC> {
@@ -56,8 +68,8 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
         }
     }
 
-    pub fn roots(&self) -> TreeVec<<MC::H as MerkleHasher>::Hash> {
-        self.trees.as_ref().map(|tree| tree.commitment.root())
+    pub fn caps(&self) -> TreeVec<Vec<<MC::H as MerkleHasher>::Hash>> {
+        self.trees.as_ref().map(|tree| tree.commitment.cap())
     }
 
     pub fn polynomials(&self) -> TreeVec<ColumnVec<&CirclePoly<B>>> {
@@ -66,21 +78,38 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
             .map(|tree| tree.polynomials.iter().collect())
     }
 
-    fn evaluations(&self) -> TreeVec<ColumnVec<&CircleEvaluation<B, BaseField, BitReversedOrder>>> {
-        self.trees
-            .as_ref()
-            .map(|tree| tree.evaluations.iter().collect())
+    pub fn prove_values(
+        &self,
+        sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        channel: &mut MC::C,
+    ) -> CommitmentSchemeProof<MC::H> {
+        self.prove_trees_values(self.trees.as_ref(), sampled_points, channel)
     }
 
-    pub fn prove_values(
+    /// Like [Self::prove_values], with `tree` as the last tree, after the trees of the scheme. It
+    /// is expected to be built with [Self::build_tree] after them.
+    pub fn prove_values_with_tree(
         &self,
+        tree: &CommitmentTreeProver<B, MC>,
+        sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
+        channel: &mut MC::C,
+    ) -> CommitmentSchemeProof<MC::H> {
+        let mut trees = self.trees.as_ref();
+        trees.push(tree);
+        self.prove_trees_values(trees, sampled_points, channel)
+    }
+
+    fn prove_trees_values(
+        &self,
+        trees: TreeVec<&CommitmentTreeProver<B, MC>>,
         sampled_points: TreeVec<ColumnVec<Vec<CirclePoint<SecureField>>>>,
         channel: &mut MC::C,
     ) -> CommitmentSchemeProof<MC::H> {
         // Evaluate polynomials on open points.
         let span = span!(Level::INFO, "Evaluate columns out of domain").entered();
-        let samples = self
-            .polynomials()
+        let samples = trees
+            .as_ref()
+            .map(|tree| tree.polynomials.iter().collect_vec())
             .zip_cols(&sampled_points)
             .map_cols(|(poly, points)| {
                 points
@@ -98,7 +127,10 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
         channel.mix_felts(&sampled_values.clone().flatten_cols());
 
         // Compute oods quotients for boundary constraints on the sampled points.
-        let columns = self.evaluations().flatten();
+        let columns = trees
+            .as_ref()
+            .map(|tree| tree.evaluations.iter().collect_vec())
+            .flatten();
         let quotients = compute_fri_quotients(
             &columns,
             &samples.flatten(),
@@ -107,8 +139,13 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
         );
 
         // Run FRI commitment phase on the oods quotients.
-        let fri_prover =
-            FriProver::<B, MC>::commit(channel, self.config.fri_config, &quotients, self.twiddles);
+        let fri_prover = FriProver::<B, MC>::commit(
+            channel,
+            self.config.fri_config,
+            self.config.log_merkle_cap_size,
+            &quotients,
+            self.twiddles,
+        );
 
         // Proof of work.
         let span1 = span!(Level::INFO, "Grind").entered();
@@ -120,7 +157,7 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
         let (fri_proof, fri_query_domains) = fri_prover.decommit(channel);
 
         // Decommit the FRI queries on the merkle trees.
-        let decommitment_results = self.trees.as_ref().map(|tree| {
+        let decommitment_results = trees.map(|tree| {
             let queries = fri_query_domains
                 .iter()
                 .map(|(&log_size, domain)| (log_size, domain.flatten()))
@@ -189,8 +226,16 @@ impl<B: BackendForChannel<MC>, MC: MerkleChannel> TreeBuilder<'_, '_, B, MC> {
         }
     }
 
+    /// Returns the polynomials of the trees committed so far, followed by the columns added to
+    /// this tree, e.g. to check the constraints of the trace before committing it, see
+    /// [crate::core::prover::check_constraints_on_trace].
+    pub fn trace_polys(&self) -> TreeVec<ColumnVec<&CirclePoly<B>>> {
+        let mut polys = self.commitment_scheme.polynomials();
+        polys.push(self.polys.iter().collect());
+        polys
+    }
+
     pub fn commit(self, channel: &mut MC::C) {
-        let _span = span!(Level::INFO, "Commitment").entered();
         self.commitment_scheme.commit(self.polys, channel);
     }
 }
@@ -207,6 +252,7 @@ impl<B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentTreeProver<B, MC> {
     pub fn new(
         polynomials: ColumnVec<CirclePoly<B>>,
         log_blowup_factor: u32,
+        log_merkle_cap_size: u32,
         channel: &mut MC::C,
         twiddles: &TwiddleTree<B>,
     ) -> Self {
@@ -224,8 +270,11 @@ impl<B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentTreeProver<B, MC> {
         span.exit();
 
         let _span = span!(Level::INFO, "Merkle").entered();
-        let tree = MerkleProver::commit(evaluations.iter().map(|eval| &eval.values).collect());
-        MC::mix_root(channel, tree.root());
+        let tree = MerkleProver::commit_with_cap(
+            evaluations.iter().map(|eval| &eval.values).collect(),
+            log_merkle_cap_size,
+        );
+        MC::mix_cap(channel, &tree.cap());
 
         CommitmentTreeProver {
             polynomials,
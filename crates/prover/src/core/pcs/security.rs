@@ -0,0 +1,202 @@
+//! Estimates the security of a proof from its [PcsConfig].
+//!
+//! The estimates are the minimum of two bounds:
+//! - The query bound: the soundness of FRI, from the number of queries and the blowup factor, plus
+//!   the proof of work bits.
+//! - The field bound: the soundness of sampling the out of domain point and the random
+//!   coefficients, from the size of [SecureField](crate::core::fields::qm31::SecureField) and the
+//!   size of the evaluation domain.
+//!
+//! The conjectured estimate assumes each query gives `log_blowup_factor` bits (the ethSTARK
+//! conjecture). The provable estimate only assumes the unique decoding regime.
+
+use super::PcsConfig;
+use crate::core::fields::m31::P;
+use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::fri::FriConfig;
+
+/// The bits of security of a proof, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SecurityEstimate {
+    pub conjectured_bits: u32,
+    pub provable_bits: u32,
+}
+
+/// Returns the number of bits of [SecureField](crate::core::fields::qm31::SecureField), rounded
+/// down.
+pub fn secure_field_bits() -> u32 {
+    ((P as f64).log2() * SECURE_EXTENSION_DEGREE as f64).floor() as u32
+}
+
+/// Estimates the security of a proof with `config`, whose composition polynomial is of degree
+/// bound `2^log_composition_degree_bound`, i.e. the max constraint degree times the trace size.
+pub fn estimate_security(
+    config: &PcsConfig,
+    log_composition_degree_bound: u32,
+) -> SecurityEstimate {
+    let fri_config = &config.fri_config;
+    let field_bits = field_security_bits(fri_config, log_composition_degree_bound);
+
+    let conjectured_query_bits = fri_config.security_bits() + config.pow_bits;
+
+    // In the unique decoding regime, a query catches a cheating prover with probability at least
+    // (1 - rate) / 2.
+    let rate = 0.5f64.powi(fri_config.log_blowup_factor as i32);
+    let provable_bits_per_query = -((1. + rate) / 2.).log2();
+    let provable_query_bits =
+        (provable_bits_per_query * fri_config.n_queries as f64).floor() as u32 + config.pow_bits;
+
+    SecurityEstimate {
+        conjectured_bits: conjectured_query_bits.min(field_bits),
+        provable_bits: provable_query_bits.min(field_bits),
+    }
+}
+
+fn field_security_bits(fri_config: &FriConfig, log_composition_degree_bound: u32) -> u32 {
+    let log_evaluation_domain_size = log_composition_degree_bound + fri_config.log_blowup_factor;
+    secure_field_bits().saturating_sub(log_evaluation_domain_size)
+}
+
+/// Returns the config with the smallest proof that reaches `target_bits` of conjectured security,
+/// or `None` if there is none within the given bounds.
+///
+/// The proof size is estimated by the size of the query decommitments: each query opens a Merkle
+/// path in each FRI layer, so its cost grows with the square of the evaluation domain log size.
+/// The blowup factor is bounded by `max_log_blowup_factor`, since the proving time grows with it,
+/// and the proof of work by `max_pow_bits`.
+pub fn suggest_config(
+    target_bits: u32,
+    log_composition_degree_bound: u32,
+    max_log_blowup_factor: u32,
+    max_pow_bits: u32,
+) -> Option<PcsConfig> {
+    let log_blowup_factors = FriConfig::LOG_MIN_BLOWUP_FACTOR
+        ..=max_log_blowup_factor.min(FriConfig::LOG_MAX_BLOWUP_FACTOR);
+    log_blowup_factors
+        .flat_map(|log_blowup_factor| {
+            (0..=max_pow_bits).map(move |pow_bits| {
+                let query_bits = target_bits.saturating_sub(pow_bits);
+                // FRI needs at least one query, even when the proof of work reaches the target.
+                let n_queries = query_bits.div_ceil(log_blowup_factor).max(1) as usize;
+                PcsConfig {
+                    pow_bits,
+                    fri_config: FriConfig::new(0, log_blowup_factor, n_queries),
+                    log_merkle_cap_size: 0,
+                }
+            })
+        })
+        .filter(|config| {
+            estimate_security(config, log_composition_degree_bound).conjectured_bits >= target_bits
+        })
+        .min_by_key(|config| {
+            let log_evaluation_domain_size =
+                (log_composition_degree_bound + config.fri_config.log_blowup_factor) as usize;
+            let decommitment_size = log_evaluation_domain_size * (log_evaluation_domain_size + 1);
+            // Prefer less proof of work among configs of equal proof size.
+            (
+                config.fri_config.n_queries * decommitment_size,
+                config.pow_bits,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use super::{estimate_security, secure_field_bits, suggest_config};
+    use crate::core::fri::FriConfig;
+    use crate::core::pcs::{CommitmentSchemeVerifier, PcsConfig};
+    use crate::core::prover::VerificationError;
+    use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+
+    #[test]
+    fn test_secure_field_bits() {
+        assert_eq!(secure_field_bits(), 123);
+    }
+
+    #[test]
+    fn test_estimate_security() {
+        let config = PcsConfig {
+            pow_bits: 20,
+            fri_config: FriConfig::new(0, 4, 20),
+            log_merkle_cap_size: 0,
+        };
+
+        let estimate = estimate_security(&config, 16);
+
+        assert_eq!(estimate.conjectured_bits, 100);
+        assert!(estimate.provable_bits < estimate.conjectured_bits);
+    }
+
+    #[test]
+    fn test_estimate_security_is_bounded_by_field() {
+        let config = PcsConfig {
+            pow_bits: 30,
+            fri_config: FriConfig::new(0, 10, 20),
+            log_merkle_cap_size: 0,
+        };
+
+        let estimate = estimate_security(&config, 30);
+
+        assert_eq!(estimate.conjectured_bits, secure_field_bits() - 40);
+    }
+
+    #[test]
+    fn test_suggest_config_meets_target() {
+        let config = suggest_config(96, 20, 8, 26).unwrap();
+
+        assert!(estimate_security(&config, 20).conjectured_bits >= 96);
+        assert!(config.fri_config.log_blowup_factor <= 8);
+        assert!(config.pow_bits <= 26);
+    }
+
+    #[test]
+    fn test_suggest_config_has_queries() {
+        let config = suggest_config(20, 20, 8, 26).unwrap();
+
+        assert!(config.fri_config.n_queries >= 1);
+        assert!(estimate_security(&config, 20).conjectured_bits >= 20);
+    }
+
+    #[test]
+    fn test_suggest_config_unreachable_target() {
+        assert!(suggest_config(128, 20, 16, 30).is_none());
+    }
+
+    #[test]
+    fn test_verifier_rejects_insufficient_security() {
+        let config = PcsConfig::tiny();
+
+        let result =
+            CommitmentSchemeVerifier::<Blake3MerkleChannel>::new_with_min_security(config, 20, 128);
+
+        assert_matches!(
+            result.err(),
+            Some(VerificationError::InsufficientSecurity {
+                security_bits: 100,
+                min_security_bits: 128
+            })
+        );
+        assert!(
+            CommitmentSchemeVerifier::<Blake3MerkleChannel>::new_with_min_security(config, 20, 100)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_verifier_rejects_insufficient_field_security() {
+        let config = PcsConfig::tiny();
+
+        let result =
+            CommitmentSchemeVerifier::<Blake3MerkleChannel>::new_with_min_security(config, 30, 100);
+
+        assert_matches!(
+            result.err(),
+            Some(VerificationError::InsufficientSecurity {
+                security_bits: 91,
+                min_security_bits: 100
+            })
+        );
+    }
+}
@@ -6,6 +6,7 @@ use super::super::circle::CirclePoint;
 use super::super::fields::qm31::SecureField;
 use super::super::fri::{CirclePolyDegreeBound, FriVerifier};
 use super::quotients::{fri_answers, PointSample};
+use super::security::estimate_security;
 use super::utils::TreeVec;
 use super::{CommitmentSchemeProof, PcsConfig};
 use crate::core::channel::{Channel, MerkleChannel};
@@ -29,6 +30,25 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
         }
     }
 
+    /// Creates a verifier that rejects configs below `min_security_bits` of conjectured security,
+    /// for a composition polynomial of degree bound `2^log_composition_degree_bound`, see
+    /// [estimate_security].
+    pub fn new_with_min_security(
+        config: PcsConfig,
+        log_composition_degree_bound: u32,
+        min_security_bits: u32,
+    ) -> Result<Self, VerificationError> {
+        let security_bits =
+            estimate_security(&config, log_composition_degree_bound).conjectured_bits;
+        if security_bits < min_security_bits {
+            return Err(VerificationError::InsufficientSecurity {
+                security_bits,
+                min_security_bits,
+            });
+        }
+        Ok(Self::new(config))
+    }
+
     /// A [`TreeVec<ColumnVec>`] of the log sizes of each column in each commitment tree.
     pub fn column_log_sizes(&self) -> TreeVec<ColumnVec<u32>> {
         self.trees
@@ -36,19 +56,24 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
             .map(|tree| tree.column_log_sizes.clone())
     }
 
-    /// Reads a commitment from the prover.
+    /// Reads a commitment from the prover, i.e. the Merkle cap of the tree, see
+    /// [PcsConfig::log_merkle_cap_size].
     pub fn commit(
         &mut self,
-        commitment: <MC::H as MerkleHasher>::Hash,
+        commitment: Vec<<MC::H as MerkleHasher>::Hash>,
         log_sizes: &[u32],
         channel: &mut MC::C,
     ) {
-        MC::mix_root(channel, commitment);
+        MC::mix_cap(channel, &commitment);
         let extended_log_sizes = log_sizes
             .iter()
             .map(|&log_size| log_size + self.config.fri_config.log_blowup_factor)
             .collect();
-        let verifier = MerkleVerifier::new(commitment, extended_log_sizes);
+        let verifier = MerkleVerifier::new(
+            commitment,
+            extended_log_sizes,
+            self.config.log_merkle_cap_size,
+        );
         self.trees.push(verifier);
     }
 
@@ -78,8 +103,13 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
             .collect_vec();
 
         // FRI commitment phase on OODS quotients.
-        let mut fri_verifier =
-            FriVerifier::<MC>::commit(channel, self.config.fri_config, proof.fri_proof, bounds)?;
+        let mut fri_verifier = FriVerifier::<MC>::commit(
+            channel,
+            self.config.fri_config,
+            self.config.log_merkle_cap_size,
+            proof.fri_proof,
+            bounds,
+        )?;
 
         // Verify proof of work.
         channel.mix_nonce(proof.proof_of_work);
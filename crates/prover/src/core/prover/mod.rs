@@ -1,50 +1,51 @@
-use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::slice;
+
+use itertools::{zip_eq, Itertools};
+use num_traits::Zero;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{span, Level};
 
-use super::air::{Component, ComponentProver, ComponentProvers, Components};
-use super::backend::BackendForChannel;
+use super::air::{
+    Component, ComponentProver, ComponentProvers, ComponentTrace, Components, RelationClaimedSum,
+    PREPROCESSED_TRACE_IDX,
+};
+use super::backend::{Backend, BackendForChannel};
 use super::channel::MerkleChannel;
 use super::fields::secure_column::SECURE_EXTENSION_DEGREE;
 use super::fri::FriVerificationError;
+use super::lookups::gkr_verifier::{GkrBatchProof, GkrError};
 use super::pcs::{CommitmentSchemeProof, TreeVec};
 use super::vcs::ops::MerkleHasher;
-use super::{ColumnVec, InteractionElements, LookupValues};
+use super::{ColumnVec, InteractionElements, LookupValues, Statement};
 use crate::core::backend::CpuBackend;
 use crate::core::channel::Channel;
 use crate::core::circle::CirclePoint;
 use crate::core::fields::qm31::SecureField;
 use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier};
-use crate::core::poly::circle::CircleEvaluation;
+use crate::core::poly::circle::{CircleEvaluation, CirclePoly};
 use crate::core::poly::BitReversedOrder;
 use crate::core::vcs::verifier::MerkleVerificationError;
 
-#[cfg(all(feature = "tiny_blowup", feature = "small_blowup"))]
-compile_error!(
-    "feature \"tiny_blowup\" and feature \"small_blowup\" cannot be enabled at the same time"
-);
-
-#[cfg(not(any(feature = "small_blowup", feature = "tiny_blowup")))]
-pub const LOG_BLOWUP_FACTOR: u32 = 10;
-#[cfg(feature = "small_blowup")]
-pub const LOG_BLOWUP_FACTOR: u32 = 5;
-#[cfg(feature = "tiny_blowup")]
-pub const LOG_BLOWUP_FACTOR: u32 = 2;
-
-pub const LOG_LAST_LAYER_DEGREE_BOUND: u32 = 0;
-pub const PROOF_OF_WORK_BITS: u32 = 20;
-#[cfg(not(any(feature = "small_blowup", feature = "tiny_blowup")))]
-pub const N_QUERIES: usize = 8;
-#[cfg(feature = "small_blowup")]
-pub const N_QUERIES: usize = 16;
-#[cfg(feature = "tiny_blowup")]
-pub const N_QUERIES: usize = 40;
+mod session;
+pub use session::{LookupLayersGenerator, Phase, Session, SessionChallenges, TraceGenerator};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StarkProof<H: MerkleHasher> {
-    pub commitments: TreeVec<H::Hash>,
+    /// The public inputs the proof is about, see [Statement::mix_into].
+    pub statement: Statement,
+    /// The GKR proof of the lookups proven with
+    /// [crate::core::lookups::gkr_lookups::prove_lookups], if any, instead of committed logup
+    /// columns. It is present if and only if a component declares such lookups, see
+    /// [Component::gkr_lookups].
+    pub gkr_proof: Option<GkrBatchProof>,
+    /// The Merkle cap of each committed tree, see [crate::core::pcs::PcsConfig].
+    pub commitments: TreeVec<Vec<H::Hash>>,
     pub lookup_values: LookupValues,
+    /// The claimed sums of each component, see [Component::claimed_sums]. The verifier checks that
+    /// they balance with the public claimed sums, and builds the components from them.
+    pub claimed_sums: Vec<Vec<RelationClaimedSum>>,
     pub commitment_scheme_proof: CommitmentSchemeProof<H>,
 }
 
@@ -56,15 +57,29 @@ pub struct AdditionalProofData {
     pub oods_quotients: Vec<CircleEvaluation<CpuBackend, SecureField, BitReversedOrder>>,
 }
 
+/// Proves the constraints of the components on the trees committed in `commitment_scheme`.
+///
+/// `statement` is expected to be mixed into `channel` before the first commitment, see
+/// [Statement::mix_into]. It is recorded in the proof for the verifier, see
+/// [Session::commit_verifier].
+///
+/// `gkr_proof` is the GKR proof of the lookups of the components, if any, proven between the trace
+/// commitments, see [Session::commit_prover]. It is recorded in the proof for the verifier, who
+/// verifies it in [Session::commit_verifier] before building the components.
 pub fn prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
     components: &[&dyn ComponentProver<B>],
     channel: &mut MC::C,
+    statement: &Statement,
+    gkr_proof: Option<GkrBatchProof>,
     interaction_elements: &InteractionElements,
     commitment_scheme: &mut CommitmentSchemeProver<'_, B, MC>,
 ) -> Result<StarkProof<MC::H>, ProvingError> {
     let component_provers = ComponentProvers(components.to_vec());
     let component_traces = component_provers.component_traces(&commitment_scheme.trees);
     let lookup_values = component_provers.lookup_values(&component_traces);
+    let claimed_sums = component_provers.components().claimed_sums();
+
+    mix_claimed_sums(channel, &claimed_sums);
 
     // Evaluate and commit on composition polynomial.
     let random_coeff = channel.draw_felt();
@@ -79,9 +94,10 @@ pub fn prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
     );
     span1.exit();
 
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_polys(composition_polynomial_poly.to_vec());
-    tree_builder.commit(channel);
+    // The composition tree is added to the commitment scheme once the proof is checked, as the
+    // component traces borrow its trace trees until then.
+    let composition_tree =
+        commitment_scheme.build_tree(composition_polynomial_poly.to_vec(), channel);
     span.exit();
 
     // Draw OODS point.
@@ -93,7 +109,8 @@ pub fn prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
     sample_points.push(vec![vec![oods_point]; SECURE_EXTENSION_DEGREE]);
 
     // Prove the trace and composition OODS values, and retrieve them.
-    let commitment_scheme_proof = commitment_scheme.prove_values(sample_points, channel);
+    let commitment_scheme_proof =
+        commitment_scheme.prove_values_with_tree(&composition_tree, sample_points, channel);
 
     // Evaluate composition polynomial at OODS point and check that it matches the trace OODS
     // values. This is a sanity check.
@@ -115,16 +132,98 @@ pub fn prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
                 &lookup_values,
             )
     {
-        return Err(ProvingError::ConstraintsNotSatisfied);
+        let components = inconsistent_components(
+            &component_provers,
+            &component_traces,
+            &trace_oods_values,
+            oods_point,
+            random_coeff,
+            interaction_elements,
+            &lookup_values,
+        );
+        return Err(ProvingError::ConstraintsNotSatisfied { components });
     }
+    commitment_scheme.trees.push(composition_tree);
 
     Ok(StarkProof {
-        commitments: commitment_scheme.roots(),
+        statement: statement.clone(),
+        gkr_proof,
+        commitments: commitment_scheme.caps(),
         lookup_values,
+        claimed_sums,
         commitment_scheme_proof,
     })
 }
 
+/// Returns the indices of the components whose composition polynomial, evaluated alone, does not
+/// match their mask values at the OODS point.
+fn inconsistent_components<B: Backend>(
+    component_provers: &ComponentProvers<'_, B>,
+    component_traces: &[ComponentTrace<'_, B>],
+    trace_oods_values: &[TreeVec<Vec<Vec<SecureField>>>],
+    oods_point: CirclePoint<SecureField>,
+    random_coeff: SecureField,
+    interaction_elements: &InteractionElements,
+    lookup_values: &LookupValues,
+) -> Vec<usize> {
+    let _span = span!(Level::INFO, "Inconsistent components").entered();
+    zip_eq(
+        &component_provers.0,
+        zip_eq(component_traces, trace_oods_values),
+    )
+    .positions(|(&component, (trace, mask))| {
+        let component_prover = ComponentProvers(vec![component]);
+        let composition_poly = component_prover.compute_composition_polynomial(
+            random_coeff,
+            slice::from_ref(trace),
+            interaction_elements,
+            lookup_values,
+        );
+        composition_poly.eval_at_point(oods_point)
+            != component_prover
+                .components()
+                .eval_composition_polynomial_at_point(
+                    oods_point,
+                    &vec![mask.clone()],
+                    random_coeff,
+                    interaction_elements,
+                    lookup_values,
+                )
+    })
+    .collect()
+}
+
+/// Checks the constraints of each component row by row on its trace domain, see
+/// [ComponentProver::check_constraints_on_trace].
+///
+/// `trace_polys` are the polynomials of the trace trees, e.g.
+/// [crate::core::pcs::TreeBuilder::trace_polys] before the last trace tree is committed, so that
+/// an invalid trace fails before it is committed and proven.
+pub fn check_constraints_on_trace<B: Backend>(
+    components: &[&dyn ComponentProver<B>],
+    trace_polys: &TreeVec<ColumnVec<&CirclePoly<B>>>,
+) -> Result<(), ProvingError> {
+    let _span = span!(Level::INFO, "Trace constraints check").entered();
+    let component_provers = ComponentProvers(components.to_vec());
+    for (component_index, (component, trace)) in
+        zip_eq(components, component_provers.component_polys(trace_polys)).enumerate()
+    {
+        component
+            .check_constraints_on_trace(&trace)
+            .map_err(|report| ProvingError::TraceConstraintsNotSatisfied {
+                component: component_index,
+                report,
+            })?;
+    }
+    Ok(())
+}
+
+/// Verifies a proof of the components, once its trace commitments are read into
+/// `commitment_scheme` and the interaction elements are drawn, see [Session::commit_verifier].
+///
+/// The proof is rejected if its trees do not have the layout described by the components, see
+/// [Components::column_log_sizes], or if it carries a GKR proof while no component declares GKR
+/// lookups, see [Component::gkr_lookups].
 pub fn verify<MC: MerkleChannel>(
     components: &[&dyn Component],
     channel: &mut MC::C,
@@ -133,11 +232,23 @@ pub fn verify<MC: MerkleChannel>(
     proof: StarkProof<MC::H>,
 ) -> Result<(), VerificationError> {
     let components = Components(components.to_vec());
+    check_proof_layout(&components.column_log_sizes(), &proof)?;
+    check_gkr_proof(!components.gkr_lookups().is_empty(), &proof)?;
+    check_claimed_sums(&components, &proof.claimed_sums)?;
+    check_logup_balance(
+        proof
+            .claimed_sums
+            .iter()
+            .flatten()
+            .chain(&components.public_claimed_sums()),
+    )?;
+    check_lookup_values(&components, &proof.lookup_values)?;
+    mix_claimed_sums(channel, &proof.claimed_sums);
     let random_coeff = channel.draw_felt();
 
-    // Read composition polynomial commitment.
+    // Read composition polynomial commitment. The layout check ensures it is there.
     commitment_scheme.commit(
-        *proof.commitments.last().unwrap(),
+        proof.commitments.last().unwrap().clone(),
         &[components.composition_log_degree_bound(); SECURE_EXTENSION_DEGREE],
         channel,
     );
@@ -174,6 +285,144 @@ pub fn verify<MC: MerkleChannel>(
     commitment_scheme.verify_values(sample_points, proof.commitment_scheme_proof, channel)
 }
 
+/// Checks that `proof` is of the `statement` the verifier expects.
+fn check_statement<H: MerkleHasher>(
+    statement: &Statement,
+    proof: &StarkProof<H>,
+) -> Result<(), VerificationError> {
+    if proof.statement != *statement {
+        return Err(VerificationError::StatementMismatch);
+    }
+    Ok(())
+}
+
+/// Checks that the trees of `proof` have the shape implied by the trace `column_log_sizes`,
+/// followed by a single composition polynomial tree.
+fn check_proof_layout<H: MerkleHasher>(
+    column_log_sizes: &TreeVec<ColumnVec<u32>>,
+    proof: &StarkProof<H>,
+) -> Result<(), VerificationError> {
+    let n_trees = column_log_sizes.len() + 1;
+    if proof.commitments.len() != n_trees {
+        return Err(VerificationError::InvalidCommitmentCount {
+            expected: n_trees,
+            actual: proof.commitments.len(),
+        });
+    }
+
+    let pcs_proof = &proof.commitment_scheme_proof;
+    for n_tree_items in [
+        pcs_proof.sampled_values.len(),
+        pcs_proof.queried_values.len(),
+        pcs_proof.decommitments.len(),
+    ] {
+        if n_tree_items != n_trees {
+            return Err(VerificationError::InvalidCommitmentCount {
+                expected: n_trees,
+                actual: n_tree_items,
+            });
+        }
+    }
+
+    let expected_n_columns = column_log_sizes
+        .iter()
+        .map(|log_sizes| log_sizes.len())
+        .chain([SECURE_EXTENSION_DEGREE]);
+    for (tree, expected) in expected_n_columns.enumerate() {
+        for actual in [
+            pcs_proof.sampled_values[tree].len(),
+            pcs_proof.queried_values[tree].len(),
+        ] {
+            if actual != expected {
+                return Err(VerificationError::InvalidTreeShape {
+                    tree,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `proof` carries a GKR proof if and only if lookups are proven with GKR.
+fn check_gkr_proof<H: MerkleHasher>(
+    expects_gkr_proof: bool,
+    proof: &StarkProof<H>,
+) -> Result<(), VerificationError> {
+    match (expects_gkr_proof, &proof.gkr_proof) {
+        (true, None) => Err(VerificationError::InvalidStructure(
+            "Missing GKR proof of the lookups".to_string(),
+        )),
+        (false, Some(_)) => Err(VerificationError::InvalidStructure(
+            "Unexpected GKR proof".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn mix_claimed_sums(channel: &mut impl Channel, claimed_sums: &[Vec<RelationClaimedSum>]) {
+    channel.mix_felts(
+        &claimed_sums
+            .iter()
+            .flatten()
+            .map(|relation_sum| relation_sum.claimed_sum)
+            .collect_vec(),
+    );
+}
+
+/// Checks that the components were built from the claimed sums of the proof, which are balanced
+/// and mixed into the channel: otherwise the constraints would bind the logup columns to other
+/// sums than the checked ones.
+fn check_claimed_sums(
+    components: &Components<'_>,
+    claimed_sums: &[Vec<RelationClaimedSum>],
+) -> Result<(), VerificationError> {
+    if components.claimed_sums() != claimed_sums {
+        return Err(VerificationError::InvalidStructure(
+            "Claimed sums do not match the components".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the claimed sums of each lookup relation add up to zero.
+fn check_logup_balance<'a>(
+    claimed_sums: impl IntoIterator<Item = &'a RelationClaimedSum>,
+) -> Result<(), VerificationError> {
+    let mut relation_sums = BTreeMap::<&str, SecureField>::new();
+    for relation_sum in claimed_sums {
+        *relation_sums.entry(&relation_sum.relation).or_default() += relation_sum.claimed_sum;
+    }
+    match relation_sums.into_iter().find(|(_, sum)| !sum.is_zero()) {
+        Some((relation, sum)) => Err(VerificationError::LogupImbalance {
+            relation: relation.to_string(),
+            sum,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `lookup_values` holds exactly the lookup values read by the components.
+fn check_lookup_values(
+    components: &Components<'_>,
+    lookup_values: &LookupValues,
+) -> Result<(), VerificationError> {
+    let expected_ids = components.lookup_value_ids();
+    for id in &expected_ids {
+        lookup_values
+            .get(id)
+            .map_err(|err| VerificationError::InvalidLookup(err.to_string()))?;
+    }
+    match lookup_values.0.keys().find(|id| !expected_ids.contains(id)) {
+        Some(id) => Err(VerificationError::InvalidLookup(format!(
+            "Unexpected lookup value {id}."
+        ))),
+        None => Ok(()),
+    }
+}
+
 #[allow(clippy::type_complexity)]
 /// Structures the tree-wise sampled values into component-wise OODS values and a composition
 /// polynomial OODS value.
@@ -184,19 +433,32 @@ pub fn sampled_values_to_mask(
     let mut sampled_values = sampled_values.as_ref();
     let composition_values = sampled_values.pop().ok_or(InvalidOodsSampleStructure)?;
 
+    let preprocessed_values: &[Vec<SecureField>] = sampled_values
+        .get(PREPROCESSED_TRACE_IDX)
+        .map_or(&[], |tree_values| tree_values);
     let mut sample_iters = sampled_values.map(|tree_value| tree_value.iter());
     let trace_oods_values = components
         .0
         .iter()
         .map(|component| {
-            component
+            let mut mask = component
                 .mask_points(CirclePoint::zero())
                 .zip(sample_iters.as_mut())
                 .map(|(mask_per_tree, tree_iter)| {
                     tree_iter.take(mask_per_tree.len()).cloned().collect_vec()
-                })
+                });
+            // Preprocessed columns are shared, and picked by index.
+            if let Some(preprocessed_mask) = mask.get_mut(PREPROCESSED_TRACE_IDX) {
+                *preprocessed_mask = component
+                    .preprocessed_column_indices()
+                    .iter()
+                    .map(|&i| preprocessed_values.get(i).cloned())
+                    .collect::<Option<_>>()
+                    .ok_or(InvalidOodsSampleStructure)?;
+            }
+            Ok(mask)
         })
-        .collect_vec();
+        .collect::<Result<Vec<_>, _>>()?;
 
     let composition_oods_value = SecureField::from_partial_evals(
         composition_values
@@ -215,18 +477,42 @@ pub fn sampled_values_to_mask(
 #[derive(Clone, Copy, Debug)]
 pub struct InvalidOodsSampleStructure;
 
-#[derive(Clone, Copy, Debug, Error)]
+#[derive(Clone, Debug, Error)]
 pub enum ProvingError {
-    #[error("Constraints not satisfied.")]
-    ConstraintsNotSatisfied,
+    #[error("Constraints not satisfied by components {components:?}.")]
+    ConstraintsNotSatisfied { components: Vec<usize> },
+    #[error("Constraints of component {component} not satisfied on the trace domain:\n{report}")]
+    TraceConstraintsNotSatisfied { component: usize, report: String },
 }
 
 #[derive(Clone, Debug, Error)]
 pub enum VerificationError {
     #[error("Proof has invalid structure: {0}.")]
     InvalidStructure(String),
-    #[error("{0} lookup values do not match.")]
+    #[error("Lookup values do not match: {0}")]
     InvalidLookup(String),
+    #[error("Proof has {actual} commitment trees, but the components expect {expected}.")]
+    InvalidCommitmentCount { expected: usize, actual: usize },
+    #[error(
+        "Tree {tree} of the proof has {actual} columns, but the components expect {expected}."
+    )]
+    InvalidTreeShape {
+        tree: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("The proof is of another statement than the expected one.")]
+    StatementMismatch,
+    #[error("The claimed sums of lookup relation {relation} add up to {sum}, instead of zero.")]
+    LogupImbalance { relation: String, sum: SecureField },
+    #[error(
+        "The config gives {security_bits} bits of security, below the minimum of \
+         {min_security_bits}."
+    )]
+    InsufficientSecurity {
+        security_bits: u32,
+        min_security_bits: u32,
+    },
     #[error(transparent)]
     Merkle(#[from] MerkleVerificationError),
     #[error(
@@ -236,6 +522,8 @@ pub enum VerificationError {
     OodsNotMatching,
     #[error(transparent)]
     Fri(#[from] FriVerificationError),
+    #[error("GKR proof of the lookups is invalid: {0}.")]
+    Gkr(#[from] GkrError),
     #[error("Proof of work verification failed.")]
     ProofOfWork,
 }
@@ -0,0 +1,345 @@
+use itertools::{zip_eq, Itertools};
+use tracing::{span, Level};
+
+use super::{check_gkr_proof, check_proof_layout, check_statement, StarkProof, VerificationError};
+use crate::core::backend::{BackendForChannel, CpuBackend};
+use crate::core::channel::{Channel, MerkleChannel};
+use crate::core::lookups::gkr_lookups::{prove_lookups, verify_lookups, GkrLookup, GkrLookups};
+use crate::core::lookups::gkr_prover::Layer;
+use crate::core::lookups::gkr_verifier::{Gate, GkrBatchProof};
+use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, TreeBuilder, TreeVec};
+use crate::core::{ColumnVec, InteractionElementId, InteractionElements, Statement};
+
+/// A phase of the interaction between the prover and the verifier: the prover commits on a tree of
+/// trace columns, then both sides draw the challenges the next phases depend on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Phase {
+    /// The log degree bounds of the columns of the committed tree, see
+    /// [crate::core::air::Components::column_log_sizes].
+    pub column_log_sizes: ColumnVec<u32>,
+    /// The challenges drawn from the channel once the tree is committed, in order.
+    pub challenges: Vec<InteractionElementId>,
+    /// The lookups proven with GKR once the challenges are drawn, see
+    /// [crate::core::lookups::gkr_lookups]. The trees of the next phases may depend on the claims
+    /// the GKR proof leaves on them.
+    pub gkr_lookups: Vec<GkrLookup>,
+}
+
+impl Phase {
+    pub fn new(column_log_sizes: ColumnVec<u32>, challenges: Vec<InteractionElementId>) -> Self {
+        Self {
+            column_log_sizes,
+            challenges,
+            gkr_lookups: vec![],
+        }
+    }
+
+    /// Returns the phase, proving `gkr_lookups` once its challenges are drawn.
+    pub fn with_gkr_lookups(self, gkr_lookups: Vec<GkrLookup>) -> Self {
+        Self {
+            gkr_lookups,
+            ..self
+        }
+    }
+
+    /// Draws the challenges of the phase, all at once. A phase without challenges leaves the
+    /// channel as is.
+    fn draw_challenges(&self, channel: &mut impl Channel) -> InteractionElements {
+        if self.challenges.is_empty() {
+            return InteractionElements::default();
+        }
+        let values = channel.draw_felts(self.challenges.len());
+        InteractionElements::new(zip_eq(self.challenges.iter().cloned(), values).collect())
+    }
+}
+
+/// The challenges drawn in a session, from which the components are built.
+#[derive(Default)]
+pub struct SessionChallenges {
+    /// The challenges of the phases.
+    pub interaction_elements: InteractionElements,
+    /// The claims the GKR proof leaves on the lookups, once they are proven. See
+    /// [Phase::gkr_lookups].
+    pub gkr_lookups: Option<GkrLookups>,
+}
+
+/// Adds the columns of a phase to the tree builder, from the challenges drawn in the previous
+/// phases, see [Session::commit_prover].
+pub type TraceGenerator<'a, B, MC> =
+    Box<dyn FnOnce(&SessionChallenges, &mut TreeBuilder<'_, '_, B, MC>) + 'a>;
+
+/// Returns the input layers of the lookups proven with GKR, from the challenges drawn so far, in
+/// the order of [Phase::gkr_lookups]. See [Session::commit_prover].
+pub type LookupLayersGenerator<'a> =
+    Box<dyn FnOnce(&InteractionElements) -> Vec<Layer<CpuBackend>> + 'a>;
+
+/// The trace commitment phases of a proof, in order, starting with the preprocessed trace.
+///
+/// The same session drives both the prover, see [Self::commit_prover], and the verifier, see
+/// [Self::commit_verifier], so that both sides mix and draw the same transcript.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    pub phases: Vec<Phase>,
+}
+
+impl Session {
+    pub fn new(phases: Vec<Phase>) -> Self {
+        Self { phases }
+    }
+
+    /// Returns the log degree bounds of the columns of each trace tree.
+    pub fn column_log_sizes(&self) -> TreeVec<ColumnVec<u32>> {
+        TreeVec::new(
+            self.phases
+                .iter()
+                .map(|phase| phase.column_log_sizes.clone())
+                .collect(),
+        )
+    }
+
+    /// Returns whether a phase proves lookups with GKR.
+    ///
+    /// # Panics
+    ///
+    /// Panics if several phases do, as a proof holds a single GKR proof.
+    fn has_gkr_lookups(&self) -> bool {
+        let n_gkr_phases = self
+            .phases
+            .iter()
+            .filter(|phase| !phase.gkr_lookups.is_empty())
+            .count();
+        assert!(
+            n_gkr_phases <= 1,
+            "The lookups of a session are proven with GKR in a single phase."
+        );
+        n_gkr_phases == 1
+    }
+
+    /// Commits on the trace trees of all the phases, after mixing `statement` into the channel.
+    ///
+    /// `trace_generators` holds the trace generator of each phase, in order. Each is invoked with
+    /// the challenges drawn so far. The input layers of the lookups proven with GKR, if any, are
+    /// generated by `lookup_layers` once the challenges of their phase are drawn.
+    ///
+    /// Returns all the drawn challenges, to build the components proved with [super::prove], and
+    /// the GKR proof of the lookups, to record in the proof.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is not one trace generator per phase, if the columns committed in a phase do
+    /// not have the log sizes the phase describes, or if `lookup_layers` is missing, unexpected,
+    /// or does not generate the lookups the session describes.
+    pub fn commit_prover<B: BackendForChannel<MC>, MC: MerkleChannel>(
+        &self,
+        statement: &Statement,
+        channel: &mut MC::C,
+        commitment_scheme: &mut CommitmentSchemeProver<'_, B, MC>,
+        trace_generators: Vec<TraceGenerator<'_, B, MC>>,
+        mut lookup_layers: Option<LookupLayersGenerator<'_>>,
+    ) -> (SessionChallenges, Option<GkrBatchProof>) {
+        assert_eq!(
+            self.has_gkr_lookups(),
+            lookup_layers.is_some(),
+            "Lookup layers are generated if and only if the session proves lookups with GKR."
+        );
+        let mut challenges = SessionChallenges::default();
+        let mut gkr_proof = None;
+        statement.mix_into(channel);
+        for (phase_index, (phase, gen_trace)) in zip_eq(&self.phases, trace_generators).enumerate()
+        {
+            let _span = span!(Level::INFO, "Phase", phase_index).entered();
+            let mut tree_builder = commitment_scheme.tree_builder();
+            gen_trace(&challenges, &mut tree_builder);
+            tree_builder.commit(channel);
+
+            let tree = commitment_scheme.trees.last().unwrap();
+            let log_sizes = tree
+                .polynomials
+                .iter()
+                .map(|poly| poly.log_size())
+                .collect_vec();
+            assert_eq!(
+                log_sizes, phase.column_log_sizes,
+                "Columns committed in phase {phase_index} do not match the session."
+            );
+
+            challenges
+                .interaction_elements
+                .extend(phase.draw_challenges(channel));
+
+            if !phase.gkr_lookups.is_empty() {
+                let _span = span!(Level::INFO, "GKR", phase_index).entered();
+                let gen_layers = lookup_layers.take().unwrap();
+                let input_layers = gen_layers(&challenges.interaction_elements);
+                let lookups = input_layers
+                    .iter()
+                    .map(|layer| GkrLookup {
+                        gate: match layer {
+                            Layer::LogUpSingles { .. } => Gate::LogUpSingles,
+                            _ => Gate::LogUp,
+                        },
+                        log_size: layer.n_variables() as u32,
+                    })
+                    .collect_vec();
+                assert_eq!(
+                    lookups, phase.gkr_lookups,
+                    "Lookups proven in phase {phase_index} do not match the session."
+                );
+                let (proof, gkr_lookups) = prove_lookups(channel, input_layers);
+                gkr_proof = Some(proof);
+                challenges.gkr_lookups = Some(gkr_lookups);
+            }
+        }
+        (challenges, gkr_proof)
+    }
+
+    /// Reads the trace commitments of `proof` into `commitment_scheme`, after mixing the expected
+    /// `statement` into the channel, drawing the challenges of each phase and verifying the GKR
+    /// proof of the lookups as the prover did. A proof of another statement, or whose GKR proof is
+    /// missing or unexpected, is rejected.
+    ///
+    /// Returns all the drawn challenges, to build the components verified with [super::verify].
+    /// `commitment_scheme` is expected to hold no trees yet.
+    pub fn commit_verifier<MC: MerkleChannel>(
+        &self,
+        statement: &Statement,
+        channel: &mut MC::C,
+        commitment_scheme: &mut CommitmentSchemeVerifier<MC>,
+        proof: &StarkProof<MC::H>,
+    ) -> Result<SessionChallenges, VerificationError> {
+        check_proof_layout(&self.column_log_sizes(), proof)?;
+        check_statement(statement, proof)?;
+        check_gkr_proof(self.has_gkr_lookups(), proof)?;
+
+        let mut challenges = SessionChallenges::default();
+        statement.mix_into(channel);
+        for (phase, commitment) in zip_eq(&self.phases, &proof.commitments[..self.phases.len()]) {
+            commitment_scheme.commit(commitment.clone(), &phase.column_log_sizes, channel);
+            challenges
+                .interaction_elements
+                .extend(phase.draw_challenges(channel));
+
+            if !phase.gkr_lookups.is_empty() {
+                // The GKR proof is present, see [check_gkr_proof].
+                let gkr_proof = proof.gkr_proof.as_ref().unwrap();
+                challenges.gkr_lookups =
+                    Some(verify_lookups(channel, &phase.gkr_lookups, gkr_proof)?);
+            }
+        }
+        Ok(challenges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use num_traits::Zero;
+
+    use super::{Phase, Session};
+    use crate::core::backend::CpuBackend;
+    use crate::core::channel::Blake3Channel;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::pcs::{CommitmentSchemeProver, PcsConfig};
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, PolyOps};
+    use crate::core::poly::BitReversedOrder;
+    use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+    use crate::core::{InteractionElementId, Statement};
+
+    const LOG_SIZE: u32 = 4;
+
+    fn element_id(name: &str) -> InteractionElementId {
+        InteractionElementId(name.to_string())
+    }
+
+    fn test_session() -> Session {
+        Session::new(vec![
+            Phase::new(
+                vec![LOG_SIZE; 2],
+                vec![element_id("z"), element_id("alpha")],
+            ),
+            Phase::new(vec![LOG_SIZE], vec![]),
+        ])
+    }
+
+    fn gen_columns(
+        n_columns: usize,
+    ) -> Vec<CircleEvaluation<CpuBackend, BaseField, BitReversedOrder>> {
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        (0..n_columns)
+            .map(|i| {
+                let values = (0..1 << LOG_SIZE).map(|j| BaseField::from(i + j)).collect();
+                CircleEvaluation::new(domain, values)
+            })
+            .collect_vec()
+    }
+
+    #[test]
+    fn test_commit_prover_draws_challenges_in_order() {
+        let config = PcsConfig::default();
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<_, Blake3MerkleChannel>::new(config, &twiddles);
+        let mut first_phase_z = None;
+        let mut second_phase_z = None;
+
+        let (challenges, gkr_proof) = test_session().commit_prover(
+            &Statement::default(),
+            channel,
+            commitment_scheme,
+            vec![
+                Box::new(|challenges, tree_builder| {
+                    first_phase_z = challenges.interaction_elements.get(&element_id("z")).ok();
+                    tree_builder.extend_evals(gen_columns(2), LOG_SIZE);
+                }),
+                Box::new(|challenges, tree_builder| {
+                    second_phase_z = challenges.interaction_elements.get(&element_id("z")).ok();
+                    tree_builder.extend_evals(gen_columns(1), LOG_SIZE);
+                }),
+            ],
+            None,
+        );
+
+        let elements = &challenges.interaction_elements;
+        let z = elements.get(&element_id("z")).unwrap();
+        assert_eq!([first_phase_z, second_phase_z], [None, Some(z)]);
+        assert_ne!(z, SecureField::zero());
+        assert_ne!(elements.get(&element_id("alpha")).unwrap(), z);
+        assert_eq!(commitment_scheme.trees.len(), 2);
+        assert!(gkr_proof.is_none() && challenges.gkr_lookups.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "Columns committed in phase 1 do not match the session.")]
+    fn test_commit_prover_rejects_unexpected_columns() {
+        let config = PcsConfig::default();
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + config.fri_config.log_blowup_factor)
+                .circle_domain()
+                .half_coset,
+        );
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme =
+            &mut CommitmentSchemeProver::<_, Blake3MerkleChannel>::new(config, &twiddles);
+
+        test_session().commit_prover(
+            &Statement::default(),
+            channel,
+            commitment_scheme,
+            vec![
+                Box::new(|_, tree_builder| {
+                    tree_builder.extend_evals(gen_columns(2), LOG_SIZE);
+                }),
+                Box::new(|_, tree_builder| {
+                    tree_builder.extend_evals(gen_columns(2), LOG_SIZE);
+                }),
+            ],
+            None,
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use std::collections::BTreeSet;
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
 use itertools::Itertools;
 
@@ -61,6 +61,20 @@ impl Queries {
         }
     }
 
+    /// Groups the queries by the folding coset they reside in, where a folding coset is a range of
+    /// `2^n_folds` consecutive positions that get folded into a single position by
+    /// [`Queries::fold`].
+    ///
+    /// Returns the positions of each coset along with the queries inside it.
+    pub fn folding_cosets(&self, n_folds: u32) -> impl Iterator<Item = (Range<usize>, &[usize])> {
+        self.chunk_by(move |a, b| a >> n_folds == b >> n_folds)
+            .map(move |coset_queries| {
+                let coset_start = (coset_queries[0] >> n_folds) << n_folds;
+                let coset_end = coset_start + (1 << n_folds);
+                (coset_start..coset_end, coset_queries)
+            })
+    }
+
     pub fn opening_positions(&self, fri_step_size: u32) -> SparseSubCircleDomain {
         assert!(fri_step_size > 0);
         SparseSubCircleDomain {
@@ -132,6 +146,8 @@ impl SubCircleDomain {
 
 #[cfg(test)]
 mod tests {
+    use std::iter::zip;
+
     use crate::core::channel::sha256::Sha256Channel;
     use crate::core::poly::circle::CanonicCoset;
     use crate::core::queries::Queries;
@@ -184,6 +200,28 @@ mod tests {
         }
     }
 
+    #[test]
+    pub fn test_folding_cosets() {
+        let log_domain_size = 7;
+        let n_folds = 3;
+        let queries = Queries::from_positions(vec![1, 2, 7, 9, 30, 31, 32], log_domain_size);
+
+        let cosets = queries.folding_cosets(n_folds).collect::<Vec<_>>();
+
+        assert_eq!(
+            cosets,
+            [
+                (0..8, &[1, 2, 7][..]),
+                (8..16, &[9][..]),
+                (24..32, &[30, 31][..]),
+                (32..40, &[32][..]),
+            ]
+        );
+        let folded_queries = queries.fold(n_folds);
+        assert!(zip(&cosets, folded_queries.iter())
+            .all(|((coset, _), &folded_query)| coset.start >> n_folds == folded_query));
+    }
+
     #[test]
     pub fn test_conjugate_queries() {
         let channel = &mut Sha256Channel::default();
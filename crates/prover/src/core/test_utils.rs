@@ -1,6 +1,8 @@
 use super::backend::cpu::CpuCircleEvaluation;
 use super::fields::m31::BaseField;
 use super::fields::qm31::SecureField;
+use super::fri::FriConfig;
+use super::pcs::PcsConfig;
 use crate::core::channel::sha256::Sha256Channel;
 
 pub fn secure_eval_to_base_eval<EvalOrder>(
@@ -15,3 +17,12 @@ pub fn secure_eval_to_base_eval<EvalOrder>(
 pub fn test_channel() -> Sha256Channel {
     Sha256Channel::default()
 }
+
+/// A config with a small proof of work and enough queries, for fast tests.
+pub fn test_pcs_config() -> PcsConfig {
+    PcsConfig {
+        pow_bits: 10,
+        fri_config: FriConfig::new(0, 4, 64),
+        log_merkle_cap_size: 0,
+    }
+}
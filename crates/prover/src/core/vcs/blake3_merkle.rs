@@ -139,8 +139,9 @@ mod tests {
         let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
 
         let verifier = MerkleVerifier {
-            root: merkle.root(),
+            cap: merkle.cap(),
             column_log_sizes: log_sizes,
+            log_cap_size: 0,
         };
         (queries, decommitment, values, verifier)
     }
@@ -217,4 +218,109 @@ mod tests {
             MerkleVerificationError::WitnessTooLong
         );
     }
+
+    #[test]
+    fn test_merkle_with_cap_success() {
+        const LOG_CAP_SIZE: u32 = 4;
+        let cols = (0..4)
+            .map(|i| (0..1u32 << (5 + i)).map(BaseField::from).collect_vec())
+            .collect_vec();
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle = MerkleProver::<CpuBackend, Blake3MerkleHasher>::commit_with_cap(
+            cols.iter().collect_vec(),
+            LOG_CAP_SIZE,
+        );
+        let queries = BTreeMap::from([(8, vec![3, 100, 200]), (5, vec![7])]);
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+        let full_decommitment =
+            MerkleProver::<CpuBackend, Blake3MerkleHasher>::commit(cols.iter().collect_vec())
+                .decommit(queries.clone(), cols.iter().collect_vec())
+                .1;
+        let verifier = MerkleVerifier::new(merkle.cap(), log_sizes, LOG_CAP_SIZE);
+
+        assert_eq!(merkle.cap().len(), 1 << LOG_CAP_SIZE);
+        assert!(decommitment.hash_witness.len() < full_decommitment.hash_witness.len());
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_cap_lowered_to_smallest_column() {
+        let cols = [
+            vec![BaseField::zero(); 1 << 6],
+            vec![BaseField::zero(); 1 << 2],
+        ];
+        let merkle = MerkleProver::<CpuBackend, Blake3MerkleHasher>::commit_with_cap(
+            cols.iter().collect_vec(),
+            4,
+        );
+
+        assert_eq!(merkle.cap().len(), 1 << 2);
+    }
+
+    #[test]
+    fn test_merkle_invalid_cap_size() {
+        let (queries, decommitment, values, mut verifier) = prepare_merkle();
+        verifier.cap = vec![Blake3Hash::default(); 3];
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::InvalidCapSize
+        );
+    }
+
+    #[test]
+    fn test_merkle_invalid_cap_size_without_queries() {
+        let (_, _, _, mut verifier) = prepare_merkle();
+        verifier.cap = vec![Blake3Hash::default(); 2];
+        let mut empty_verifier = MerkleVerifier::<Blake3MerkleHasher>::new(vec![], vec![], 0);
+        empty_verifier.cap = vec![Blake3Hash::default(); 2];
+
+        for verifier in [verifier, empty_verifier] {
+            let n_columns = verifier.column_log_sizes.len();
+            assert_eq!(
+                verifier
+                    .verify(
+                        BTreeMap::new(),
+                        vec![vec![]; n_columns],
+                        MerkleDecommitment {
+                            hash_witness: vec![],
+                            column_witness: vec![],
+                        },
+                    )
+                    .unwrap_err(),
+                MerkleVerificationError::InvalidCapSize
+            );
+        }
+    }
+
+    #[test]
+    fn test_merkle_unexpected_cap_size() {
+        let cols = (0..2)
+            .map(|i| (0..1u32 << (5 + i)).map(BaseField::from).collect_vec())
+            .collect_vec();
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle = MerkleProver::<CpuBackend, Blake3MerkleHasher>::commit_with_cap(
+            cols.iter().collect_vec(),
+            2,
+        );
+        let queries = BTreeMap::from([(6, vec![3])]);
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+        let verifier = MerkleVerifier::new(merkle.cap(), log_sizes, 0);
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::InvalidCapSize
+        );
+    }
+
+    #[test]
+    fn test_merkle_empty_tree() {
+        let merkle = MerkleProver::<CpuBackend, Blake3MerkleHasher>::commit(vec![]);
+        let queries = BTreeMap::from([(6, vec![1, 5])]);
+        let (values, decommitment) = merkle.decommit(queries.clone(), vec![]);
+        let verifier = MerkleVerifier::<Blake3MerkleHasher>::new(merkle.cap(), vec![], 0);
+
+        assert_eq!(merkle.root(), Blake3Hash::default());
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
 }
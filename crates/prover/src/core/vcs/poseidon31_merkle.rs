@@ -64,3 +64,45 @@ impl MerkleChannel for Poseidon31MerkleChannel {
         channel.mix_felts(&[r1, r2]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use num_traits::Zero;
+
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::poseidon31_hash::Poseidon31Hash;
+    use crate::core::vcs::poseidon31_merkle::Poseidon31MerkleHasher;
+    use crate::core::vcs::test_utils::prepare_merkle;
+    use crate::core::vcs::verifier::MerkleVerificationError;
+
+    #[test]
+    fn test_merkle_success() {
+        let (queries, decommitment, values, verifier) = prepare_merkle::<Poseidon31MerkleHasher>();
+
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_invalid_witness() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        decommitment.hash_witness[4] = Poseidon31Hash::default();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[test]
+    fn test_merkle_invalid_value() {
+        let (queries, decommitment, mut values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        values[3][2] = BaseField::zero();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+}
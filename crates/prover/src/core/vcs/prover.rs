@@ -17,6 +17,9 @@ pub struct MerkleProver<B: MerkleOps<H>, H: MerkleHasher> {
     /// The last layer is the largest layer.
     /// See [MerkleOps::commit_on_layer] for more details.
     pub layers: Vec<Col<B, H::Hash>>,
+    /// Log size of the cap layer, whose nodes are sent as the commitment instead of the root.
+    /// Decommitments stop at this layer. See [MerkleProver::cap].
+    pub log_cap_size: u32,
 }
 /// The MerkleProver struct represents a prover for a Merkle commitment scheme.
 /// It is generic over the types `B` and `H`, which represent the Merkle operations and Merkle
@@ -24,6 +27,7 @@ pub struct MerkleProver<B: MerkleOps<H>, H: MerkleHasher> {
 impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
     /// Commits to columns.
     /// Columns must be of power of 2 sizes.
+    /// Committing to no columns yields an empty tree, with no layers.
     ///
     /// # Arguments
     ///
@@ -31,14 +35,27 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
     ///
     /// # Panics
     ///
-    /// This function will panic if the columns are not sorted in descending order or if the columns
-    /// vector is empty.
+    /// This function will panic if the columns are not sorted in descending order.
     ///
     /// # Returns
     ///
     /// A new instance of `MerkleProver` with the committed layers.
     pub fn commit(columns: Vec<&Col<B, BaseField>>) -> Self {
-        assert!(!columns.is_empty());
+        Self::commit_with_cap(columns, 0)
+    }
+
+    /// Commits to columns, with a cap of `2^log_cap_size` nodes as the commitment instead of the
+    /// root.
+    ///
+    /// Nodes above the cap would hash the columns smaller than the cap without being decommitted,
+    /// so the cap is lowered to the log size of the smallest column if needed.
+    pub fn commit_with_cap(columns: Vec<&Col<B, BaseField>>, log_cap_size: u32) -> Self {
+        if columns.is_empty() {
+            return Self {
+                layers: vec![],
+                log_cap_size: 0,
+            };
+        }
 
         let columns = &mut columns
             .into_iter()
@@ -47,16 +64,23 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
         let mut layers: Vec<Col<B, H::Hash>> = Vec::new();
 
         let max_log_size = columns.peek().unwrap().len().ilog2();
+        let mut min_log_size = max_log_size;
         for log_size in (0..=max_log_size).rev() {
             // Take columns of the current log_size.
             let layer_columns = columns
                 .peek_take_while(|column| column.len().ilog2() == log_size)
                 .collect_vec();
+            if !layer_columns.is_empty() {
+                min_log_size = log_size;
+            }
 
             layers.push(B::commit_on_layer(log_size, layers.last(), &layer_columns));
         }
         layers.reverse();
-        Self { layers }
+        Self {
+            layers,
+            log_cap_size: log_cap_size.min(min_log_size),
+        }
     }
 
     /// Decommits to columns on the given queries.
@@ -98,7 +122,7 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
             .peekable();
 
         let mut last_layer_queries = vec![];
-        for layer_log_size in (0..self.layers.len() as u32).rev() {
+        for layer_log_size in (self.log_cap_size..self.layers.len() as u32).rev() {
             // Prepare write buffer for queried values to the current layer.
             let mut layer_queried_values = vec![];
 
@@ -162,13 +186,15 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
         queried_values_by_layer.reverse();
 
         // Rearrange returned queried values according to input, and not by layer.
-        let queried_values = Self::rearrange_queried_values(queried_values_by_layer, columns);
+        let queried_values = self.rearrange_queried_values(queried_values_by_layer, columns);
 
         (queried_values, decommitment)
     }
 
-    /// Given queried values by layer, rearranges in the order of input columns.
+    /// Given queried values by layer, from the cap layer down, rearranges in the order of input
+    /// columns.
     fn rearrange_queried_values(
+        &self,
         queried_values_by_layer: Vec<Vec<Vec<BaseField>>>,
         columns: Vec<&Col<B, BaseField>>,
     ) -> Vec<Vec<BaseField>> {
@@ -188,7 +214,7 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
             .iter()
             .map(|column| {
                 queried_values_by_layer
-                    .get_mut(column.len().ilog2() as usize)
+                    .get_mut((column.len().ilog2() - self.log_cap_size) as usize)
                     .unwrap()
                     .iter_mut()
                     .map(|x| x.next().unwrap())
@@ -198,8 +224,19 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
         queried_values
     }
 
+    /// Returns the root of the tree. The root of an empty tree is the default hash.
     pub fn root(&self) -> H::Hash {
-        self.layers.first().unwrap().at(0)
+        self.layers
+            .first()
+            .map_or_else(H::Hash::default, |layer| layer.at(0))
+    }
+
+    /// Returns the `2^log_cap_size` nodes of the cap layer, which commit to the tree in place of
+    /// the root. The cap of an empty tree is its root.
+    pub fn cap(&self) -> Vec<H::Hash> {
+        self.layers
+            .get(self.log_cap_size as usize)
+            .map_or_else(|| vec![self.root()], |layer| layer.to_cpu())
     }
 }
 
@@ -139,8 +139,9 @@ mod tests {
         let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
 
         let verifier = MerkleVerifier {
-            root: merkle.root(),
+            cap: merkle.cap(),
             column_log_sizes: log_sizes,
+            log_cap_size: 0,
         };
         (queries, decommitment, values, verifier)
     }
@@ -55,8 +55,9 @@ where
     let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
 
     let verifier = MerkleVerifier {
-        root: merkle.root(),
+        cap: merkle.cap(),
         column_log_sizes: log_sizes,
+        log_cap_size: 0,
     };
     (queries, decommitment, values, verifier)
 }
@@ -13,14 +13,21 @@ use crate::core::ColumnVec;
 
 // TODO(spapini): This struct is not necessary. Make it a function on decommitment?
 pub struct MerkleVerifier<H: MerkleHasher> {
-    pub root: H::Hash,
+    /// The nodes of the cap layer the tree is committed to, see
+    /// [MerkleProver::cap](super::prover::MerkleProver::cap). A cap of a single node is the root.
+    pub cap: Vec<H::Hash>,
     pub column_log_sizes: Vec<u32>,
+    /// The expected log size of the cap, lowered to the log size of the smallest column like the
+    /// prover does, see
+    /// [MerkleProver::commit_with_cap](super::prover::MerkleProver::commit_with_cap).
+    pub log_cap_size: u32,
 }
 impl<H: MerkleHasher> MerkleVerifier<H> {
-    pub fn new(root: H::Hash, column_log_sizes: Vec<u32>) -> Self {
+    pub fn new(cap: Vec<H::Hash>, column_log_sizes: Vec<u32>, log_cap_size: u32) -> Self {
         Self {
-            root,
+            cap,
             column_log_sizes,
+            log_cap_size,
         }
     }
     /// Verifies the decommitment of the columns.
@@ -41,7 +48,8 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
     /// * The witness is too short (missing values).
     /// * The column values are too long (not fully consumed).
     /// * The column values are too short (missing values).
-    /// * The computed root does not match the expected root.
+    /// * The cap size is not the expected one.
+    /// * The computed cap nodes do not match the expected cap.
     ///
     /// # Panics
     ///
@@ -57,7 +65,29 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
         queried_values: ColumnVec<Vec<BaseField>>,
         decommitment: MerkleDecommitment<H>,
     ) -> Result<(), MerkleVerificationError> {
-        let max_log_size = self.column_log_sizes.iter().max().copied().unwrap_or(0);
+        // The cap is checked before any query is read, so that a cap of the wrong size is rejected
+        // even when nothing is queried. A tree with no columns is committed to its root.
+        let log_cap_size = self
+            .column_log_sizes
+            .iter()
+            .min()
+            .map_or(0, |&min_log_size| self.log_cap_size.min(min_log_size));
+        if self.cap.len() != 1 << log_cap_size {
+            return Err(MerkleVerificationError::InvalidCapSize);
+        }
+        let Some(max_log_size) = self.column_log_sizes.iter().max().copied() else {
+            // The root of a tree with no columns is the default hash, and nothing is decommitted.
+            if !queried_values.is_empty() {
+                return Err(MerkleVerificationError::ColumnValuesTooLong);
+            }
+            if !decommitment.hash_witness.is_empty() || !decommitment.column_witness.is_empty() {
+                return Err(MerkleVerificationError::WitnessTooLong);
+            }
+            if self.cap[0] != H::Hash::default() {
+                return Err(MerkleVerificationError::RootMismatch);
+            }
+            return Ok(());
+        };
 
         // Prepare read buffers.
         let mut queried_values_by_layer = self
@@ -75,7 +105,7 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
         let mut column_witness = decommitment.column_witness.into_iter();
 
         let mut last_layer_hashes: Option<Vec<(usize, H::Hash)>> = None;
-        for layer_log_size in (0..=max_log_size).rev() {
+        for layer_log_size in (log_cap_size..=max_log_size).rev() {
             // Prepare read buffer for queried values to the current layer.
             let mut layer_queried_values = queried_values_by_layer
                 .peek_take_while(|(log_size, _)| *log_size == layer_log_size)
@@ -170,9 +200,10 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
             return Err(MerkleVerificationError::WitnessTooLong);
         }
 
-        let [(_, computed_root)] = last_layer_hashes.unwrap().try_into().unwrap();
-        if computed_root != self.root {
-            return Err(MerkleVerificationError::RootMismatch);
+        for (node_index, computed_hash) in last_layer_hashes.unwrap() {
+            if self.cap.get(node_index) != Some(&computed_hash) {
+                return Err(MerkleVerificationError::RootMismatch);
+            }
         }
 
         Ok(())
@@ -191,4 +222,6 @@ pub enum MerkleVerificationError {
     ColumnValuesTooShort,
     #[error("Root mismatch.")]
     RootMismatch,
+    #[error("Cap size is invalid.")]
+    InvalidCapSize,
 }
@@ -0,0 +1,443 @@
+//! The fibonacci circuit of [super], with the lookups of its wires proven by GKR instead of
+//! committed logup columns, see [crate::constraint_framework::gkr].
+use itertools::zip_eq;
+use num_traits::{One, Zero};
+use tracing::{span, Level};
+
+use super::{
+    fibonacci_circuit, fibonacci_inputs_sum, gen_circuit_column, gen_trace, preprocessed_columns,
+    PlonkCircuitTrace, PLONK_RELATION,
+};
+use crate::constraint_framework::constant_columns::{
+    PreprocessedColumn, PreprocessedColumnRegistry,
+};
+use crate::constraint_framework::gkr::{
+    gen_input_layer, gen_interaction_trace, lookup_preprocessed_columns, GkrLookupAtRow,
+};
+use crate::constraint_framework::logup::LookupElements;
+use crate::constraint_framework::{
+    EvalAtRow, FrameworkComponent, FrameworkEval, INTERACTION_TRACE_IDX, ORIGINAL_TRACE_IDX,
+    PREPROCESSED_TRACE_IDX,
+};
+use crate::core::air::{Component, Components, RelationClaimedSum};
+use crate::core::backend::simd::column::BaseColumn;
+use crate::core::backend::simd::m31::LOG_N_LANES;
+use crate::core::backend::simd::SimdBackend;
+use crate::core::backend::{BackendForChannel, Col, Column, CpuBackend};
+use crate::core::channel::MerkleChannel;
+use crate::core::fields::m31::BaseField;
+use crate::core::fields::qm31::SecureField;
+use crate::core::lookups::gkr_lookups::{GkrLookup, GkrLookupClaims};
+use crate::core::lookups::gkr_prover::Layer;
+use crate::core::lookups::gkr_verifier::Gate;
+use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig};
+use crate::core::poly::circle::{CanonicCoset, PolyOps};
+use crate::core::prover::{prove, verify, Phase, Session, StarkProof, VerificationError};
+use crate::core::{ColumnVec, InteractionElements, Statement};
+
+/// The number of lookups of the circuit, of the a, b and c wires.
+const N_LOOKUPS: usize = 3;
+
+/// The GKR gates of the lookups of the a, b and c wires, see [gen_input_layers].
+const LOOKUP_GATES: [Gate; N_LOOKUPS] = [Gate::LogUpSingles, Gate::LogUpSingles, Gate::LogUp];
+
+pub type PlonkGkrComponent = FrameworkComponent<PlonkGkrEval>;
+
+#[derive(Clone)]
+pub struct PlonkGkrEval {
+    pub log_n_rows: u32,
+    pub lookup_elements: LookupElements<2>,
+    /// The claims the GKR proof leaves on the lookups of the wires.
+    pub claims: GkrLookupClaims,
+    /// The sums of the lookups of the wires, proven by GKR.
+    pub claimed_sums: [SecureField; N_LOOKUPS],
+    /// The indices of [gkr_preprocessed_columns] in the preprocessed trace.
+    pub preprocessed_column_indices: ColumnVec<usize>,
+}
+
+/// Returns the preprocessed columns read by [PlonkGkrEval], in order: the circuit columns,
+/// then the columns checking the GKR claims.
+pub fn gkr_preprocessed_columns(log_n_rows: u32) -> Vec<PreprocessedColumn> {
+    preprocessed_columns(log_n_rows)
+        .into_iter()
+        .chain(lookup_preprocessed_columns(log_n_rows))
+        .collect()
+}
+
+impl PlonkGkrEval {
+    /// Returns the constraints of `2^log_n_rows` gates, adding the preprocessed columns they read
+    /// to `registry`.
+    pub fn new(
+        log_n_rows: u32,
+        lookup_elements: LookupElements<2>,
+        claims: GkrLookupClaims,
+        claimed_sums: [SecureField; N_LOOKUPS],
+        registry: &mut PreprocessedColumnRegistry,
+    ) -> Self {
+        let mut component = Self {
+            log_n_rows,
+            lookup_elements,
+            claims,
+            claimed_sums,
+            preprocessed_column_indices: vec![],
+        };
+        component.preprocessed_column_indices = registry.add_component(&component);
+        component
+    }
+
+    /// Returns the constraints of `2^log_n_rows` gates with placeholder lookup elements and
+    /// claims. Used to derive the column sizes of the trace before it is committed.
+    pub fn layout(log_n_rows: u32) -> Self {
+        Self::new(
+            log_n_rows,
+            LookupElements::dummy(),
+            GkrLookupClaims::dummy(log_n_rows, N_LOOKUPS),
+            [SecureField::zero(); N_LOOKUPS],
+            &mut PreprocessedColumnRegistry::new(),
+        )
+    }
+}
+
+impl FrameworkEval for PlonkGkrEval {
+    fn log_size(&self) -> u32 {
+        self.log_n_rows
+    }
+
+    fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+        self.preprocessed_column_indices.clone()
+    }
+
+    fn claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        self.claimed_sums
+            .iter()
+            .map(|&claimed_sum| RelationClaimedSum {
+                relation: PLONK_RELATION.to_string(),
+                claimed_sum,
+            })
+            .collect()
+    }
+
+    fn public_claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        // The inputs of the fibonacci circuit are consumed, but not produced by the circuit.
+        vec![RelationClaimedSum {
+            relation: PLONK_RELATION.to_string(),
+            claimed_sum: -fibonacci_inputs_sum(&self.lookup_elements),
+        }]
+    }
+
+    fn gkr_lookups(&self) -> Vec<GkrLookup> {
+        LOOKUP_GATES
+            .map(|gate| GkrLookup {
+                gate,
+                log_size: self.log_n_rows,
+            })
+            .to_vec()
+    }
+
+    fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
+        let mut lookups = GkrLookupAtRow::new(INTERACTION_TRACE_IDX, &self.claims, self.log_n_rows);
+
+        let [a_wire, b_wire, c_wire, op] = preprocessed_columns(self.log_n_rows)
+            .map(|column| eval.get_preprocessed_column(column));
+
+        let mult = eval.next_trace_mask();
+        let a_val = eval.next_trace_mask();
+        let b_val = eval.next_trace_mask();
+        let c_val = eval.next_trace_mask();
+
+        eval.add_constraint_named(
+            "gate",
+            c_val.clone()
+                - op.clone() * (a_val.clone() + b_val.clone())
+                - (E::F::one() - op) * a_val.clone() * b_val.clone(),
+        );
+
+        lookups.push_lookup(
+            &mut eval,
+            E::EF::one(),
+            &[a_wire, a_val],
+            &self.lookup_elements,
+        );
+        lookups.push_lookup(
+            &mut eval,
+            E::EF::one(),
+            &[b_wire, b_val],
+            &self.lookup_elements,
+        );
+        lookups.push_lookup(
+            &mut eval,
+            E::EF::from(-mult),
+            &[c_wire, c_val],
+            &self.lookup_elements,
+        );
+
+        lookups.finalize(&mut eval);
+        eval
+    }
+}
+
+/// Returns the GKR input layers of the lookups of the a, b and c wires, in the order
+/// [PlonkGkrEval] pushes them.
+/// The layers are on [CpuBackend], whose GKR ops support layers of any size.
+pub fn gen_input_layers(
+    circuit: &PlonkCircuitTrace,
+    lookup_elements: &LookupElements<2>,
+) -> Vec<Layer<CpuBackend>> {
+    let _span = span!(Level::INFO, "Generate input layers").entered();
+    let combine = |wire: &BaseColumn, val: &BaseColumn| -> Col<CpuBackend, SecureField> {
+        zip_eq(wire.to_cpu(), val.to_cpu())
+            .map(|(wire, val)| lookup_elements.combine::<BaseField, SecureField>(&[wire, val]))
+            .collect()
+    };
+    let neg_mult: Col<CpuBackend, BaseField> =
+        circuit.mult.to_cpu().into_iter().map(|m| -m).collect();
+    vec![
+        gen_input_layer(None, &combine(&circuit.a_wire, &circuit.a_val)),
+        gen_input_layer(None, &combine(&circuit.b_wire, &circuit.b_val)),
+        gen_input_layer(Some(&neg_mult), &combine(&circuit.c_wire, &circuit.c_val)),
+    ]
+}
+
+/// Returns the phases of [prove_fibonacci_plonk_gkr]: the preprocessed trace, the trace, from
+/// which the lookup elements are drawn and the lookups proven with GKR, and the interaction trace
+/// checking the claims of the GKR proof.
+pub fn plonk_gkr_session(log_n_rows: u32) -> Session {
+    let layout = PlonkGkrComponent::new(PlonkGkrEval::layout(log_n_rows));
+    let column_log_sizes = Components(vec![&layout]).column_log_sizes();
+    Session::new(vec![
+        Phase::new(column_log_sizes[PREPROCESSED_TRACE_IDX].clone(), vec![]),
+        Phase::new(
+            column_log_sizes[ORIGINAL_TRACE_IDX].clone(),
+            LookupElements::<2>::interaction_element_ids(PLONK_RELATION).to_vec(),
+        )
+        .with_gkr_lookups(layout.gkr_lookups()),
+        Phase::new(column_log_sizes[INTERACTION_TRACE_IDX].clone(), vec![]),
+    ])
+}
+
+pub fn prove_fibonacci_plonk_gkr<MC: MerkleChannel>(
+    log_n_rows: u32,
+    config: PcsConfig,
+) -> (PlonkGkrComponent, StarkProof<MC::H>)
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    assert!(log_n_rows >= LOG_N_LANES);
+    let circuit = fibonacci_circuit(log_n_rows);
+    let mut registry = PreprocessedColumnRegistry::new();
+    let layout = PlonkGkrComponent::new(PlonkGkrEval::new(
+        log_n_rows,
+        LookupElements::dummy(),
+        GkrLookupClaims::dummy(log_n_rows, N_LOOKUPS),
+        [SecureField::zero(); N_LOOKUPS],
+        &mut registry,
+    ));
+    // All the columns are extended to the constraint degree bound.
+    let max_degree = Components(vec![&layout]).composition_log_degree_bound();
+
+    // Precompute twiddles.
+    let span = span!(Level::INFO, "Precompute twiddles").entered();
+    let twiddles = SimdBackend::precompute_twiddles(
+        CanonicCoset::new(max_degree + config.fri_config.log_blowup_factor)
+            .circle_domain()
+            .half_coset,
+    );
+    span.exit();
+
+    // Setup protocol.
+    let channel = &mut MC::C::default();
+    let commitment_scheme = &mut CommitmentSchemeProver::new(config, &twiddles);
+
+    // Commit the preprocessed trace and the trace, prove the lookups, and commit the interaction
+    // trace, in the phases of the session.
+    let lookup_elements_of = |interaction_elements: &InteractionElements| {
+        LookupElements::from_interaction_elements(interaction_elements, PLONK_RELATION).unwrap()
+    };
+    let mut component = None;
+    let (challenges, gkr_proof) = plonk_gkr_session(log_n_rows).commit_prover(
+        &Statement::default(),
+        channel,
+        commitment_scheme,
+        vec![
+            Box::new(|_, tree_builder| {
+                let _span = span!(Level::INFO, "Constant").entered();
+                tree_builder.extend_evals(
+                    registry.gen_trace(|column| gen_circuit_column(&circuit, column)),
+                    max_degree,
+                );
+            }),
+            Box::new(|_, tree_builder| {
+                let _span = span!(Level::INFO, "Trace").entered();
+                tree_builder.extend_evals(gen_trace(log_n_rows, &circuit), max_degree);
+            }),
+            Box::new(|challenges, tree_builder| {
+                let _span = span!(Level::INFO, "Interaction").entered();
+                let lookup_elements = lookup_elements_of(&challenges.interaction_elements);
+                let lookups = challenges.gkr_lookups.as_ref().unwrap();
+                let claims = lookups.claims(0..N_LOOKUPS);
+                // The input layers were consumed by the GKR prover: they are regenerated.
+                let input_layers = gen_input_layers(&circuit, &lookup_elements);
+                tree_builder
+                    .extend_evals(gen_interaction_trace(&input_layers, &claims), max_degree);
+                component = Some(PlonkGkrComponent::new(PlonkGkrEval {
+                    lookup_elements,
+                    claims,
+                    claimed_sums: lookups.claimed_sums().try_into().unwrap(),
+                    ..layout.into_eval()
+                }));
+            }),
+        ],
+        Some(Box::new(|interaction_elements: &InteractionElements| {
+            gen_input_layers(&circuit, &lookup_elements_of(interaction_elements))
+        })),
+    );
+
+    // Prove constraints.
+    let component = component.unwrap();
+
+    let proof = prove::<SimdBackend, MC>(
+        &[&component],
+        channel,
+        &Statement::default(),
+        gkr_proof,
+        &challenges.interaction_elements,
+        commitment_scheme,
+    )
+    .unwrap();
+
+    (component, proof)
+}
+
+/// Verifies a proof of [prove_fibonacci_plonk_gkr]. The GKR proof of the lookups is verified by
+/// the session, and the component is built from the lookup elements and the GKR claims it returns.
+/// The claimed sums of the proof are checked by [verify] to be the sums proven by GKR.
+pub fn verify_fibonacci_plonk_gkr<MC: MerkleChannel>(
+    log_n_rows: u32,
+    config: PcsConfig,
+    proof: StarkProof<MC::H>,
+) -> Result<(), VerificationError> {
+    let channel = &mut MC::C::default();
+    let commitment_scheme = &mut CommitmentSchemeVerifier::<MC>::new(config);
+    let challenges = plonk_gkr_session(log_n_rows).commit_verifier(
+        &Statement::default(),
+        channel,
+        commitment_scheme,
+        &proof,
+    )?;
+    let lookup_elements =
+        LookupElements::from_interaction_elements(&challenges.interaction_elements, PLONK_RELATION)
+            .map_err(|err| VerificationError::InvalidLookup(err.to_string()))?;
+    // The session verified the GKR proof of the lookups.
+    let lookups = challenges.gkr_lookups.as_ref().unwrap();
+    let component = PlonkGkrComponent::new(PlonkGkrEval::new(
+        log_n_rows,
+        lookup_elements,
+        lookups.claims(0..N_LOOKUPS),
+        lookups.claimed_sums().try_into().unwrap(),
+        &mut PreprocessedColumnRegistry::new(),
+    ));
+
+    verify(
+        &[&component],
+        channel,
+        &challenges.interaction_elements,
+        commitment_scheme,
+        proof,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::assert_matches::assert_matches;
+
+    use num_traits::{One, Zero};
+
+    use super::{prove_fibonacci_plonk_gkr, verify_fibonacci_plonk_gkr};
+    use crate::core::air::Component;
+    use crate::core::channel::Blake3Channel;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::pcs::{CommitmentSchemeVerifier, PcsConfig};
+    use crate::core::prover::{verify, VerificationError};
+    use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+    use crate::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
+    use crate::core::Statement;
+    use crate::examples::plonk::{plonk_session, prove_fibonacci_plonk};
+
+    #[test]
+    fn test_simd_plonk_gkr_prove_blake3() {
+        let config = PcsConfig::tiny();
+
+        let (_, proof) = prove_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config);
+
+        verify_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config, proof).unwrap();
+    }
+
+    #[test]
+    fn test_simd_plonk_gkr_prove_poseidon31() {
+        let config = PcsConfig::tiny();
+
+        let (_, proof) = prove_fibonacci_plonk_gkr::<Poseidon31MerkleChannel>(6, config);
+
+        verify_fibonacci_plonk_gkr::<Poseidon31MerkleChannel>(6, config, proof).unwrap();
+    }
+
+    #[test]
+    fn test_simd_plonk_gkr_claimed_sums_balance() {
+        let (component, _) = prove_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, PcsConfig::tiny());
+
+        let sum = component
+            .claimed_sums()
+            .iter()
+            .chain(&component.public_claimed_sums())
+            .map(|claimed_sum| claimed_sum.claimed_sum)
+            .sum::<SecureField>();
+
+        assert_eq!(sum, SecureField::zero());
+    }
+
+    #[test]
+    fn test_simd_plonk_gkr_rejects_tampered_output() {
+        let config = PcsConfig::tiny();
+        let (_, mut proof) = prove_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config);
+        proof.gkr_proof.as_mut().unwrap().output_claims_by_instance[0][0] += SecureField::one();
+
+        let result = verify_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config, proof);
+
+        assert_matches!(result, Err(VerificationError::Gkr(_)));
+    }
+
+    #[test]
+    fn test_simd_plonk_gkr_rejects_missing_gkr_proof() {
+        let config = PcsConfig::tiny();
+        let (_, mut proof) = prove_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config);
+        proof.gkr_proof = None;
+
+        let result = verify_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config, proof);
+
+        assert_matches!(result, Err(VerificationError::InvalidStructure(_)));
+    }
+
+    #[test]
+    fn test_simd_plonk_rejects_unexpected_gkr_proof() {
+        let config = PcsConfig::tiny();
+        let (component, mut proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
+        let challenges = plonk_session(5)
+            .commit_verifier(&Statement::default(), channel, commitment_scheme, &proof)
+            .unwrap();
+        // The plonk component proves its lookups with logup columns, not GKR.
+        let (_, gkr_plonk_proof) = prove_fibonacci_plonk_gkr::<Blake3MerkleChannel>(5, config);
+        proof.gkr_proof = gkr_plonk_proof.gkr_proof;
+
+        let result = verify(
+            &[&component],
+            channel,
+            &challenges.interaction_elements,
+            commitment_scheme,
+            proof,
+        );
+
+        assert_matches!(result, Err(VerificationError::InvalidStructure(_)));
+    }
+}
@@ -1,55 +1,138 @@
-use itertools::{chain, Itertools};
-use num_traits::One;
+pub mod gkr;
+
+use itertools::Itertools;
+use num_traits::{One, Zero};
 use tracing::{span, Level};
 
+use crate::constraint_framework::constant_columns::{
+    PreprocessedColumn, PreprocessedColumnRegistry,
+};
 use crate::constraint_framework::logup::{LogupAtRow, LogupTraceGenerator, LookupElements};
-use crate::constraint_framework::{EvalAtRow, FrameworkComponent};
+use crate::constraint_framework::{
+    EvalAtRow, FrameworkComponent, FrameworkEval, INTERACTION_TRACE_IDX, ORIGINAL_TRACE_IDX,
+    PREPROCESSED_TRACE_IDX,
+};
+use crate::core::air::{Components, RelationClaimedSum};
 use crate::core::backend::simd::column::BaseColumn;
 use crate::core::backend::simd::m31::LOG_N_LANES;
 use crate::core::backend::simd::qm31::PackedSecureField;
 use crate::core::backend::simd::SimdBackend;
 use crate::core::backend::{BackendForChannel, Column};
 use crate::core::channel::MerkleChannel;
-use crate::core::fields::m31::{BaseField, M31};
+use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
-use crate::core::pcs::{CommitmentSchemeProver, PcsConfig};
+use crate::core::fields::FieldExpOps;
+use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig};
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, PolyOps};
 use crate::core::poly::BitReversedOrder;
-use crate::core::prover::{prove, StarkProof};
-use crate::core::{ColumnVec, InteractionElements};
+use crate::core::prover::{prove, verify, Phase, Session, StarkProof, VerificationError};
+use crate::core::{ColumnVec, Statement};
+
+pub type PlonkComponent = FrameworkComponent<PlonkEval>;
 
 #[derive(Clone)]
-pub struct PlonkComponent {
+pub struct PlonkEval {
     pub log_n_rows: u32,
     pub lookup_elements: LookupElements<2>,
     pub claimed_sum: SecureField,
+    /// The indices of [preprocessed_columns] in the preprocessed trace.
+    pub preprocessed_column_indices: ColumnVec<usize>,
+}
+
+/// The name of the lookup relation between the wires and their values.
+pub const PLONK_RELATION: &str = "plonk_wires";
+
+/// Returns the sum of the lookups of the inputs of the fibonacci circuit, which are known to the
+/// verifier: wire 0 is read once, and wire 1 twice, both with value 1.
+pub fn fibonacci_inputs_sum(lookup_elements: &LookupElements<2>) -> SecureField {
+    let [q0, q1]: [SecureField; 2] =
+        [0, 1].map(|wire| lookup_elements.combine(&[BaseField::from(wire), BaseField::one()]));
+    q0.inverse() + q1.inverse() * BaseField::from(2)
+}
+
+/// Returns the circuit columns of the preprocessed trace, in the order they are read.
+pub fn preprocessed_columns(log_n_rows: u32) -> [PreprocessedColumn; 4] {
+    ["a_wire", "b_wire", "c_wire", "op"].map(|name| PreprocessedColumn::Custom {
+        name,
+        log_size: log_n_rows,
+    })
+}
+
+impl PlonkEval {
+    /// Returns the constraints of `2^log_n_rows` gates, adding the preprocessed columns they read
+    /// to `registry`.
+    pub fn new(
+        log_n_rows: u32,
+        lookup_elements: LookupElements<2>,
+        claimed_sum: SecureField,
+        registry: &mut PreprocessedColumnRegistry,
+    ) -> Self {
+        let mut component = Self {
+            log_n_rows,
+            lookup_elements,
+            claimed_sum,
+            preprocessed_column_indices: vec![],
+        };
+        component.preprocessed_column_indices = registry.add_component(&component);
+        component
+    }
+
+    /// Returns the constraints of `2^log_n_rows` gates with placeholder lookup elements and
+    /// claimed sum. Used to derive the column sizes of the trace before it is committed.
+    pub fn layout(log_n_rows: u32) -> Self {
+        Self::new(
+            log_n_rows,
+            LookupElements::dummy(),
+            SecureField::zero(),
+            &mut PreprocessedColumnRegistry::new(),
+        )
+    }
 }
 
-impl FrameworkComponent for PlonkComponent {
+impl FrameworkEval for PlonkEval {
     fn log_size(&self) -> u32 {
         self.log_n_rows
     }
 
-    fn max_constraint_log_degree_bound(&self) -> u32 {
-        self.log_n_rows + 1
+    fn preprocessed_column_indices(&self) -> ColumnVec<usize> {
+        self.preprocessed_column_indices.clone()
+    }
+
+    fn claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        vec![RelationClaimedSum {
+            relation: PLONK_RELATION.to_string(),
+            claimed_sum: self.claimed_sum,
+        }]
+    }
+
+    fn public_claimed_sums(&self) -> Vec<RelationClaimedSum> {
+        // The inputs of the fibonacci circuit are consumed, but not produced by the circuit.
+        vec![RelationClaimedSum {
+            relation: PLONK_RELATION.to_string(),
+            claimed_sum: -fibonacci_inputs_sum(&self.lookup_elements),
+        }]
     }
 
     fn evaluate<E: EvalAtRow>(&self, mut eval: E) -> E {
-        let mut logup = LogupAtRow::<2, _>::new(1, self.claimed_sum, self.log_n_rows);
+        let mut logup =
+            LogupAtRow::<2, _>::new(INTERACTION_TRACE_IDX, self.claimed_sum, self.log_n_rows);
 
-        let [a_wire] = eval.next_interaction_mask(2, [0]);
-        let [b_wire] = eval.next_interaction_mask(2, [0]);
         // Note: c_wire could also be implicit: (self.eval.point() - M31_CIRCLE_GEN.into_ef()).x.
         //   A constant column is easier though.
-        let [c_wire] = eval.next_interaction_mask(2, [0]);
-        let [op] = eval.next_interaction_mask(2, [0]);
+        let [a_wire, b_wire, c_wire, op] = preprocessed_columns(self.log_n_rows)
+            .map(|column| eval.get_preprocessed_column(column));
 
         let mult = eval.next_trace_mask();
         let a_val = eval.next_trace_mask();
         let b_val = eval.next_trace_mask();
         let c_val = eval.next_trace_mask();
 
-        eval.add_constraint(c_val - op * (a_val + b_val) - (E::F::one() - op) * a_val * b_val);
+        eval.add_constraint_named(
+            "gate",
+            c_val.clone()
+                - op.clone() * (a_val.clone() + b_val.clone())
+                - (E::F::one() - op) * a_val.clone() * b_val.clone(),
+        );
 
         logup.push_lookup(
             &mut eval,
@@ -137,16 +220,9 @@ pub fn gen_interaction_trace(
     logup_gen.finalize()
 }
 
-pub fn prove_fibonacci_plonk<MC: MerkleChannel>(
-    log_n_rows: u32,
-    config: PcsConfig,
-) -> (PlonkComponent, StarkProof<MC::H>)
-where
-    SimdBackend: BackendForChannel<MC>,
-{
-    assert!(log_n_rows >= LOG_N_LANES);
-
-    // Prepare a fibonacci circuit.
+/// Returns the trace of a fibonacci circuit of `2^log_n_rows` gates, whose wire `i + 2` is the sum
+/// of wires `i` and `i + 1`, from the inputs `1, 1`.
+pub fn fibonacci_circuit(log_n_rows: u32) -> PlonkCircuitTrace {
     let mut fib_values = vec![BaseField::one(), BaseField::one()];
     for _ in 0..(1 << log_n_rows) {
         fib_values.push(fib_values[fib_values.len() - 1] + fib_values[fib_values.len() - 2]);
@@ -165,10 +241,66 @@ where
     circuit.mult.set((1 << log_n_rows) - 1, 0.into());
     circuit.mult.set((1 << log_n_rows) - 2, 1.into());
 
+    circuit
+}
+
+/// Generates a circuit column of the preprocessed trace, see [preprocessed_columns].
+pub fn gen_circuit_column(
+    circuit: &PlonkCircuitTrace,
+    column: &PreprocessedColumn,
+) -> CircleEvaluation<SimdBackend, BaseField, BitReversedOrder> {
+    let col = match column.name().as_str() {
+        "a_wire" => &circuit.a_wire,
+        "b_wire" => &circuit.b_wire,
+        "c_wire" => &circuit.c_wire,
+        "op" => &circuit.op,
+        name => panic!("Unknown preprocessed column {name}."),
+    };
+    CircleEvaluation::new(
+        CanonicCoset::new(column.log_size()).circle_domain(),
+        col.clone(),
+    )
+}
+
+/// Returns the commitment phases of a plonk proof: the preprocessed circuit columns, the trace, and
+/// the interaction trace. The lookup elements of [PLONK_RELATION] are drawn after the trace.
+pub fn plonk_session(log_n_rows: u32) -> Session {
+    let layout = PlonkComponent::new(PlonkEval::layout(log_n_rows));
+    let column_log_sizes = Components(vec![&layout]).column_log_sizes();
+    Session::new(vec![
+        Phase::new(column_log_sizes[PREPROCESSED_TRACE_IDX].clone(), vec![]),
+        Phase::new(
+            column_log_sizes[ORIGINAL_TRACE_IDX].clone(),
+            LookupElements::<2>::interaction_element_ids(PLONK_RELATION).to_vec(),
+        ),
+        Phase::new(column_log_sizes[INTERACTION_TRACE_IDX].clone(), vec![]),
+    ])
+}
+
+pub fn prove_fibonacci_plonk<MC: MerkleChannel>(
+    log_n_rows: u32,
+    config: PcsConfig,
+) -> (PlonkComponent, StarkProof<MC::H>)
+where
+    SimdBackend: BackendForChannel<MC>,
+{
+    assert!(log_n_rows >= LOG_N_LANES);
+
+    let circuit = fibonacci_circuit(log_n_rows);
+    let mut registry = PreprocessedColumnRegistry::new();
+    let layout = PlonkComponent::new(PlonkEval::new(
+        log_n_rows,
+        LookupElements::dummy(),
+        SecureField::zero(),
+        &mut registry,
+    ));
+    // All the columns are extended to the constraint degree bound.
+    let max_degree = Components(vec![&layout]).composition_log_degree_bound();
+
     // Precompute twiddles.
     let span = span!(Level::INFO, "Precompute twiddles").entered();
     let twiddles = SimdBackend::precompute_twiddles(
-        CanonicCoset::new(log_n_rows + config.fri_config.log_blowup_factor + 1)
+        CanonicCoset::new(max_degree + config.fri_config.log_blowup_factor)
             .circle_domain()
             .half_coset,
     );
@@ -178,55 +310,62 @@ where
     let channel = &mut MC::C::default();
     let commitment_scheme = &mut CommitmentSchemeProver::new(config, &twiddles);
 
-    // Trace.
-    let span = span!(Level::INFO, "Trace").entered();
-    let trace = gen_trace(log_n_rows, &circuit);
-    let max_degree = log_n_rows + 1;
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(trace, max_degree);
-    tree_builder.commit(channel);
-    span.exit();
-
-    // Draw lookup element.
-    let lookup_elements = LookupElements::draw(channel);
-
-    // Interaction trace.
-    let span = span!(Level::INFO, "Interaction").entered();
-    let (trace, claimed_sum) = gen_interaction_trace(log_n_rows, &circuit, &lookup_elements);
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(trace, max_degree);
-    tree_builder.commit(channel);
-    span.exit();
-
-    // Constant trace.
-    let span = span!(Level::INFO, "Constant").entered();
-    let mut tree_builder = commitment_scheme.tree_builder();
-    tree_builder.extend_evals(
-        chain!([circuit.a_wire, circuit.b_wire, circuit.c_wire, circuit.op]
-            .into_iter()
-            .map(|col| {
-                CircleEvaluation::<SimdBackend, M31, BitReversedOrder>::new(
-                    CanonicCoset::new(log_n_rows).circle_domain(),
-                    col,
+    // Commit the preprocessed trace, the trace, and the interaction trace, in the phases of the
+    // session.
+    let mut component = None;
+    let (challenges, _) = plonk_session(log_n_rows).commit_prover(
+        &Statement::default(),
+        channel,
+        commitment_scheme,
+        vec![
+            Box::new(|_, tree_builder| {
+                let _span = span!(Level::INFO, "Constant").entered();
+                tree_builder.extend_evals(
+                    registry.gen_trace(|column| gen_circuit_column(&circuit, column)),
+                    max_degree,
+                );
+            }),
+            Box::new(|_, tree_builder| {
+                let _span = span!(Level::INFO, "Trace").entered();
+                tree_builder.extend_evals(gen_trace(log_n_rows, &circuit), max_degree);
+            }),
+            Box::new(|challenges, tree_builder| {
+                let _span = span!(Level::INFO, "Interaction").entered();
+                let lookup_elements = LookupElements::from_interaction_elements(
+                    &challenges.interaction_elements,
+                    PLONK_RELATION,
+                )
+                .unwrap();
+                let (trace, claimed_sum) =
+                    gen_interaction_trace(log_n_rows, &circuit, &lookup_elements);
+                tree_builder.extend_evals(trace, max_degree);
+                let plonk_component = PlonkComponent::new(PlonkEval {
+                    lookup_elements,
+                    claimed_sum,
+                    ..layout.into_eval()
+                });
+                // Check the constraints before the last trace tree is committed.
+                #[cfg(feature = "debug_constraints")]
+                crate::core::prover::check_constraints_on_trace(
+                    &[&plonk_component],
+                    &tree_builder.trace_polys(),
                 )
-            }))
-        .collect_vec(),
-        max_degree,
+                .unwrap();
+                component = Some(plonk_component);
+            }),
+        ],
+        None,
     );
-    tree_builder.commit(channel);
-    span.exit();
 
     // Prove constraints.
-    let component = PlonkComponent {
-        log_n_rows,
-        lookup_elements,
-        claimed_sum,
-    };
+    let component = component.unwrap();
 
     let proof = prove::<SimdBackend, MC>(
         &[&component],
         channel,
-        &InteractionElements::default(),
+        &Statement::default(),
+        None,
+        &challenges.interaction_elements,
         commitment_scheme,
     )
     .unwrap();
@@ -234,22 +373,76 @@ where
     (component, proof)
 }
 
+/// Verifies a proof of [prove_fibonacci_plonk]. The component is built from the lookup elements
+/// drawn in the session and the claimed sum of the proof, whose balance with the inputs of the
+/// circuit is checked by [verify].
+pub fn verify_fibonacci_plonk<MC: MerkleChannel>(
+    log_n_rows: u32,
+    config: PcsConfig,
+    proof: StarkProof<MC::H>,
+) -> Result<(), VerificationError> {
+    let channel = &mut MC::C::default();
+    let commitment_scheme = &mut CommitmentSchemeVerifier::<MC>::new(config);
+    let challenges = plonk_session(log_n_rows).commit_verifier(
+        &Statement::default(),
+        channel,
+        commitment_scheme,
+        &proof,
+    )?;
+    let lookup_elements =
+        LookupElements::from_interaction_elements(&challenges.interaction_elements, PLONK_RELATION)
+            .map_err(|err| VerificationError::InvalidLookup(err.to_string()))?;
+    let claimed_sum = proof
+        .claimed_sums
+        .first()
+        .and_then(|sums| sums.first())
+        .map(|sum| sum.claimed_sum)
+        .ok_or_else(|| {
+            VerificationError::InvalidStructure("Missing plonk claimed sum".to_string())
+        })?;
+    let component = PlonkComponent::new(PlonkEval::new(
+        log_n_rows,
+        lookup_elements,
+        claimed_sum,
+        &mut PreprocessedColumnRegistry::new(),
+    ));
+
+    verify(
+        &[&component],
+        channel,
+        &challenges.interaction_elements,
+        commitment_scheme,
+        proof,
+    )
+}
+
 #[cfg(test)]
 mod tests {
+    use std::assert_matches::assert_matches;
     use std::env;
 
+    use num_traits::One;
+
+    use crate::constraint_framework::expr::ExprEvaluator;
     use crate::constraint_framework::logup::LookupElements;
+    use crate::constraint_framework::FrameworkEval;
+    use crate::core::air::Component;
     use crate::core::channel::blake3::Blake3Channel;
-    use crate::core::channel::poseidon31::Poseidon31Channel;
-    use crate::core::channel::Sha256Channel;
-    use crate::core::fri::FriConfig;
-    use crate::core::pcs::{CommitmentSchemeVerifier, PcsConfig, TreeVec};
-    use crate::core::prover::verify;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::pcs::{CommitmentSchemeVerifier, PcsConfig};
+    use crate::core::prover::{verify, VerificationError};
+    use crate::core::test_utils::test_pcs_config;
     use crate::core::vcs::blake3_merkle::Blake3MerkleChannel;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleChannel;
     use crate::core::vcs::poseidon31_merkle::Poseidon31MerkleChannel;
     use crate::core::vcs::sha256_merkle::Sha256MerkleChannel;
-    use crate::core::InteractionElements;
-    use crate::examples::plonk::prove_fibonacci_plonk;
+    use crate::core::{InteractionElements, LookupValueId, Statement};
+    use crate::examples::plonk::{
+        plonk_session, prove_fibonacci_plonk, verify_fibonacci_plonk, PlonkComponent, PlonkEval,
+        PLONK_RELATION,
+    };
 
     #[test_log::test]
     fn test_simd_plonk_prove_blake3() {
@@ -258,49 +451,32 @@ mod tests {
             .unwrap_or_else(|_| "5".to_string())
             .parse::<u32>()
             .unwrap();
-        let config = PcsConfig {
-            pow_bits: 10,
-            fri_config: FriConfig::new(0, 4, 64),
-        };
+        let config = test_pcs_config();
 
         // Prove.
-        let (component, proof) =
-            prove_fibonacci_plonk::<Blake3MerkleChannel>(log_n_instances, config);
+        let (_, proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(log_n_instances, config);
 
         // Verify.
-        // TODO: Create Air instance independently.
-        let channel = &mut Blake3Channel::default();
-        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
-
-        // Decommit.
-        // Retrieve the expected column sizes in each commitment interaction, from the AIR.
-        let max_degree = log_n_instances + 1;
-
-        let sizes = TreeVec::new(vec![
-            vec![max_degree; 4],
-            vec![max_degree; 8],
-            vec![max_degree; 4],
-        ]);
-
-        // Trace columns.
-        commitment_scheme.commit(proof.commitments[0], &sizes[0], channel);
-        // Draw lookup element.
-        let lookup_elements = LookupElements::<2>::draw(channel);
-        assert_eq!(lookup_elements, component.lookup_elements);
-        // TODO(spapini): Check claimed sum against first and last instances.
-        // Interaction columns.
-        commitment_scheme.commit(proof.commitments[1], &sizes[1], channel);
-        // Constant columns.
-        commitment_scheme.commit(proof.commitments[2], &sizes[2], channel);
+        verify_fibonacci_plonk::<Blake3MerkleChannel>(log_n_instances, config, proof).unwrap();
+    }
 
-        verify(
-            &[&component],
-            channel,
-            &InteractionElements::default(),
-            commitment_scheme,
-            proof,
-        )
-        .unwrap();
+    #[test]
+    fn test_simd_plonk_prove_with_merkle_cap() {
+        const LOG_MERKLE_CAP_SIZE: u32 = 3;
+        let config = PcsConfig {
+            log_merkle_cap_size: LOG_MERKLE_CAP_SIZE,
+            ..test_pcs_config()
+        };
+        let (_, proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        let fri_proof = &proof.commitment_scheme_proof.fri_proof;
+        let fri_caps = fri_proof.inner_layers.iter().map(|layer| &layer.commitment);
+        assert!(proof
+            .commitments
+            .iter()
+            .chain(fri_caps)
+            .all(|cap| cap.len() == 1 << LOG_MERKLE_CAP_SIZE));
+
+        verify_fibonacci_plonk::<Blake3MerkleChannel>(5, config, proof).unwrap();
     }
 
     #[test_log::test]
@@ -310,49 +486,13 @@ mod tests {
             .unwrap_or_else(|_| "5".to_string())
             .parse::<u32>()
             .unwrap();
-        let config = PcsConfig {
-            pow_bits: 10,
-            fri_config: FriConfig::new(0, 4, 64),
-        };
+        let config = test_pcs_config();
 
         // Prove.
-        let (component, proof) =
-            prove_fibonacci_plonk::<Sha256MerkleChannel>(log_n_instances, config);
+        let (_, proof) = prove_fibonacci_plonk::<Sha256MerkleChannel>(log_n_instances, config);
 
         // Verify.
-        // TODO: Create Air instance independently.
-        let channel = &mut Sha256Channel::default();
-        let commitment_scheme = &mut CommitmentSchemeVerifier::<Sha256MerkleChannel>::new(config);
-
-        // Decommit.
-        // Retrieve the expected column sizes in each commitment interaction, from the AIR.
-        let max_degree = log_n_instances + 1;
-
-        let sizes = TreeVec::new(vec![
-            vec![max_degree; 4],
-            vec![max_degree; 8],
-            vec![max_degree; 4],
-        ]);
-
-        // Trace columns.
-        commitment_scheme.commit(proof.commitments[0], &sizes[0], channel);
-        // Draw lookup element.
-        let lookup_elements = LookupElements::<2>::draw(channel);
-        assert_eq!(lookup_elements, component.lookup_elements);
-        // TODO(spapini): Check claimed sum against first and last instances.
-        // Interaction columns.
-        commitment_scheme.commit(proof.commitments[1], &sizes[1], channel);
-        // Constant columns.
-        commitment_scheme.commit(proof.commitments[2], &sizes[2], channel);
-
-        verify(
-            &[&component],
-            channel,
-            &InteractionElements::default(),
-            commitment_scheme,
-            proof,
-        )
-        .unwrap();
+        verify_fibonacci_plonk::<Sha256MerkleChannel>(log_n_instances, config, proof).unwrap();
     }
 
     #[test_log::test]
@@ -362,49 +502,165 @@ mod tests {
             .unwrap_or_else(|_| "5".to_string())
             .parse::<u32>()
             .unwrap();
-        let config = PcsConfig {
-            pow_bits: 10,
-            fri_config: FriConfig::new(0, 4, 64),
-        };
+        let config = test_pcs_config();
 
         // Prove.
-        let (component, proof) =
-            prove_fibonacci_plonk::<Poseidon31MerkleChannel>(log_n_instances, config);
+        let (_, proof) = prove_fibonacci_plonk::<Poseidon31MerkleChannel>(log_n_instances, config);
 
         // Verify.
-        // TODO: Create Air instance independently.
-        let channel = &mut Poseidon31Channel::default();
-        let commitment_scheme =
-            &mut CommitmentSchemeVerifier::<Poseidon31MerkleChannel>::new(config);
-
-        // Decommit.
-        // Retrieve the expected column sizes in each commitment interaction, from the AIR.
-        let max_degree = log_n_instances + 1;
-
-        let sizes = TreeVec::new(vec![
-            vec![max_degree; 4],
-            vec![max_degree; 8],
-            vec![max_degree; 4],
-        ]);
-
-        // Trace columns.
-        commitment_scheme.commit(proof.commitments[0], &sizes[0], channel);
-        // Draw lookup element.
-        let lookup_elements = LookupElements::<2>::draw(channel);
+        verify_fibonacci_plonk::<Poseidon31MerkleChannel>(log_n_instances, config, proof).unwrap();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test_log::test]
+    fn test_simd_plonk_prove_poseidon252() {
+        let config = test_pcs_config();
+
+        // Prove.
+        let (_, proof) = prove_fibonacci_plonk::<Poseidon252MerkleChannel>(5, config);
+
+        // Verify.
+        verify_fibonacci_plonk::<Poseidon252MerkleChannel>(5, config, proof).unwrap();
+    }
+
+    #[test]
+    fn test_simd_plonk_verify_with_session() {
+        let config = PcsConfig::tiny();
+        let (component, proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+
+        // Draw the lookup elements from the session, as the prover did, and build the component
+        // from them and the claimed sum of the proof.
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
+        let challenges = plonk_session(5)
+            .commit_verifier(&Statement::default(), channel, commitment_scheme, &proof)
+            .unwrap();
+        let lookup_elements = LookupElements::from_interaction_elements(
+            &challenges.interaction_elements,
+            PLONK_RELATION,
+        )
+        .unwrap();
         assert_eq!(lookup_elements, component.lookup_elements);
-        // TODO(spapini): Check claimed sum against first and last instances.
-        // Interaction columns.
-        commitment_scheme.commit(proof.commitments[1], &sizes[1], channel);
-        // Constant columns.
-        commitment_scheme.commit(proof.commitments[2], &sizes[2], channel);
+        let verifier_component = PlonkComponent::new(PlonkEval {
+            lookup_elements,
+            claimed_sum: proof.claimed_sums[0][0].claimed_sum,
+            ..component.into_eval()
+        });
 
         verify(
-            &[&component],
+            &[&verifier_component],
             channel,
-            &InteractionElements::default(),
+            &challenges.interaction_elements,
             commitment_scheme,
             proof,
         )
         .unwrap();
     }
+
+    #[test]
+    fn test_simd_plonk_session_rejects_missing_commitment() {
+        let config = PcsConfig::tiny();
+        let (_, mut proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        proof.commitments.remove(2);
+
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
+        let result = plonk_session(5).commit_verifier(
+            &Statement::default(),
+            channel,
+            commitment_scheme,
+            &proof,
+        );
+
+        assert_matches!(
+            result,
+            Err(VerificationError::InvalidCommitmentCount {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_simd_plonk_rejects_empty_commitments() {
+        let config = test_pcs_config();
+        let (component, mut proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        proof.commitments.clear();
+
+        let channel = &mut Blake3Channel::default();
+        let commitment_scheme = &mut CommitmentSchemeVerifier::<Blake3MerkleChannel>::new(config);
+        let result = verify(
+            &[&component],
+            channel,
+            &InteractionElements::default(),
+            commitment_scheme,
+            proof,
+        );
+
+        assert_matches!(
+            result,
+            Err(VerificationError::InvalidCommitmentCount {
+                expected: 4,
+                actual: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_simd_plonk_rejects_unbalanced_claimed_sum() {
+        let config = test_pcs_config();
+        let (_, mut proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        proof.claimed_sums[0][0].claimed_sum += SecureField::one();
+
+        let result = verify_fibonacci_plonk::<Blake3MerkleChannel>(5, config, proof);
+
+        assert_matches!(
+            result,
+            Err(VerificationError::LogupImbalance { relation, sum })
+                if relation == PLONK_RELATION && sum == SecureField::one()
+        );
+    }
+
+    #[test]
+    fn test_simd_plonk_rejects_unexpected_lookup_value() {
+        let config = test_pcs_config();
+        let (_, mut proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+        proof
+            .lookup_values
+            .0
+            .insert(LookupValueId("plonk_output".to_string()), BaseField::one());
+
+        let result = verify_fibonacci_plonk::<Blake3MerkleChannel>(5, config, proof);
+
+        assert_matches!(result, Err(VerificationError::InvalidLookup(_)));
+    }
+
+    #[test]
+    fn test_simd_plonk_prove_with_config_presets() {
+        for config in [PcsConfig::small(), PcsConfig::tiny()] {
+            let (_, proof) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, config);
+
+            verify_fibonacci_plonk::<Blake3MerkleChannel>(5, config, proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_plonk_constraints_expr() {
+        let (component, _) = prove_fibonacci_plonk::<Blake3MerkleChannel>(5, PcsConfig::tiny());
+
+        let eval = component.evaluate(ExprEvaluator::new(component.log_n_rows));
+
+        assert_eq!(eval.constraints.len(), component.n_constraints());
+        assert_eq!(
+            eval.preprocessed_columns,
+            ["a_wire", "b_wire", "c_wire", "op"]
+        );
+        let gate = &eval.constraints[0];
+        assert_eq!(gate.name.as_deref(), Some("gate"));
+        assert_eq!(
+            gate.expr.to_string(),
+            "((trace_1[3] - trace_0[3] * (trace_1[1] + trace_1[2])) - \
+             (1 - trace_0[3]) * trace_1[1] * trace_1[2])"
+        );
+    }
 }
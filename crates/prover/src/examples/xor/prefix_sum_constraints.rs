@@ -9,8 +9,8 @@ pub fn inclusive_prefix_sum_check<E: EvalAtRow>(
     is_first: E::F,
     at: &PrefixSumMask<E>,
 ) {
-    let prev = at.prev - is_first * final_sum;
-    eval.add_constraint(at.curr - prev - row_diff);
+    let prev = at.prev.clone() - is_first * final_sum;
+    eval.add_constraint(at.curr.clone() - prev - row_diff);
 }
 
 #[derive(Debug, Clone, Copy)]